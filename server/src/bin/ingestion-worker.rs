@@ -659,6 +659,7 @@ pub async fn bulk_upload_chunks(
             dataset_config.BM25_AVG_LEN,
             dataset_config.BM25_B,
             dataset_config.BM25_K,
+            dataset_config.BM25_LANGUAGE.as_deref(),
         )
         .into_iter()
         .map(Some)
@@ -966,6 +967,7 @@ async fn upload_chunk(
                 dataset_config.BM25_AVG_LEN,
                 dataset_config.BM25_B,
                 dataset_config.BM25_K,
+                dataset_config.BM25_LANGUAGE.as_deref(),
             )
             .first()
             .expect("Vector Must exist")
@@ -1172,6 +1174,7 @@ async fn update_chunk(
             dataset_config.BM25_AVG_LEN,
             dataset_config.BM25_B,
             dataset_config.BM25_K,
+            dataset_config.BM25_LANGUAGE.as_deref(),
         );
 
         vecs.first().cloned()
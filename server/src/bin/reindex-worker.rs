@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use diesel_async::pooled_connection::{AsyncDieselConnectionManager, ManagerConfig};
 use itertools::Itertools;
 #[allow(deprecated)]
 use qdrant_client::{
@@ -5,10 +8,13 @@ use qdrant_client::{
     Qdrant,
 };
 use trieve_server::{
-    data::models::{MigratePointMessage, MigrationMode},
+    data::models::{DatasetConfiguration, MigratePointMessage, MigrationMode, Pool},
     errors::ServiceError,
-    get_env,
-    operators::{model_operator::get_bm25_embeddings, qdrant_operator::get_qdrant_connection},
+    establish_connection, get_env,
+    operators::{
+        dataset_operator::get_datasets_by_ids_query, model_operator::get_bm25_embeddings,
+        qdrant_operator::get_qdrant_connection,
+    },
 };
 
 #[allow(clippy::print_stdout)]
@@ -33,6 +39,24 @@ async fn main() -> Result<(), ServiceError> {
         .expect("Failed to create redis pool");
 
     let web_redis_pool = actix_web::web::Data::new(redis_pool);
+
+    let database_url = get_env!("DATABASE_URL", "DATABASE_URL is not set");
+
+    let mut config = ManagerConfig::default();
+    config.custom_setup = Box::new(establish_connection);
+
+    let mgr = AsyncDieselConnectionManager::<diesel_async::AsyncPgConnection>::new_with_config(
+        database_url,
+        config,
+    );
+
+    let pool = diesel_async::pooled_connection::deadpool::Pool::builder(mgr)
+        .max_size(3)
+        .build()
+        .expect("Failed to create diesel_async pool");
+
+    let web_pool = actix_web::web::Data::new(pool);
+
     let mut connection = web_redis_pool
         .get()
         .await
@@ -124,7 +148,8 @@ async fn main() -> Result<(), ServiceError> {
         let result = match migration_message.mode {
             MigrationMode::BM25 { average_len, k, b } => {
                 migrate_bm25(
-                    qdrant_client,
+                    &qdrant_client,
+                    web_pool.clone(),
                     points,
                     migration_message.to_collection,
                     average_len,
@@ -156,14 +181,42 @@ async fn main() -> Result<(), ServiceError> {
     }
 }
 
+fn point_dataset_id(point: &RetrievedPoint) -> Option<uuid::Uuid> {
+    match point.payload.get("dataset_id") {
+        Some(qdrant::Value {
+            kind: Some(qdrant::value::Kind::StringValue(dataset_id)),
+        }) => uuid::Uuid::parse_str(dataset_id).ok(),
+        _ => None,
+    }
+}
+
 pub async fn migrate_bm25(
-    qdrant_client: Qdrant,
+    qdrant_client: &Qdrant,
+    pool: actix_web::web::Data<Pool>,
     points: Vec<RetrievedPoint>,
     to_collection: String,
     average_len: f32,
     b: f32,
     k: f32,
 ) -> Result<(), ServiceError> {
+    // Collections hold many datasets, so each point is tokenized in its own dataset's BM25_LANGUAGE
+    let dataset_ids = points
+        .iter()
+        .filter_map(point_dataset_id)
+        .unique()
+        .collect_vec();
+    let bm25_languages: HashMap<uuid::Uuid, Option<String>> =
+        get_datasets_by_ids_query(dataset_ids, pool)
+            .await?
+            .into_iter()
+            .map(|dataset| {
+                (
+                    dataset.id,
+                    DatasetConfiguration::from_json(dataset.server_configuration).BM25_LANGUAGE,
+                )
+            })
+            .collect();
+
     // Insert points into new collection
     let new_points = points
         .iter()
@@ -177,8 +230,17 @@ pub async fn migrate_bm25(
                 }
             };
 
+            let language = point_dataset_id(point)
+                .and_then(|dataset_id| bm25_languages.get(&dataset_id).cloned().flatten());
+
             // calculate bm25
-            let bm25_embeddings = get_bm25_embeddings(vec![(content, None)], average_len, b, k);
+            let bm25_embeddings = get_bm25_embeddings(
+                vec![(content, None)],
+                average_len,
+                b,
+                k,
+                language.as_deref(),
+            );
 
             let bm25_embedding = bm25_embeddings.first().expect("BM25 Vectors");
 
@@ -1,23 +1,203 @@
-use diesel_async::pooled_connection::{AsyncDieselConnectionManager, ManagerConfig};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::{BufRead, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use diesel_async::pooled_connection::{
+    AsyncDieselConnectionManager, ManagerConfig, RecyclingMethod,
+};
+use futures::{StreamExt, TryStreamExt};
 use trieve_server::{
+    data::models::{DatasetConfiguration, Pool},
     errors::ServiceError,
     establish_connection, get_env,
     operators::{
-        chunk_operator::get_pg_point_ids_from_qdrant_point_ids,
+        chunk_operator::{
+            check_pg_point_ids_query_schema, delete_chunks_without_qdrant_points_query,
+            get_chunk_counts_in_deleted_datasets_query, get_duplicate_qdrant_point_ids_query,
+            get_pg_point_ids_from_qdrant_point_ids, scroll_chunk_point_ids_query,
+        },
+        dataset_operator::{
+            get_chunk_counts_by_qdrant_collection_query, get_datasets_by_ids_query,
+            scroll_dataset_ids_query,
+        },
         qdrant_operator::{
-            delete_points_from_qdrant, get_qdrant_collections, scroll_qdrant_collection_ids,
+            check_qdrant_health, delete_points_from_qdrant, get_existing_qdrant_point_ids,
+            get_qdrant_collection_from_dataset_config, get_qdrant_collections,
+            get_qdrant_collections_info, get_qdrant_point_dataset_ids,
+            get_qdrant_point_time_stamps, get_remaining_qdrant_retry_budget,
+            qdrant_retry_budget_exhausted, scroll_qdrant_collection_ids, set_qdrant_retry_budget,
         },
     },
 };
-#[allow(clippy::print_stdout)]
-#[tokio::main]
-async fn main() -> Result<(), ServiceError> {
-    dotenvy::dotenv().ok();
 
-    let database_url = get_env!("DATABASE_URL", "DATABASE_URL is not set");
+// Exit codes, so scripts can tell failures apart. 1 is left to any other error returned from
+// main.
+const EXIT_QDRANT_UNREACHABLE: i32 = 2;
+const EXIT_POSTGRES_UNREACHABLE: i32 = 3;
+const EXIT_COLLECTION_COUNTS_DRIFTED: i32 = 4;
+const EXIT_PG_POINT_ID_MAPPING_INCONSISTENT: i32 = 5;
+const EXIT_RETRY_BUDGET_EXHAUSTED: i32 = 6;
+const EXIT_PG_ORPHAN_THRESHOLD_EXCEEDED: i32 = 7;
+const EXIT_SCHEMA_MISMATCH: i32 = 8;
+
+/// Copies the structured fields of a log line into its JSON object.
+struct JsonLogFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for JsonLogFields<'_> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let value = if let Some(value) = value.to_u64() {
+            serde_json::Value::from(value)
+        } else if let Some(value) = value.to_i64() {
+            serde_json::Value::from(value)
+        } else if let Some(value) = value.to_f64() {
+            serde_json::Value::from(value)
+        } else if let Some(value) = value.to_bool() {
+            serde_json::Value::from(value)
+        } else {
+            serde_json::Value::from(value.to_string())
+        };
+        self.0.insert(key.as_str().to_string(), value);
+
+        Ok(())
+    }
+}
+
+/// Logs plain messages by default. With `SYNC_QDRANT_LOG_FORMAT=json` every line is a JSON object
+/// with the `timestamp`, `level`, `target` and `message` of the line and its structured fields,
+/// e.g. `collection`, its counts and `elapsed_secs`, for log pipelines. Levels are read from
+/// `RUST_LOG`, falling back to info.
+fn init_logger() {
+    let json = std::env::var("SYNC_QDRANT_LOG_FORMAT")
+        .is_ok_and(|log_format| log_format.eq_ignore_ascii_case("json"));
+
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    builder.target(env_logger::Target::Stdout);
+
+    if json {
+        builder.format(|buf, record| {
+            let mut line = serde_json::Map::new();
+            line.insert(
+                "timestamp".to_string(),
+                chrono::Utc::now().to_rfc3339().into(),
+            );
+            line.insert(
+                "level".to_string(),
+                record.level().as_str().to_lowercase().into(),
+            );
+            line.insert("target".to_string(), record.target().into());
+            line.insert("message".to_string(), record.args().to_string().into());
+            let _ = record.key_values().visit(&mut JsonLogFields(&mut line));
+
+            writeln!(buf, "{}", serde_json::Value::Object(line))
+        });
+    } else {
+        builder.format(|buf, record| writeln!(buf, "{}", record.args()));
+    }
+
+    builder.init();
+}
+
+/// Aborts the run when `err` was caused by spending the whole `SYNC_QDRANT_MAX_TOTAL_RETRIES`
+/// budget, so a degraded Qdrant does not keep the sync grinding through retries.
+fn exit_if_retry_budget_exhausted(err: &ServiceError, max_total_retries: usize) {
+    if qdrant_retry_budget_exhausted() {
+        log::error!(
+            max_total_retries = max_total_retries;
+            "aborting sync, Qdrant retry budget of {} retries (SYNC_QDRANT_MAX_TOTAL_RETRIES) exhausted, Qdrant looks unhealthy: {}",
+            max_total_retries, err
+        );
+        std::process::exit(EXIT_RETRY_BUDGET_EXHAUSTED);
+    }
+}
+
+/// Strips credentials and query parameters from an endpoint url so it is safe to print.
+fn redact_endpoint(endpoint: &str) -> String {
+    match reqwest::Url::parse(endpoint) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.set_query(None);
+            url.to_string()
+        }
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+/// Connection recycling settings for the sweep's PG pools, read from the environment.
+#[derive(Debug, Clone, Copy)]
+struct PgPoolRecycling {
+    /// Connections idle for longer than this are dropped by the reaper instead of reused.
+    idle_timeout: std::time::Duration,
+    /// Connections older than this are dropped by the reaper even when they are in use often.
+    max_lifetime: std::time::Duration,
+    /// Runs a `SELECT 1` on every connection handed out by the pool so dead ones are replaced.
+    verify_on_acquire: bool,
+}
+
+impl PgPoolRecycling {
+    fn from_env() -> Self {
+        let get_secs = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|secs| secs.parse::<u64>().ok())
+                .unwrap_or(default)
+                .max(1)
+        };
+
+        PgPoolRecycling {
+            idle_timeout: std::time::Duration::from_secs(get_secs(
+                "SYNC_QDRANT_PG_IDLE_TIMEOUT_SECS",
+                300,
+            )),
+            max_lifetime: std::time::Duration::from_secs(get_secs(
+                "SYNC_QDRANT_PG_MAX_LIFETIME_SECS",
+                1800,
+            )),
+            verify_on_acquire: std::env::var("SYNC_QDRANT_PG_VERIFY_ON_ACQUIRE")
+                .map(|verify| verify != "false" && verify != "0")
+                .unwrap_or(true),
+        }
+    }
+}
+
+/// Periodically drops idle and expired connections from `pool` so a multi-hour sweep does
+/// not pick up a connection the server or a proxy already closed.
+fn spawn_pg_pool_reaper(pool: Pool, recycling: PgPoolRecycling) {
+    let interval = (recycling.idle_timeout / 2).clamp(
+        std::time::Duration::from_secs(1),
+        std::time::Duration::from_secs(30),
+    );
 
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            pool.retain(|_, metrics| {
+                metrics.last_used() < recycling.idle_timeout
+                    && metrics.age() < recycling.max_lifetime
+            });
+        }
+    });
+}
+
+fn create_pg_pool(database_url: &str, max_size: usize, recycling: PgPoolRecycling) -> Pool {
     let mut config = ManagerConfig::default();
     config.custom_setup = Box::new(establish_connection);
+    config.recycling_method = if recycling.verify_on_acquire {
+        RecyclingMethod::Verified
+    } else {
+        RecyclingMethod::Fast
+    };
 
     let mgr = AsyncDieselConnectionManager::<diesel_async::AsyncPgConnection>::new_with_config(
         database_url,
@@ -25,66 +205,1037 @@ async fn main() -> Result<(), ServiceError> {
     );
 
     let pool = diesel_async::pooled_connection::deadpool::Pool::builder(mgr)
-        .max_size(3)
+        .max_size(max_size)
         .build()
         .expect("Failed to create diesel_async pool");
 
-    let web_pool = actix_web::web::Data::new(pool.clone());
+    spawn_pg_pool_reaper(pool.clone(), recycling);
 
-    let collections = get_qdrant_collections().await?;
+    pool
+}
 
-    for collection in collections {
-        println!("starting on collection: {:?}", collection);
+/// Returns the value of a `--flag value` or `--flag=value` command line argument.
+fn get_flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(&format!("{}=", flag))
+                .map(|value| value.to_string())
+        }
+    })
+}
+
+/// Splits a scrolled batch into `concurrency` sub-queries and runs them concurrently against
+/// the pool, returning the set of qdrant point ids which still exist in PG.
+async fn get_pg_point_ids_concurrently(
+    qdrant_point_ids: &[uuid::Uuid],
+    concurrency: usize,
+    pool: actix_web::web::Data<Pool>,
+) -> Result<HashSet<uuid::Uuid>, ServiceError> {
+    if qdrant_point_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let sub_batch_size = qdrant_point_ids.len().div_ceil(concurrency).max(1);
+
+    let pg_point_ids = futures::stream::iter(
+        qdrant_point_ids
+            .chunks(sub_batch_size)
+            .map(|ids| get_pg_point_ids_from_qdrant_point_ids(ids.to_vec(), pool.clone())),
+    )
+    .buffer_unordered(concurrency)
+    .try_collect::<Vec<Vec<(uuid::Uuid, uuid::Uuid)>>>()
+    .await?
+    .into_iter()
+    .flatten()
+    .map(|(qdrant_point_id, _)| qdrant_point_id)
+    .collect::<HashSet<uuid::Uuid>>();
+
+    Ok(pg_point_ids)
+}
+
+/// Counters shared by every shard scanning the same collection so progress and totals are
+/// reported over the whole collection.
+#[derive(Default)]
+struct CollectionSyncCounters {
+    batches_processed: AtomicUsize,
+    points_scanned: AtomicUsize,
+    orphans_found: AtomicUsize,
+    orphans_exported: AtomicUsize,
+    skipped_without_time_stamp: AtomicUsize,
+}
+
+/// Writer of the `--export-orphans` file, gzip compressed when its path ends in `.gz`.
+enum OrphanExportWriter {
+    Plain(std::io::BufWriter<std::fs::File>),
+    Gzip(flate2::write::GzEncoder<std::io::BufWriter<std::fs::File>>),
+}
+
+impl OrphanExportWriter {
+    /// Appends to the file at `path`. Appending to a `.gz` file adds a gzip member, which
+    /// `open_orphan_export_reader` reads back as one stream.
+    fn open(path: &str) -> std::io::Result<Self> {
+        let file = std::io::BufWriter::new(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?,
+        );
+
+        if path.ends_with(".gz") {
+            Ok(OrphanExportWriter::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            )))
+        } else {
+            Ok(OrphanExportWriter::Plain(file))
+        }
+    }
+
+    /// Writes the gzip trailer, if any, and flushes the file.
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            OrphanExportWriter::Plain(mut file) => file.flush(),
+            OrphanExportWriter::Gzip(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for OrphanExportWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            OrphanExportWriter::Plain(file) => file.write(buf),
+            OrphanExportWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            OrphanExportWriter::Plain(file) => file.flush(),
+            OrphanExportWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Opens an `--export-orphans` file for reading, decompressing it when its path ends in `.gz`.
+fn open_orphan_export_reader(path: &str) -> std::io::Result<Box<dyn BufRead>> {
+    let file = std::fs::File::open(path)?;
+
+    if path.ends_with(".gz") {
+        Ok(Box::new(std::io::BufReader::new(
+            flate2::read::MultiGzDecoder::new(file),
+        )))
+    } else {
+        Ok(Box::new(std::io::BufReader::new(file)))
+    }
+}
 
-        let mut offset = Some(uuid::Uuid::nil().to_string());
+/// Appends `<collection>\t<point id>` lines to the `--export-orphans` file. Each batch is flushed
+/// before its points are deleted, so an interrupted run leaves a complete prefix of the orphans
+/// and rerunning appends to it.
+fn export_orphans(
+    orphan_export: &Mutex<OrphanExportWriter>,
+    collection: &str,
+    qdrant_point_ids: &[uuid::Uuid],
+) -> Result<(), ServiceError> {
+    let mut orphan_export = orphan_export.lock().map_err(|_| {
+        ServiceError::InternalServerError("Orphan export lock poisoned".to_string())
+    })?;
+
+    for point_id in qdrant_point_ids {
+        writeln!(orphan_export, "{}\t{}", collection, point_id).map_err(|err| {
+            ServiceError::InternalServerError(format!("Failed to export orphans: {}", err))
+        })?;
+    }
+
+    orphan_export.flush().map_err(|err| {
+        ServiceError::InternalServerError(format!("Failed to export orphans: {}", err))
+    })
+}
+
+/// Splits the UUID space into `shard_count` contiguous `[start, end)` ranges. The last range is
+/// open ended.
+fn get_uuid_shard_ranges(shard_count: usize) -> Vec<(uuid::Uuid, Option<uuid::Uuid>)> {
+    let shard_width = u128::MAX / shard_count as u128;
+
+    (0..shard_count)
+        .map(|shard| {
+            let start = uuid::Uuid::from_u128(shard as u128 * shard_width);
+            let end = (shard + 1 < shard_count)
+                .then(|| uuid::Uuid::from_u128((shard as u128 + 1) * shard_width));
+
+            (start, end)
+        })
+        .collect()
+}
 
-        while let Some(cur_offset) = offset {
-            let (qdrant_point_ids, new_offset) = scroll_qdrant_collection_ids(
+/// Scrolls the `[start, end)` id range of a collection with its own offset cursor and reports
+/// the points which no longer exist in PG along with the datasets they belong to, exporting them
+/// when `orphan_export` is set. They are only deleted when `delete` is set. When `web_pool` is a
+/// replica, `primary_pool` rechecks the orphans so rows the replica hasn't caught up on yet keep
+/// their points.
+#[allow(clippy::too_many_arguments)]
+async fn sync_collection_shard(
+    collection: String,
+    (start, end): (uuid::Uuid, Option<uuid::Uuid>),
+    since: Option<i64>,
+    pg_concurrency: usize,
+    progress_every: usize,
+    collection_started_at: std::time::Instant,
+    counters: &CollectionSyncCounters,
+    orphan_export: Option<&Mutex<OrphanExportWriter>>,
+    delete: bool,
+    web_pool: actix_web::web::Data<Pool>,
+    primary_pool: Option<actix_web::web::Data<Pool>>,
+) -> Result<(), ServiceError> {
+    let mut offset = Some(start.to_string());
+
+    while let Some(cur_offset) = offset {
+        let (mut qdrant_point_ids, mut new_offset) = scroll_qdrant_collection_ids(
+            collection.clone(),
+            Some(cur_offset.to_string()),
+            Some(1000),
+        )
+        .await?;
+
+        if let Some(end) = end {
+            qdrant_point_ids.retain(|point_id| *point_id < end);
+
+            let reached_end = new_offset
+                .as_ref()
+                .and_then(|new_offset| uuid::Uuid::parse_str(new_offset).ok())
+                .map_or(true, |new_offset| new_offset >= end);
+            if reached_end {
+                new_offset = None;
+            }
+        }
+
+        let pg_point_ids =
+            get_pg_point_ids_concurrently(&qdrant_point_ids, pg_concurrency, web_pool.clone())
+                .await?;
+
+        let mut qdrant_point_ids_not_in_pg = qdrant_point_ids
+            .iter()
+            .filter(|x| !pg_point_ids.contains(x))
+            .copied()
+            .collect::<Vec<uuid::Uuid>>();
+
+        if let Some(primary_pool) = primary_pool.as_ref() {
+            let primary_pg_point_ids = get_pg_point_ids_concurrently(
+                &qdrant_point_ids_not_in_pg,
+                pg_concurrency,
+                primary_pool.clone(),
+            )
+            .await?;
+            qdrant_point_ids_not_in_pg.retain(|x| !primary_pg_point_ids.contains(x));
+        }
+
+        // Filtered before orphans are counted, so orphans_found only counts the ones --since
+        // leaves to export and delete
+        if let Some(since) = since {
+            let time_stamps = get_qdrant_point_time_stamps(
+                qdrant_point_ids_not_in_pg.clone(),
                 collection.clone(),
-                Some(cur_offset.to_string()),
-                Some(1000),
             )
             .await?;
 
-            let pg_point_ids_and_datasets =
-                get_pg_point_ids_from_qdrant_point_ids(qdrant_point_ids.clone(), web_pool.clone())
-                    .await?;
+            qdrant_point_ids_not_in_pg.retain(|point_id| {
+                match time_stamps.get(point_id).copied().flatten() {
+                    Some(time_stamp) => time_stamp >= since,
+                    None => {
+                        counters
+                            .skipped_without_time_stamp
+                            .fetch_add(1, Ordering::Relaxed);
+                        false
+                    }
+                }
+            });
+        }
 
-            let pg_point_ids = pg_point_ids_and_datasets
-                .iter()
-                .map(|(x, _)| *x)
-                .collect::<Vec<uuid::Uuid>>();
+        let batches_processed = counters.batches_processed.fetch_add(1, Ordering::Relaxed) + 1;
+        let points_scanned = counters
+            .points_scanned
+            .fetch_add(qdrant_point_ids.len(), Ordering::Relaxed)
+            + qdrant_point_ids.len();
+        let orphans_found = counters
+            .orphans_found
+            .fetch_add(qdrant_point_ids_not_in_pg.len(), Ordering::Relaxed)
+            + qdrant_point_ids_not_in_pg.len();
 
-            let qdrant_point_ids_and_datasets_not_in_pg = qdrant_point_ids
-                .iter()
-                .filter(|x| !pg_point_ids.contains(x))
-                .filter_map(|x| pg_point_ids_and_datasets.iter().find(|(y, _)| y == x))
-                .cloned()
-                .collect::<Vec<(uuid::Uuid, uuid::Uuid)>>();
+        if batches_processed % progress_every == 0 {
+            log::info!(
+                collection = collection.as_str(),
+                points_scanned = points_scanned,
+                orphans_found = orphans_found,
+                elapsed_secs = collection_started_at.elapsed().as_secs();
+                "progress on collection {:?}: points scanned: {:?}, orphans found: {:?}, elapsed: {:?}s",
+                collection,
+                points_scanned,
+                orphans_found,
+                collection_started_at.elapsed().as_secs()
+            );
+        }
 
-            let qdrant_point_ids_not_in_pg = qdrant_point_ids_and_datasets_not_in_pg
-                .iter()
-                .map(|(x, _)| *x)
-                .collect::<Vec<uuid::Uuid>>();
+        if !qdrant_point_ids_not_in_pg.is_empty() {
+            let datasets_out_of_sync = get_qdrant_point_dataset_ids(
+                qdrant_point_ids_not_in_pg.clone(),
+                collection.clone(),
+            )
+            .await?
+            .into_values()
+            .collect::<BTreeSet<uuid::Uuid>>();
+
+            log::info!(
+                collection = collection.as_str(),
+                orphans = qdrant_point_ids_not_in_pg.len(),
+                datasets_out_of_sync:? = datasets_out_of_sync;
+                "len of qdrant_point_ids_not_in_pg: {:?}, {:?}",
+                qdrant_point_ids_not_in_pg.len(),
+                datasets_out_of_sync
+            );
+        }
+
+        if let Some(orphan_export) = orphan_export {
+            export_orphans(orphan_export, &collection, &qdrant_point_ids_not_in_pg)?;
+            counters
+                .orphans_exported
+                .fetch_add(qdrant_point_ids_not_in_pg.len(), Ordering::Relaxed);
+        }
+
+        if !delete {
+            offset = new_offset;
+            continue;
+        }
+
+        let deleted_count =
+            delete_points_from_qdrant(qdrant_point_ids_not_in_pg, collection.clone()).await?;
+
+        if deleted_count > 0 {
+            log::info!(
+                collection = collection.as_str(),
+                deleted = deleted_count;
+                "deleted qdrant points not in pg: {:?}",
+                deleted_count
+            );
+        }
+
+        offset = new_offset;
+    }
+
+    Ok(())
+}
+
+/// Sweeps every shard of a collection, returning the collection's counters.
+#[allow(clippy::too_many_arguments)]
+async fn sync_collection(
+    collection: String,
+    since: Option<i64>,
+    pg_concurrency: usize,
+    progress_every: usize,
+    concurrency_per_collection: usize,
+    orphan_export: Option<&Mutex<OrphanExportWriter>>,
+    delete: bool,
+    web_pool: actix_web::web::Data<Pool>,
+    primary_pool: Option<actix_web::web::Data<Pool>>,
+) -> Result<CollectionSyncCounters, ServiceError> {
+    log::info!(collection = collection.as_str(); "starting on collection: {:?}", collection);
+
+    let collection_started_at = std::time::Instant::now();
+    let counters = CollectionSyncCounters::default();
+
+    futures::future::try_join_all(
+        get_uuid_shard_ranges(concurrency_per_collection)
+            .into_iter()
+            .map(|shard_range| {
+                sync_collection_shard(
+                    collection.clone(),
+                    shard_range,
+                    since,
+                    pg_concurrency,
+                    progress_every,
+                    collection_started_at,
+                    &counters,
+                    orphan_export,
+                    delete,
+                    web_pool.clone(),
+                    primary_pool.clone(),
+                )
+            }),
+    )
+    .await?;
+
+    if concurrency_per_collection > 1 {
+        log::info!(
+            collection = collection.as_str(),
+            shards = concurrency_per_collection,
+            points_scanned = counters.points_scanned.load(Ordering::Relaxed),
+            orphans_found = counters.orphans_found.load(Ordering::Relaxed),
+            elapsed_secs = collection_started_at.elapsed().as_secs();
+            "finished collection {:?} across {:?} shards: points scanned: {:?}, orphans found: {:?}",
+            collection,
+            concurrency_per_collection,
+            counters.points_scanned.load(Ordering::Relaxed),
+            counters.orphans_found.load(Ordering::Relaxed)
+        );
+    }
+
+    Ok(counters)
+}
+
+/// Deletes the still orphaned points of a batch read back from an `--export-orphans` file.
+/// Points which exist in PG again are kept. Returns the number of deleted points.
+async fn delete_exported_orphan_batch(
+    collection: String,
+    qdrant_point_ids: Vec<uuid::Uuid>,
+    pg_concurrency: usize,
+    dry_run: bool,
+    web_pool: actix_web::web::Data<Pool>,
+) -> Result<usize, ServiceError> {
+    let pg_point_ids =
+        get_pg_point_ids_concurrently(&qdrant_point_ids, pg_concurrency, web_pool).await?;
+
+    let qdrant_point_ids_not_in_pg = qdrant_point_ids
+        .into_iter()
+        .filter(|point_id| !pg_point_ids.contains(point_id))
+        .collect::<Vec<uuid::Uuid>>();
+
+    if dry_run {
+        return Ok(0);
+    }
+
+    delete_points_from_qdrant(qdrant_point_ids_not_in_pg, collection).await
+}
+
+/// Reads an `--export-orphans` file back, e.g. after reviewing it, and deletes its points in
+/// batches of 1000 per collection. A truncated file, e.g. from an interrupted export, is read up
+/// to its last complete line.
+async fn delete_exported_orphans(
+    path: &str,
+    pg_concurrency: usize,
+    dry_run: bool,
+    web_pool: actix_web::web::Data<Pool>,
+) -> Result<(), ServiceError> {
+    let reader = open_orphan_export_reader(path).map_err(|err| {
+        ServiceError::BadRequest(format!("Failed to open exported orphans: {}", err))
+    })?;
+
+    let mut batches: HashMap<String, Vec<uuid::Uuid>> = HashMap::new();
+    let mut orphans_read = 0;
+    let mut skipped_lines = 0;
+    let mut deleted_count = 0;
 
-            let datasets_out_of_sync = qdrant_point_ids_and_datasets_not_in_pg
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                log::warn!("stopped reading exported orphans early: {}", err);
+                break;
+            }
+        };
+
+        let Some((collection, point_id)) =
+            line.split_once('\t').and_then(|(collection, point_id)| {
+                uuid::Uuid::parse_str(point_id.trim())
+                    .ok()
+                    .map(|point_id| (collection.to_string(), point_id))
+            })
+        else {
+            skipped_lines += 1;
+            continue;
+        };
+
+        orphans_read += 1;
+        let batch = batches.entry(collection.clone()).or_default();
+        batch.push(point_id);
+
+        if batch.len() >= 1000 {
+            let batch = std::mem::take(batch);
+            deleted_count += delete_exported_orphan_batch(
+                collection,
+                batch,
+                pg_concurrency,
+                dry_run,
+                web_pool.clone(),
+            )
+            .await?;
+        }
+    }
+
+    for (collection, batch) in batches {
+        deleted_count += delete_exported_orphan_batch(
+            collection,
+            batch,
+            pg_concurrency,
+            dry_run,
+            web_pool.clone(),
+        )
+        .await?;
+    }
+
+    log::info!(
+        orphans_read = orphans_read,
+        skipped_lines = skipped_lines,
+        deleted = deleted_count;
+        "exported orphans read: {:?}, skipped lines: {:?}, deleted qdrant points not in pg: {:?}",
+        orphans_read, skipped_lines, deleted_count
+    );
+
+    Ok(())
+}
+
+/// Returns the `(id, qdrant_point_id)` rows whose point is missing from the collection. Missing
+/// points are looked up a second time so a transient miss does not get a row deleted.
+async fn get_chunks_without_qdrant_points(
+    rows: Vec<(uuid::Uuid, uuid::Uuid)>,
+    qdrant_collection: &str,
+) -> Result<Vec<(uuid::Uuid, uuid::Uuid)>, ServiceError> {
+    let mut missing_rows = rows;
+    for _ in 0..2 {
+        let existing_point_ids = get_existing_qdrant_point_ids(
+            missing_rows
                 .iter()
-                .map(|(_, x)| *x)
-                .collect::<Vec<uuid::Uuid>>();
-
-            if !qdrant_point_ids_not_in_pg.is_empty() {
-                println!(
-                    "len of qdrant_point_ids_not_in_pg: {:?}, {:?}",
-                    qdrant_point_ids_not_in_pg.len(),
-                    datasets_out_of_sync
+                .map(|(_, qdrant_point_id)| *qdrant_point_id)
+                .collect(),
+            qdrant_collection.to_string(),
+        )
+        .await?;
+
+        missing_rows.retain(|(_, qdrant_point_id)| !existing_point_ids.contains(qdrant_point_id));
+        if missing_rows.is_empty() {
+            break;
+        }
+    }
+
+    Ok(missing_rows)
+}
+
+/// `--delete-pg-orphans`: deletes the chunk rows of each dataset whose qdrant_point_id no longer
+/// exists in the dataset's collection. Rows younger than `SYNC_QDRANT_PG_ORPHAN_MIN_AGE_SECS`
+/// are never touched since their points may still be being written, and locked and Qdrant only
+/// datasets are skipped. A dataset where more than `SYNC_QDRANT_MAX_PG_ORPHAN_FRACTION` of the
+/// scanned rows lack a point is left alone, as that points at a wrong collection or a Qdrant
+/// problem rather than stale rows. Each batch is deleted in its own transaction, nothing is
+/// deleted on a `dry_run`. Returns whether any dataset was skipped for exceeding the threshold.
+async fn delete_pg_orphans(
+    dry_run: bool,
+    web_pool: actix_web::web::Data<Pool>,
+) -> Result<bool, ServiceError> {
+    let max_orphan_fraction = std::env::var("SYNC_QDRANT_MAX_PG_ORPHAN_FRACTION")
+        .ok()
+        .and_then(|fraction| fraction.parse::<f64>().ok())
+        .filter(|fraction| (0.0..=1.0).contains(fraction))
+        .unwrap_or(0.01);
+    let min_age_secs = std::env::var("SYNC_QDRANT_PG_ORPHAN_MIN_AGE_SECS")
+        .ok()
+        .and_then(|secs| secs.parse::<i64>().ok())
+        .unwrap_or(86400)
+        .max(0);
+    let created_before = chrono::Utc::now().naive_utc() - chrono::Duration::seconds(min_age_secs);
+
+    let mut threshold_exceeded = false;
+    let mut total_scanned = 0;
+    let mut total_orphans = 0;
+    let mut total_deleted = 0;
+    let mut dataset_offset = uuid::Uuid::nil();
+
+    while let Some(dataset_ids) =
+        scroll_dataset_ids_query(dataset_offset, 100, web_pool.clone()).await?
+    {
+        dataset_offset = *dataset_ids
+            .last()
+            .expect("Scrolled dataset ids are never empty");
+
+        for dataset in get_datasets_by_ids_query(dataset_ids, web_pool.clone()).await? {
+            let dataset_config =
+                DatasetConfiguration::from_json(dataset.server_configuration.clone());
+            if dataset_config.QDRANT_ONLY || dataset_config.LOCKED {
+                continue;
+            }
+            let qdrant_collection = get_qdrant_collection_from_dataset_config(&dataset_config);
+
+            let mut scanned = 0;
+            let mut orphan_rows = vec![];
+            let mut after_id = None;
+            loop {
+                let rows = scroll_chunk_point_ids_query(
+                    dataset.id,
+                    created_before,
+                    after_id,
+                    1000,
+                    web_pool.clone(),
+                )
+                .await?;
+                let Some((last_id, _)) = rows.last().copied() else {
+                    break;
+                };
+
+                after_id = Some(last_id);
+                scanned += rows.len();
+                orphan_rows
+                    .extend(get_chunks_without_qdrant_points(rows, &qdrant_collection).await?);
+            }
+
+            total_scanned += scanned;
+            if orphan_rows.is_empty() {
+                continue;
+            }
+            total_orphans += orphan_rows.len();
+
+            let orphan_fraction = orphan_rows.len() as f64 / scanned as f64;
+            if orphan_fraction > max_orphan_fraction {
+                threshold_exceeded = true;
+                log::warn!(
+                    dataset_id:% = dataset.id,
+                    collection = qdrant_collection.as_str(),
+                    orphans = orphan_rows.len(),
+                    scanned = scanned,
+                    orphan_fraction = orphan_fraction;
+                    "skipping dataset {}: {} of {} chunk rows ({:.2}%) have no point in {}, above SYNC_QDRANT_MAX_PG_ORPHAN_FRACTION ({:.2}%)",
+                    dataset.id,
+                    orphan_rows.len(),
+                    scanned,
+                    orphan_fraction * 100.0,
+                    qdrant_collection,
+                    max_orphan_fraction * 100.0
+                );
+                continue;
+            }
+
+            if dry_run {
+                log::info!(
+                    dataset_id:% = dataset.id,
+                    collection = qdrant_collection.as_str(),
+                    orphans = orphan_rows.len(),
+                    scanned = scanned;
+                    "dataset {}: would delete {:?} of {:?} chunk rows without a qdrant point",
+                    dataset.id,
+                    orphan_rows.len(),
+                    scanned
                 );
+                continue;
+            }
 
-                delete_points_from_qdrant(qdrant_point_ids_not_in_pg, collection.clone()).await?;
+            let mut deleted = 0;
+            for batch in orphan_rows.chunks(1000) {
+                let (chunk_ids, qdrant_point_ids) = batch.iter().copied().unzip();
+                deleted += delete_chunks_without_qdrant_points_query(
+                    chunk_ids,
+                    qdrant_point_ids,
+                    created_before,
+                    dataset.id,
+                    web_pool.clone(),
+                )
+                .await?;
             }
+            total_deleted += deleted;
 
-            offset = new_offset;
+            log::info!(
+                dataset_id:% = dataset.id,
+                collection = qdrant_collection.as_str(),
+                deleted = deleted,
+                scanned = scanned;
+                "dataset {}: deleted {:?} of {:?} chunk rows without a qdrant point",
+                dataset.id, deleted, scanned
+            );
         }
     }
 
+    log::info!(
+        scanned = total_scanned,
+        orphans = total_orphans,
+        deleted = total_deleted;
+        "pg chunk rows scanned: {:?}, without a qdrant point: {:?}, deleted: {:?}",
+        total_scanned, total_orphans, total_deleted
+    );
+
+    Ok(threshold_exceeded)
+}
+
+/// Compares each collection's Qdrant point count against the PG chunk count of the datasets
+/// stored in it without scrolling any points. Returns the number of collections whose counts
+/// differ, which are worth a full sweep. Qdrant point counts are approximate.
+async fn report_collection_counts(
+    web_pool: actix_web::web::Data<Pool>,
+) -> Result<usize, ServiceError> {
+    let qdrant_point_counts = get_qdrant_collections_info()
+        .await?
+        .into_iter()
+        .map(|collection_info| {
+            (
+                collection_info.name,
+                collection_info.points_count.unwrap_or(0) as i64,
+            )
+        })
+        .collect::<HashMap<String, i64>>();
+    let pg_chunk_counts = get_chunk_counts_by_qdrant_collection_query(web_pool).await?;
+
+    let collections = qdrant_point_counts
+        .keys()
+        .chain(pg_chunk_counts.keys())
+        .collect::<BTreeSet<&String>>();
+
+    let mut drifted_collections = 0;
+    for collection in collections {
+        let qdrant_points = qdrant_point_counts.get(collection).copied().unwrap_or(0);
+        let pg_chunks = pg_chunk_counts.get(collection).copied().unwrap_or(0);
+        let delta = qdrant_points - pg_chunks;
+
+        if delta != 0 {
+            drifted_collections += 1;
+        }
+
+        log::info!(
+            collection = collection.as_str(),
+            qdrant_points = qdrant_points,
+            pg_chunks = pg_chunks,
+            delta = delta;
+            "collection {:?}: qdrant points: {:?}, pg chunks: {:?}, delta: {:?}",
+            collection, qdrant_points, pg_chunks, delta
+        );
+    }
+
+    log::info!(
+        drifted_collections = drifted_collections;
+        "collections with count drift: {:?}",
+        drifted_collections
+    );
+
+    Ok(drifted_collections)
+}
+
+/// Reports chunk rows which would make the PG side of a sweep wrong: qdrant_point_ids shared by
+/// several chunks and chunks left in soft deleted datasets. Only reads Postgres. Returns whether
+/// any were found.
+async fn report_pg_point_id_mapping(
+    web_pool: actix_web::web::Data<Pool>,
+) -> Result<bool, ServiceError> {
+    let duplicate_point_ids = get_duplicate_qdrant_point_ids_query(web_pool.clone()).await?;
+    for (qdrant_point_id, chunk_ids) in duplicate_point_ids.iter() {
+        log::info!(
+            qdrant_point_id:% = qdrant_point_id,
+            chunk_ids:? = chunk_ids;
+            "duplicate qdrant_point_id {:?} used by chunks: {:?}",
+            qdrant_point_id, chunk_ids
+        );
+    }
+
+    let orphaned_chunk_counts = get_chunk_counts_in_deleted_datasets_query(web_pool).await?;
+    for (dataset_id, chunk_count) in orphaned_chunk_counts.iter() {
+        log::info!(
+            dataset_id:% = dataset_id,
+            chunks = *chunk_count;
+            "deleted dataset {:?} still has chunks: {:?}",
+            dataset_id, chunk_count
+        );
+    }
+
+    log::info!(
+        duplicate_point_ids = duplicate_point_ids.len(),
+        deleted_datasets_with_chunks = orphaned_chunk_counts.len();
+        "duplicate qdrant_point_ids: {:?}, deleted datasets with chunks: {:?}",
+        duplicate_point_ids.len(),
+        orphaned_chunk_counts.len()
+    );
+
+    Ok(!duplicate_point_ids.is_empty() || !orphaned_chunk_counts.is_empty())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), ServiceError> {
+    dotenvy::dotenv().ok();
+    init_logger();
+
+    let started_at = std::time::Instant::now();
+
+    let database_url = get_env!("DATABASE_URL", "DATABASE_URL is not set");
+
+    let args = std::env::args().collect::<Vec<String>>();
+
+    // Only compares per-collection counts, exiting non-zero when any of them differ.
+    let report_only_collection_counts = args
+        .iter()
+        .any(|arg| arg == "--report-only-collection-counts");
+
+    // Only checks the PG point id mapping, exiting non-zero when it is inconsistent. Qdrant is
+    // not contacted.
+    let pg_point_id_mapping_only = args.iter().any(|arg| arg == "--report-pg-point-id-mapping");
+
+    // Only orphans whose chunk time_stamp is at or after the cutoff are handled when set. This is
+    // the chunk's own `time_stamp` payload value, which is set by whoever uploaded the chunk and
+    // is not its ingestion or creation time: orphans have no PG row left to read a server-set
+    // `created_at` from. Orphans without a time_stamp are skipped.
+    let since = get_flag_value(&args, "--since").map(|since| {
+        chrono::DateTime::parse_from_rfc3339(&since)
+            .expect("--since must be an RFC3339 timestamp")
+            .timestamp()
+    });
+    let mut skipped_without_time_stamp = 0;
+
+    // Appends every orphan which would be deleted to this file, one `<collection>\t<point id>`
+    // per line, gzip compressed when the path ends in `.gz`.
+    let orphan_export = get_flag_value(&args, "--export-orphans").map(|path| {
+        Mutex::new(
+            OrphanExportWriter::open(&path).expect("--export-orphans must be a writable file path"),
+        )
+    });
+    let mut orphans_exported = 0;
+
+    // Reports orphans without deleting them.
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+
+    // The sweep only reports the orphaned points it finds, and deletes them from Qdrant when
+    // this is passed without `--dry-run`.
+    let delete = args.iter().any(|arg| arg == "--delete") && !dry_run;
+    let mut orphans_found = 0;
+
+    // Only deletes the orphans listed in a previous `--export-orphans` file, rechecking PG first.
+    let exported_orphans_path = get_flag_value(&args, "--delete-exported-orphans");
+
+    // Deletes PG chunk rows whose points are gone from Qdrant instead of the other way around.
+    let pg_orphans_only = args.iter().any(|arg| arg == "--delete-pg-orphans");
+
+    // Splits each collection's scroll into this many UUID ranges which are scanned concurrently.
+    let concurrency_per_collection = get_flag_value(&args, "--concurrency-per-collection")
+        .map(|concurrency| {
+            concurrency
+                .parse::<usize>()
+                .expect("--concurrency-per-collection must be a positive integer")
+        })
+        .unwrap_or(1)
+        .max(1);
+
+    // Number of collections swept at the same time.
+    let collection_parallelism = get_flag_value(&args, "--collection-parallelism")
+        .map(|parallelism| {
+            parallelism
+                .parse::<usize>()
+                .expect("--collection-parallelism must be a positive integer")
+        })
+        .unwrap_or(1)
+        .max(1);
+
+    // Starts the collections with the most points first so that a giant collection is not left
+    // running alone at the tail while the other workers sit idle.
+    let collection_parallelism_by_size = args
+        .iter()
+        .any(|arg| arg == "--collection-parallelism-by-size");
+
+    let pg_concurrency = std::env::var("SYNC_QDRANT_PG_CONCURRENCY")
+        .unwrap_or("3".to_string())
+        .parse::<usize>()
+        .unwrap_or(3)
+        .max(1);
+
+    let progress_every = std::env::var("SYNC_QDRANT_PROGRESS_EVERY")
+        .unwrap_or("10".to_string())
+        .parse::<usize>()
+        .unwrap_or(10)
+        .max(1);
+
+    // Throttle retries allowed across every collection of the run before it is aborted.
+    let max_total_retries = std::env::var("SYNC_QDRANT_MAX_TOTAL_RETRIES")
+        .unwrap_or("100".to_string())
+        .parse::<usize>()
+        .unwrap_or(100);
+    set_qdrant_retry_budget(max_total_retries);
+
+    // Stale connections are reaped and verified before use, see `PgPoolRecycling`.
+    let pg_pool_recycling = PgPoolRecycling::from_env();
+
+    let pool = create_pg_pool(
+        database_url,
+        (pg_concurrency * concurrency_per_collection * collection_parallelism).max(3),
+        pg_pool_recycling,
+    );
+
+    let web_pool = actix_web::web::Data::new(pool.clone());
+
+    // The sweep only reads from PG, deletes go to Qdrant, so its existence queries can run
+    // against a replica to offload the primary. A lagging replica misses freshly inserted chunks,
+    // so the orphans it finds are rechecked on the primary before anything is reported or
+    // deleted.
+    let replica_database_url = std::env::var("DATABASE_REPLICA_URL")
+        .ok()
+        .filter(|replica_database_url| !replica_database_url.is_empty());
+    let replica_pool = replica_database_url.as_ref().map(|replica_database_url| {
+        create_pg_pool(
+            replica_database_url,
+            (pg_concurrency * concurrency_per_collection * collection_parallelism).max(3),
+            pg_pool_recycling,
+        )
+    });
+    let existence_pool = replica_pool
+        .clone()
+        .map(actix_web::web::Data::new)
+        .unwrap_or_else(|| web_pool.clone());
+    let recheck_pool = replica_pool.as_ref().map(|_| web_pool.clone());
+
+    if !pg_point_id_mapping_only {
+        if let Err(err) = check_qdrant_health().await {
+            log::error!(
+                "cannot reach Qdrant at {}: {}",
+                redact_endpoint(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+                err
+            );
+            std::process::exit(EXIT_QDRANT_UNREACHABLE);
+        }
+    }
+
+    if let Err(err) = pool.get().await {
+        log::error!(
+            "cannot reach Postgres at {}: {}",
+            redact_endpoint(database_url),
+            err
+        );
+        std::process::exit(EXIT_POSTGRES_UNREACHABLE);
+    }
+
+    if let (Some(replica_pool), Some(replica_database_url)) = (&replica_pool, &replica_database_url)
+    {
+        if let Err(err) = replica_pool.get().await {
+            log::error!(
+                "cannot reach Postgres replica at {}: {}",
+                redact_endpoint(replica_database_url),
+                err
+            );
+            std::process::exit(EXIT_POSTGRES_UNREACHABLE);
+        }
+    }
+
+    if pg_point_id_mapping_only {
+        if report_pg_point_id_mapping(web_pool.clone()).await? {
+            std::process::exit(EXIT_PG_POINT_ID_MAPPING_INCONSISTENT);
+        }
+
+        return Ok(());
+    }
+
+    if report_only_collection_counts {
+        if report_collection_counts(web_pool.clone()).await? > 0 {
+            std::process::exit(EXIT_COLLECTION_COUNTS_DRIFTED);
+        }
+
+        return Ok(());
+    }
+
+    // An old binary running against a changed schema could treat every point as an orphan
+    if let Err(err) = check_pg_point_ids_query_schema(existence_pool.clone()).await {
+        log::error!("aborting sync, schema canary failed: {}", err);
+        std::process::exit(EXIT_SCHEMA_MISMATCH);
+    }
+
+    if pg_orphans_only {
+        // Runs against the primary, the existence checks must see the rows that get deleted
+        if delete_pg_orphans(dry_run, web_pool.clone())
+            .await
+            .inspect_err(|err| exit_if_retry_budget_exhausted(err, max_total_retries))?
+        {
+            std::process::exit(EXIT_PG_ORPHAN_THRESHOLD_EXCEEDED);
+        }
+
+        return Ok(());
+    }
+
+    if let Some(exported_orphans_path) = exported_orphans_path {
+        // Rechecked against the primary, the replica may not have the rows inserted since the
+        // export yet
+        return delete_exported_orphans(
+            &exported_orphans_path,
+            pg_concurrency,
+            dry_run,
+            web_pool.clone(),
+        )
+        .await
+        .inspect_err(|err| exit_if_retry_budget_exhausted(err, max_total_retries));
+    }
+
+    let collections = if collection_parallelism_by_size {
+        let mut collection_infos = get_qdrant_collections_info()
+            .await
+            .inspect_err(|err| exit_if_retry_budget_exhausted(err, max_total_retries))?;
+        collection_infos.sort_by_key(|collection_info| {
+            std::cmp::Reverse(collection_info.points_count.unwrap_or(0))
+        });
+
+        collection_infos
+            .into_iter()
+            .map(|collection_info| collection_info.name)
+            .collect::<Vec<String>>()
+    } else {
+        get_qdrant_collections()
+            .await
+            .inspect_err(|err| exit_if_retry_budget_exhausted(err, max_total_retries))?
+    };
+
+    let mut collection_syncs = futures::stream::iter(collections)
+        .map(|collection| {
+            sync_collection(
+                collection,
+                since,
+                pg_concurrency,
+                progress_every,
+                concurrency_per_collection,
+                orphan_export.as_ref(),
+                delete,
+                existence_pool.clone(),
+                recheck_pool.clone(),
+            )
+        })
+        .buffer_unordered(collection_parallelism);
+
+    while let Some(counters) = collection_syncs.next().await {
+        let counters =
+            counters.inspect_err(|err| exit_if_retry_budget_exhausted(err, max_total_retries))?;
+
+        skipped_without_time_stamp += counters.skipped_without_time_stamp.load(Ordering::Relaxed);
+        orphans_found += counters.orphans_found.load(Ordering::Relaxed);
+        orphans_exported += counters.orphans_exported.load(Ordering::Relaxed);
+    }
+
+    if let Some(orphan_export) = orphan_export {
+        orphan_export
+            .into_inner()
+            .map_err(|_| {
+                ServiceError::InternalServerError("Orphan export lock poisoned".to_string())
+            })?
+            .finish()
+            .map_err(|err| {
+                ServiceError::InternalServerError(format!("Failed to export orphans: {}", err))
+            })?;
+
+        log::info!(
+            orphans_exported = orphans_exported;
+            "exported orphans: {:?}",
+            orphans_exported
+        );
+    }
+
+    if !delete && orphans_found > 0 {
+        log::info!(
+            orphans_found = orphans_found;
+            "orphans found: {:?}, none were deleted, pass --delete to delete them from Qdrant",
+            orphans_found
+        );
+    }
+
+    if since.is_some() {
+        log::info!(
+            skipped_without_time_stamp = skipped_without_time_stamp;
+            "skipped orphans without a time_stamp: {:?}",
+            skipped_without_time_stamp
+        );
+    }
+
+    let remaining_retries = get_remaining_qdrant_retry_budget().unwrap_or(max_total_retries);
+    log::info!(
+        remaining_retries = remaining_retries,
+        max_total_retries = max_total_retries,
+        elapsed_secs = started_at.elapsed().as_secs();
+        "remaining Qdrant retry budget: {:?} of {:?}",
+        remaining_retries,
+        max_total_retries
+    );
+
     Ok(())
 }
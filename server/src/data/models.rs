@@ -14,7 +14,9 @@ use crate::handlers::group_handler::{SearchOverGroupsReqPayload, SearchWithinGro
 use crate::handlers::message_handler::{
     CreateMessageReqPayload, EditMessageReqPayload, RegenerateMessageReqPayload,
 };
-use crate::handlers::page_handler::PublicPageParameters;
+use crate::handlers::page_handler::{
+    PopularSuggestedQueriesOptions, PublicPageFeedOptions, PublicPageParameters,
+};
 use crate::operators::analytics_operator::{
     CTRRecommendationsWithClicksResponse, CTRRecommendationsWithoutClicksResponse,
     CTRSearchQueryWithClicksResponse, CTRSearchQueryWithoutClicksResponse, HeadQueryResponse,
@@ -2306,6 +2308,7 @@ pub struct DatasetConfiguration {
     pub BM25_B: f32,
     pub BM25_K: f32,
     pub BM25_AVG_LEN: f32,
+    pub BM25_LANGUAGE: Option<String>,
     pub FULLTEXT_ENABLED: bool,
     pub SEMANTIC_ENABLED: bool,
     pub QDRANT_ONLY: bool,
@@ -2330,6 +2333,66 @@ pub struct PublicDatasetOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
     pub extra_params: Option<PublicPageParameters>,
+    /// Text prepended to the query before it is embedded by the public search route, e.g. "search_query: ". Only applies to the dense vector; fulltext and BM25 tokenize the query as typed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_prefix: Option<String>,
+    /// Text appended to the query before it is embedded by the public search route. Only applies to the dense vector; fulltext and BM25 tokenize the query as typed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_suffix: Option<String>,
+    /// Maximum number of characters, after trimming whitespace, allowed in a public search query. Defaults to 512.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_query_length: Option<usize>,
+    /// Synonyms for query terms, e.g. {"k8s": ["kubernetes"]}. The public search route ORs the synonyms of any matching term into the fulltext and BM25 query. Synonyms are not added to the query used for the dense embedding.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synonyms: Option<HashMap<String, Vec<String>>>,
+    /// When set, suggested queries for the public page are the most popular public page search queries of the dataset in analytics instead of `default_search_queries`. Searches made through the API are never suggested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub popular_suggested_queries: Option<PopularSuggestedQueriesOptions>,
+    /// Search method every public search of the dataset is run with, overriding the client's `search_type`, `fallback_search_type` and the page's `allow_switching_modes`. Use it when a search method must be guaranteed, e.g. keyword-only search for compliance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_search_type: Option<SearchMethod>,
+    /// Number of decimals scores are rounded to in public search responses. Defaults to 4. Results are ordered before rounding, so results with equal rounded scores keep their order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_precision: Option<u32>,
+    /// Regexes whose matches are replaced with `[REDACTED]` in public search queries before they are stored in analytics, in addition to the default email and long digit run patterns. The search itself uses the unredacted query. Invalid patterns are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_redaction_patterns: Option<Vec<String>>,
+    /// Content-Security-Policy sent with the public page instead of the default one, which only allows scripts from the packages the page loads and connections to the page's `base_url`. `{nonce}` is replaced with the per-response nonce set on the page's inline scripts and `{base_url}` with the page's base_url. Pages whose policy contains the nonce are sent with `Cache-Control: no-store`. An empty string sends no policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_security_policy: Option<String>,
+    /// Send the public page's Content-Security-Policy as `Content-Security-Policy-Report-Only`, so violations are only reported by browsers instead of blocked, e.g. while rolling out a custom policy. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_security_policy_report_only: Option<bool>,
+    /// Maximum public searches per second for the dataset's public page, enforced with a token bucket which allows bursts of up to max_rps searches. Searches beyond it get a 429 with a `Retry-After` header, and every public search response has `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers so clients can throttle themselves. Counted per server instance. Unset or 0 disables the limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rps: Option<u32>,
+    /// How public searches with an empty or whitespace only query are handled: `empty` returns no results with a warning, `error` rejects them with a 400 and `match_all` returns the chunks matching the search's filters in browse order, by the `sort_by` field of the search's sort_options when it is set and by point id otherwise, with a score of 0. Defaults to `empty`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty_query_behavior: Option<PublicEmptyQueryBehavior>,
+    /// Secret the public page signs its parameters with when set, adding them as an HS256 JWT in `signedConfig` so an embed can detect tampering by intermediaries. Verify the token where the secret can be kept, e.g. the embedding site's backend, never ship the secret to browsers. Use at least 32 random bytes, and rotate it by setting a new secret, after which tokens signed with the old one no longer verify. Unset or empty disables signing.
+    #[serde(skip_serializing)]
+    pub config_signing_secret: Option<String>,
+    /// How many times the requested results public searches with `dedupe_by_content` fetch, so pages stay full after duplicates are dropped, e.g. 3 fetches 30 results for a page of 10. Results are fetched from the first one up to the end of the requested page, so later pages fetch more, and at most `PUBLIC_SEARCH_MAX_CANDIDATES` (100 by default) results are fetched; pages beyond that are deduped on their own results only. Larger values keep pages full on datasets with many duplicates at the cost of slower searches, smaller values can return short pages. Defaults to 2, 1 disables the over-fetch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_multiplier: Option<f32>,
+    /// Origins allowed to call public search, e.g. `https://example.com`. A `*.` host prefix allows every subdomain but not the domain itself, e.g. `https://*.example.com`, an entry without a scheme allows any scheme and `*` allows every origin. When set, searches whose `Origin` header is missing or not allowed, which includes requests from servers and scripts unless they send one, are rejected with a 403, and allowed searches echo their origin in `Access-Control-Allow-Origin`. Origin checks stop other sites from embedding the search in browsers but are no access control: clients outside browsers can send any Origin. Unset or empty allows every origin.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_origins: Option<Vec<String>>,
+    /// Titles, links and descriptions of the items of the public page's RSS and Atom feed at `/api/public_page/{dataset_id}/feed`. The feed works without it, with items mapped from the chunks' own fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_options: Option<PublicPageFeedOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicEmptyQueryBehavior {
+    /// Return no results with a warning.
+    #[default]
+    Empty,
+    /// Reject the search with a 400.
+    Error,
+    /// Return the chunks matching the search's filters, e.g. for a browse mode. Only the first `PUBLIC_SEARCH_MAX_CANDIDATES` chunks (100 by default) can be paged through, later pages get a 400.
+    MatchAll,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -2395,6 +2458,8 @@ pub struct DatasetConfigurationDTO {
     pub BM25_K: Option<f32>,
     /// The average length of the chunks in the index for BM25
     pub BM25_AVG_LEN: Option<f32>,
+    /// ISO 639-1 code of the language BM25 tokenizes chunks and queries in. Words are stemmed for ar, da, de, el, en, es, fi, fr, hu, it, nl, no, pt, ro, ru, sv, ta and tr, and zh, ja and ko text is split into overlapping character pairs since it is written without spaces. Defaults to en, which is also used for unsupported languages. Chunks are tokenized when they are ingested, so existing chunks must be reingested after changing it. Only affects BM25, not SPLADE fulltext search.
+    pub BM25_LANGUAGE: Option<String>,
     /// Whether to use fulltext search
     pub FULLTEXT_ENABLED: Option<bool>,
     /// Whether to use semantic search
@@ -2445,6 +2510,7 @@ impl From<DatasetConfigurationDTO> for DatasetConfiguration {
             BM25_B: dto.BM25_B.unwrap_or(0.75),
             BM25_K: dto.BM25_K.unwrap_or(0.75),
             BM25_AVG_LEN: dto.BM25_AVG_LEN.unwrap_or(256.0),
+            BM25_LANGUAGE: dto.BM25_LANGUAGE,
             FULLTEXT_ENABLED: dto.FULLTEXT_ENABLED.unwrap_or(true),
             SEMANTIC_ENABLED: dto.SEMANTIC_ENABLED.unwrap_or(true),
             QDRANT_ONLY: dto.QDRANT_ONLY.unwrap_or(false),
@@ -2462,8 +2528,24 @@ impl From<DatasetConfigurationDTO> for DatasetConfiguration {
             PUBLIC_DATASET: PublicDatasetOptions {
                 enabled: dto.PUBLIC_DATASET.clone().map(|public_dataset| public_dataset.clone().enabled).unwrap_or(false),
                 api_key: Some("".to_string()),
-                extra_params: dto.PUBLIC_DATASET.map(|public_dataset| public_dataset.extra_params)
-                .unwrap_or_default()
+                extra_params: dto.PUBLIC_DATASET.clone().map(|public_dataset| public_dataset.extra_params)
+                .unwrap_or_default(),
+                query_prefix: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.query_prefix),
+                feed_options: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.feed_options),
+                allowed_origins: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.allowed_origins),
+                candidate_multiplier: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.candidate_multiplier),
+                config_signing_secret: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.config_signing_secret),
+                empty_query_behavior: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.empty_query_behavior),
+                max_rps: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.max_rps),
+                content_security_policy_report_only: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.content_security_policy_report_only),
+                content_security_policy: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.content_security_policy),
+                query_redaction_patterns: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.query_redaction_patterns),
+                score_precision: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.score_precision),
+                force_search_type: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.force_search_type),
+                popular_suggested_queries: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.popular_suggested_queries),
+                synonyms: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.synonyms),
+                max_query_length: dto.PUBLIC_DATASET.clone().and_then(|public_dataset| public_dataset.max_query_length),
+                query_suffix: dto.PUBLIC_DATASET.and_then(|public_dataset| public_dataset.query_suffix),
             },
         }
     }
@@ -2487,6 +2569,7 @@ impl From<DatasetConfiguration> for DatasetConfigurationDTO {
             BM25_B: Some(config.BM25_B),
             BM25_K: Some(config.BM25_K),
             BM25_AVG_LEN: Some(config.BM25_AVG_LEN),
+            BM25_LANGUAGE: config.BM25_LANGUAGE,
             FULLTEXT_ENABLED: Some(config.FULLTEXT_ENABLED),
             SEMANTIC_ENABLED: Some(config.SEMANTIC_ENABLED),
             QDRANT_ONLY: Some(config.QDRANT_ONLY),
@@ -2510,6 +2593,24 @@ impl From<DatasetConfiguration> for DatasetConfigurationDTO {
                         ..params
                     }
                 }),
+                query_prefix: config.PUBLIC_DATASET.query_prefix,
+                query_suffix: config.PUBLIC_DATASET.query_suffix,
+                feed_options: config.PUBLIC_DATASET.feed_options,
+                allowed_origins: config.PUBLIC_DATASET.allowed_origins,
+                candidate_multiplier: config.PUBLIC_DATASET.candidate_multiplier,
+                config_signing_secret: None,
+                empty_query_behavior: config.PUBLIC_DATASET.empty_query_behavior,
+                max_rps: config.PUBLIC_DATASET.max_rps,
+                content_security_policy_report_only: config
+                    .PUBLIC_DATASET
+                    .content_security_policy_report_only,
+                content_security_policy: config.PUBLIC_DATASET.content_security_policy,
+                query_redaction_patterns: config.PUBLIC_DATASET.query_redaction_patterns,
+                score_precision: config.PUBLIC_DATASET.score_precision,
+                force_search_type: config.PUBLIC_DATASET.force_search_type,
+                popular_suggested_queries: config.PUBLIC_DATASET.popular_suggested_queries,
+                synonyms: config.PUBLIC_DATASET.synonyms,
+                max_query_length: config.PUBLIC_DATASET.max_query_length,
             }),
         }
     }
@@ -2533,6 +2634,7 @@ impl Default for DatasetConfiguration {
             BM25_B: 0.75,
             BM25_K: 0.75,
             BM25_AVG_LEN: 256.0,
+            BM25_LANGUAGE: None,
             FULLTEXT_ENABLED: true,
             SEMANTIC_ENABLED: true,
             QDRANT_ONLY: false,
@@ -2551,6 +2653,22 @@ impl Default for DatasetConfiguration {
                 enabled: false,
                 api_key: Some("".to_string()),
                 extra_params: None,
+                query_prefix: None,
+                query_suffix: None,
+                feed_options: None,
+                allowed_origins: None,
+                candidate_multiplier: None,
+                config_signing_secret: None,
+                empty_query_behavior: None,
+                max_rps: None,
+                content_security_policy_report_only: None,
+                content_security_policy: None,
+                query_redaction_patterns: None,
+                score_precision: None,
+                force_search_type: None,
+                popular_suggested_queries: None,
+                synonyms: None,
+                max_query_length: None,
             },
         }
     }
@@ -2733,6 +2851,10 @@ impl DatasetConfiguration {
                 .get("BM25_AVG_LEN")
                 .and_then(|v| v.as_f64().map(|f| f as f32))
                 .unwrap_or(256f32),
+            BM25_LANGUAGE: configuration
+                .get("BM25_LANGUAGE")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string()),
             EMBEDDING_QUERY_PREFIX: configuration
                 .get("EMBEDDING_QUERY_PREFIX")
                 .unwrap_or(&{
@@ -2807,7 +2929,23 @@ impl DatasetConfiguration {
             PUBLIC_DATASET: PublicDatasetOptions {
                 enabled: configuration_json.pointer("/PUBLIC_DATASET/enabled").unwrap_or(&json!(false)).as_bool().unwrap_or(false),
                 api_key: Some(configuration_json.pointer("/PUBLIC_DATASET/api_key").unwrap_or(&json!("")).as_str().unwrap_or("").to_string()),
-                extra_params
+                extra_params,
+                query_prefix: configuration_json.pointer("/PUBLIC_DATASET/query_prefix").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                query_suffix: configuration_json.pointer("/PUBLIC_DATASET/query_suffix").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                feed_options: configuration_json.pointer("/PUBLIC_DATASET/feed_options").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                allowed_origins: configuration_json.pointer("/PUBLIC_DATASET/allowed_origins").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                candidate_multiplier: configuration_json.pointer("/PUBLIC_DATASET/candidate_multiplier").and_then(|v| v.as_f64()).map(|v| v as f32),
+                config_signing_secret: configuration_json.pointer("/PUBLIC_DATASET/config_signing_secret").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                empty_query_behavior: configuration_json.pointer("/PUBLIC_DATASET/empty_query_behavior").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                max_rps: configuration_json.pointer("/PUBLIC_DATASET/max_rps").and_then(|v| v.as_u64()).map(|v| v as u32),
+                content_security_policy_report_only: configuration_json.pointer("/PUBLIC_DATASET/content_security_policy_report_only").and_then(|v| v.as_bool()),
+                content_security_policy: configuration_json.pointer("/PUBLIC_DATASET/content_security_policy").and_then(|v| v.as_str()).map(|v| v.to_string()),
+                query_redaction_patterns: configuration_json.pointer("/PUBLIC_DATASET/query_redaction_patterns").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                score_precision: configuration_json.pointer("/PUBLIC_DATASET/score_precision").and_then(|v| v.as_u64()).map(|v| v as u32),
+                force_search_type: configuration_json.pointer("/PUBLIC_DATASET/force_search_type").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                popular_suggested_queries: configuration_json.pointer("/PUBLIC_DATASET/popular_suggested_queries").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                synonyms: configuration_json.pointer("/PUBLIC_DATASET/synonyms").and_then(|v| serde_json::from_value(v.clone()).ok()),
+                max_query_length: configuration_json.pointer("/PUBLIC_DATASET/max_query_length").and_then(|v| v.as_u64()).map(|v| v as usize),
             }
         }
     }
@@ -2829,6 +2967,7 @@ impl DatasetConfiguration {
             "BM25_B": self.BM25_B,
             "BM25_K": self.BM25_K,
             "BM25_AVG_LEN": self.BM25_AVG_LEN,
+            "BM25_LANGUAGE": self.BM25_LANGUAGE,
             "FULLTEXT_ENABLED": self.FULLTEXT_ENABLED,
             "SEMANTIC_ENABLED": self.SEMANTIC_ENABLED,
             "QDRANT_ONLY": self.QDRANT_ONLY,
@@ -2846,7 +2985,23 @@ impl DatasetConfiguration {
             "PUBLIC_DATASET" : {
                 "enabled": self.PUBLIC_DATASET.enabled,
                 "api_key": self.PUBLIC_DATASET.api_key,
-                "extra_params": extra_params_json
+                "extra_params": extra_params_json,
+                "query_prefix": self.PUBLIC_DATASET.query_prefix,
+                "query_suffix": self.PUBLIC_DATASET.query_suffix,
+                "feed_options": self.PUBLIC_DATASET.feed_options,
+                "allowed_origins": self.PUBLIC_DATASET.allowed_origins,
+                "candidate_multiplier": self.PUBLIC_DATASET.candidate_multiplier,
+                "config_signing_secret": self.PUBLIC_DATASET.config_signing_secret,
+                "empty_query_behavior": self.PUBLIC_DATASET.empty_query_behavior,
+                "max_rps": self.PUBLIC_DATASET.max_rps,
+                "content_security_policy_report_only": self.PUBLIC_DATASET.content_security_policy_report_only,
+                "content_security_policy": self.PUBLIC_DATASET.content_security_policy,
+                "query_redaction_patterns": self.PUBLIC_DATASET.query_redaction_patterns,
+                "score_precision": self.PUBLIC_DATASET.score_precision,
+                "force_search_type": self.PUBLIC_DATASET.force_search_type,
+                "popular_suggested_queries": self.PUBLIC_DATASET.popular_suggested_queries,
+                "synonyms": self.PUBLIC_DATASET.synonyms,
+                "max_query_length": self.PUBLIC_DATASET.max_query_length,
             }
         })
     }
@@ -2930,6 +3085,10 @@ impl DatasetConfigurationDTO {
             BM25_AVG_LEN: self
                 .BM25_AVG_LEN
                 .unwrap_or(curr_dataset_config.BM25_AVG_LEN),
+            BM25_LANGUAGE: self
+                .BM25_LANGUAGE
+                .clone()
+                .or(curr_dataset_config.BM25_LANGUAGE),
             FULLTEXT_ENABLED: self
                 .FULLTEXT_ENABLED
                 .unwrap_or(curr_dataset_config.FULLTEXT_ENABLED),
@@ -3042,7 +3201,98 @@ impl DatasetConfigurationDTO {
                     tab_messages: page_parameters_self
                         .tab_messages
                         .or(page_parameters_curr.tab_messages),
+                    url_field: page_parameters_self
+                        .url_field
+                        .or(page_parameters_curr.url_field),
+                    number_locale: page_parameters_self
+                        .number_locale
+                        .or(page_parameters_curr.number_locale),
+                    features: None,
+                    config_version: None,
+                    signed_config: None,
                 }),
+                query_prefix: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.query_prefix)
+                    .or(curr_dataset_config.PUBLIC_DATASET.query_prefix),
+                query_suffix: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.query_suffix)
+                    .or(curr_dataset_config.PUBLIC_DATASET.query_suffix),
+                feed_options: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.feed_options)
+                    .or(curr_dataset_config.PUBLIC_DATASET.feed_options),
+                allowed_origins: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.allowed_origins)
+                    .or(curr_dataset_config.PUBLIC_DATASET.allowed_origins),
+                candidate_multiplier: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.candidate_multiplier)
+                    .or(curr_dataset_config.PUBLIC_DATASET.candidate_multiplier),
+                config_signing_secret: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.config_signing_secret)
+                    .or(curr_dataset_config.PUBLIC_DATASET.config_signing_secret),
+                empty_query_behavior: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.empty_query_behavior)
+                    .or(curr_dataset_config.PUBLIC_DATASET.empty_query_behavior),
+                max_rps: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.max_rps)
+                    .or(curr_dataset_config.PUBLIC_DATASET.max_rps),
+                content_security_policy_report_only: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.content_security_policy_report_only)
+                    .or(curr_dataset_config
+                        .PUBLIC_DATASET
+                        .content_security_policy_report_only),
+                content_security_policy: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.content_security_policy)
+                    .or(curr_dataset_config.PUBLIC_DATASET.content_security_policy),
+                query_redaction_patterns: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.query_redaction_patterns)
+                    .or(curr_dataset_config.PUBLIC_DATASET.query_redaction_patterns),
+                score_precision: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.score_precision)
+                    .or(curr_dataset_config.PUBLIC_DATASET.score_precision),
+                force_search_type: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.force_search_type)
+                    .or(curr_dataset_config.PUBLIC_DATASET.force_search_type),
+                popular_suggested_queries: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.popular_suggested_queries)
+                    .or(curr_dataset_config.PUBLIC_DATASET.popular_suggested_queries),
+                synonyms: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.synonyms)
+                    .or(curr_dataset_config.PUBLIC_DATASET.synonyms),
+                max_query_length: self
+                    .PUBLIC_DATASET
+                    .clone()
+                    .and_then(|public_dataset| public_dataset.max_query_length)
+                    .or(curr_dataset_config.PUBLIC_DATASET.max_query_length),
             },
         }
     }
@@ -5098,7 +5348,7 @@ pub enum SearchType {
     SearchWithinGroups,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema, Display, Clone, PartialEq, Default)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, Display, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum SearchMethod {
     #[serde(rename = "fulltext", alias = "full_text")]
@@ -5347,6 +5597,13 @@ pub struct HeadQueries {
     pub count: i64,
 }
 
+#[derive(Debug, Row, Serialize, Deserialize)]
+pub struct ChunkClickThroughClickhouse {
+    pub chunk_id: String,
+    pub clicks: u64,
+    pub impressions: u64,
+}
+
 #[derive(Debug, Row, Serialize, Deserialize, ToSchema)]
 pub struct UsageGraphPointClickhouse {
     #[serde(with = "clickhouse::serde::time::datetime")]
@@ -6708,6 +6965,10 @@ pub struct TypoOptions {
     pub disable_on_word: Option<Vec<String>>,
     /// Auto-require non-english words present in the dataset to exist in each results chunk_html text. If not specified, this defaults to true.
     pub prioritize_domain_specifc_words: Option<bool>,
+    /// The maximum number of edits a correction may be away from the query word, capping the distance allowed by `one_typo_word_range` and `two_typo_word_range`, e.g. 1 to never correct two typos. Must be between 0 and 2, 0 disables corrections. If not specified, this defaults to 2.
+    pub max_edit_distance: Option<u32>,
+    /// The number of characters at the start of a word which must match a correction exactly, e.g. 1 to never correct the first letter. Lowers false corrections for vocabularies with many similar words. If not specified, this defaults to 0.
+    pub prefix_length: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema, Default)]
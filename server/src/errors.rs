@@ -1,5 +1,6 @@
 use actix_web::{
     error::{JsonPayloadError, ResponseError},
+    http::header,
     HttpResponse,
 };
 use derive_more::Display;
@@ -42,6 +43,9 @@ pub enum ServiceError {
     PayloadTooLarge(String),
 
     RequestTimeout,
+
+    #[display(fmt = "Service Unavailable: {_0}")]
+    ServiceUnavailable(String),
 }
 
 // impl ResponseError trait allows to convert our errors into http responses with appropriate data
@@ -86,6 +90,11 @@ impl ResponseError for ServiceError {
                     message: message.to_string(),
                 })
             }
+            ServiceError::ServiceUnavailable(ref message) => HttpResponse::ServiceUnavailable()
+                .insert_header((header::RETRY_AFTER, "1"))
+                .json(ErrorResponseBody {
+                    message: message.to_string(),
+                }),
         }
     }
 }
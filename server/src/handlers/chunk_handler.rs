@@ -42,6 +42,7 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use simple_server_timing_header::Timer;
+use std::collections::HashMap;
 use tokio_stream::StreamExt;
 use utoipa::ToSchema;
 
@@ -71,6 +72,69 @@ pub struct ScoringOptions {
     pub fulltext_boost: Option<FullTextBoost>,
     /// Semantic boosting moves the dense vector of the chunk in the direction of the distance phrase for semantic search. I.e. you can force a cluster by moving every chunk for a PDF closer to its title or push a chunk with a chunk_html of "iphone" 25% closer to the term "flagship" by using the distance phrase "flagship" and a distance factor of 0.25. Conceptually it's drawing a line (euclidean/L2 distance) between the vector for the innerText of the chunk_html and distance_phrase then moving the vector of the chunk_html distance_factor*L2Distance closer to or away from the distance_phrase point along the line between the two points.
     pub semantic_boost: Option<SemanticBoost>,
+    /// Map of metadata field names to an amount added to the score of results where the field is present and non-null, e.g. `{"has_video": 0.1}`, ranking them higher without filtering out the rest. Added after score_threshold is applied. Public page search only.
+    pub presence_boosts: Option<HashMap<String, f32>>,
+    /// Weight of the dense (semantic) results when fusing hybrid search results. Must be non-negative and a missing weight counts as 0. Weights are normalized to sum to 1, and equal weights are used if both are 0. Only applies to hybrid search, where setting either weight replaces the cross encoder rerank with a weighted sum of the max-normalized dense and sparse scores.
+    pub dense_weight: Option<f32>,
+    /// Weight of the sparse (fulltext) results when fusing hybrid search results. See dense_weight.
+    pub sparse_weight: Option<f32>,
+    /// Trade off relevance and diversity of semantic results with Maximal Marginal Relevance, from 1.0 for the unchanged order to 0.0 for pure diversity. Only reorders the requested page and costs an extra Qdrant request for the vectors of the results. Public page search only.
+    pub mmr_lambda: Option<f32>,
+    /// Amount added to the score of results in proportion to their click-through rate in the last `PUBLIC_SEARCH_POPULARITY_WINDOW_DAYS` (30 by default) days of analytics, scaled so the dataset's most clicked chunk gets the full boost. Must be non-negative. Added after score_threshold is applied. Rates are refreshed in the background every 10 minutes, the first search of a dataset is served without the boost and a warning while they load. Public page search only.
+    pub popularity_boost: Option<f32>,
+    /// Number of fused dense and sparse candidates the cross encoder reranks for hybrid search, split evenly between the two searches with each retrieving at least the page_size. Defaults to twice the page_size and is capped at `MAX_RERANK_CANDIDATES` (100 by default); every 20 candidates add a reranker batch. Pages are still offset by page_size, so a candidate reranked onto one page can appear again on the next. Ignored by other search types and when dense_weight or sparse_weight replace the rerank.
+    pub rerank_candidates: Option<usize>,
+}
+
+impl ScoringOptions {
+    /// Rejects the options only the public page search route applies, so other routes don't
+    /// silently ignore them.
+    pub fn reject_public_only_options(&self) -> Result<(), ServiceError> {
+        let public_only_options = [
+            ("presence_boosts", self.presence_boosts.is_some()),
+            ("mmr_lambda", self.mmr_lambda.is_some()),
+            ("popularity_boost", self.popularity_boost.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, is_set)| *is_set)
+        .map(|(option, _)| option)
+        .collect::<Vec<&str>>();
+
+        if !public_only_options.is_empty() {
+            return Err(ServiceError::BadRequest(format!(
+                "scoring_options {} are only supported by public page search",
+                public_only_options.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the normalized `(dense, sparse)` hybrid fusion weights, if either weight is set.
+    pub fn hybrid_weights(&self) -> Result<Option<(f32, f32)>, ServiceError> {
+        if self.dense_weight.is_none() && self.sparse_weight.is_none() {
+            return Ok(None);
+        }
+
+        let dense_weight = self.dense_weight.unwrap_or(0.0);
+        let sparse_weight = self.sparse_weight.unwrap_or(0.0);
+
+        if dense_weight < 0.0 || sparse_weight < 0.0 {
+            return Err(ServiceError::BadRequest(
+                "dense_weight and sparse_weight must be non-negative".to_string(),
+            ));
+        }
+
+        let total_weight = dense_weight + sparse_weight;
+        if total_weight == 0.0 {
+            return Ok(Some((0.5, 0.5)));
+        }
+
+        Ok(Some((
+            dense_weight / total_weight,
+            sparse_weight / total_weight,
+        )))
+    }
 }
 
 /// Request payload for creating a new chunk
@@ -1100,6 +1164,34 @@ pub struct ParsedQuery {
     pub query: String,
     pub quote_words: Option<Vec<String>>,
     pub negated_words: Option<Vec<String>>,
+    /// Prepended to the query only when creating its dense embedding.
+    pub dense_prefix: Option<String>,
+    /// Appended to the query only when creating its dense embedding.
+    pub dense_suffix: Option<String>,
+    /// Extra terms OR'd into the query only when creating its sparse (SPLADE or BM25) vector.
+    pub sparse_synonyms: Option<Vec<String>>,
+    /// Reuse the dense embedding of recent identical queries instead of embedding the query again.
+    pub cache_dense_embedding: bool,
+}
+
+impl ParsedQuery {
+    pub fn dense_query(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.dense_prefix.as_deref().unwrap_or_default(),
+            self.query,
+            self.dense_suffix.as_deref().unwrap_or_default()
+        )
+    }
+
+    pub fn sparse_query(&self) -> String {
+        match &self.sparse_synonyms {
+            Some(synonyms) if !synonyms.is_empty() => {
+                format!("{} {}", self.query, synonyms.join(" "))
+            }
+            _ => self.query.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1180,12 +1272,20 @@ pub fn parse_query(
                 query,
                 quote_words,
                 negated_words,
+                dense_prefix: None,
+                dense_suffix: None,
+                sparse_synonyms: None,
+                cache_dense_embedding: false,
             }
         }
         _ => ParsedQuery {
             query,
             quote_words: None,
             negated_words: None,
+            dense_prefix: None,
+            dense_suffix: None,
+            sparse_synonyms: None,
+            cache_dense_embedding: false,
         },
     }
 }
@@ -1225,6 +1325,10 @@ pub async fn search_chunks(
 
     let mut data = data.into_inner();
 
+    if let Some(scoring_options) = data.scoring_options.as_ref() {
+        scoring_options.reject_public_only_options()?;
+    }
+
     let parsed_query = match data.query.clone() {
         QueryTypes::Single(query) => ParsedQueryTypes::Single(parse_query(
             query.clone(),
@@ -1467,6 +1571,10 @@ pub async fn autocomplete(
     let dataset_config =
         DatasetConfiguration::from_json(dataset_org_plan_sub.dataset.server_configuration.clone());
 
+    if let Some(scoring_options) = data.scoring_options.as_ref() {
+        scoring_options.reject_public_only_options()?;
+    }
+
     let parsed_query = parse_query(
         data.query.clone(),
         data.use_quote_negated_terms,
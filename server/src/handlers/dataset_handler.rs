@@ -1,4 +1,5 @@
 use super::auth_handler::{AdminOnly, LoggedUser, OwnerOnly};
+use super::page_handler::validate_public_page_url_field;
 use crate::{
     data::models::{
         CrawlOptions, Dataset, DatasetAndOrgWithSubAndPlan, DatasetConfiguration,
@@ -67,6 +68,15 @@ impl FromRequest for OrganizationWithSubAndPlan {
     }
 }
 
+/// Re-serializes the dataset's server_configuration through DatasetConfiguration so keys which
+/// must never be returned, like the public page's api_key and config_signing_secret, are dropped.
+fn without_secrets(mut dataset: Dataset) -> Dataset {
+    dataset.server_configuration = json!(DatasetConfiguration::from_json(
+        dataset.server_configuration
+    ));
+    dataset
+}
+
 #[derive(Serialize, Deserialize, Debug, ToSchema, Clone)]
 #[schema(example = json!({
     "dataset_name": "My Dataset",
@@ -200,7 +210,7 @@ pub async fn create_dataset(
         }
     };
 
-    Ok(HttpResponse::Ok().json(d))
+    Ok(HttpResponse::Ok().json(without_secrets(d)))
 }
 
 #[derive(Serialize, Deserialize, Debug, ToSchema, Clone)]
@@ -295,6 +305,15 @@ pub async fn update_dataset(
         return Err(ServiceError::Forbidden);
     }
 
+    if let Some(params) = data
+        .server_configuration
+        .as_ref()
+        .and_then(|config| config.PUBLIC_DATASET.as_ref())
+        .and_then(|public_dataset| public_dataset.extra_params.as_ref())
+    {
+        validate_public_page_url_field(params, curr_dataset.id, pool.clone()).await?;
+    }
+
     let curr_dataset_config = DatasetConfiguration::from_json(curr_dataset.server_configuration);
 
     let d = update_dataset_query(
@@ -319,7 +338,7 @@ pub async fn update_dataset(
         .await?;
     };
 
-    Ok(HttpResponse::Ok().json(d))
+    Ok(HttpResponse::Ok().json(without_secrets(d)))
 }
 
 #[derive(Serialize, Deserialize, Debug, ToSchema, Clone)]
@@ -535,18 +554,14 @@ pub async fn get_dataset(
     dataset_id: web::Path<uuid::Uuid>,
     user: AdminOnly,
 ) -> Result<HttpResponse, ServiceError> {
-    let mut dataset =
+    let dataset =
         get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id.into_inner()), pool).await?;
 
     if !verify_admin(&user, &dataset.organization_id) {
         return Err(ServiceError::Forbidden);
     }
 
-    dataset.server_configuration = json!(DatasetConfiguration::from_json(
-        dataset.server_configuration
-    ));
-
-    Ok(HttpResponse::Ok().json(dataset))
+    Ok(HttpResponse::Ok().json(without_secrets(dataset)))
 }
 
 /// Get Usage By Dataset ID
@@ -608,20 +623,15 @@ pub async fn get_dataset_by_tracking_id(
     pool: web::Data<Pool>,
     user: AdminOnly,
 ) -> Result<HttpResponse, ServiceError> {
-    let mut dataset =
-        get_dataset_by_id_query(UnifiedId::TrackingId(tracking_id.into_inner()), pool)
-            .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
+    let dataset = get_dataset_by_id_query(UnifiedId::TrackingId(tracking_id.into_inner()), pool)
+        .await
+        .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
 
     if !verify_admin(&user, &dataset.organization_id) {
         return Err(ServiceError::Forbidden);
     }
 
-    dataset.server_configuration = json!(DatasetConfiguration::from_json(
-        dataset.server_configuration
-    ));
-
-    Ok(HttpResponse::Ok().json(dataset))
+    Ok(HttpResponse::Ok().json(without_secrets(dataset)))
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, Default)]
@@ -815,5 +825,44 @@ pub async fn batch_create_datasets(
     let created_or_upserted_datasets =
         create_datasets_query(datasets, data.upsert, pool.clone()).await?;
 
-    Ok(HttpResponse::Ok().json(created_or_upserted_datasets))
+    Ok(HttpResponse::Ok().json(
+        created_or_upserted_datasets
+            .into_iter()
+            .map(without_secrets)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::models::{DatasetAndUsage, DatasetUsageCount};
+
+    #[test]
+    pub fn test_dataset_responses_omit_the_signing_secret() {
+        let secret = "8f1c2e0b9a7d4c3e6f5a1b2c3d4e5f60";
+        let mut config = DatasetConfiguration::from_json(json!({}));
+        config.PUBLIC_DATASET.enabled = true;
+        config.PUBLIC_DATASET.config_signing_secret = Some(secret.to_string());
+        let mut dataset = Dataset::from_details(
+            "Trieve".to_string(),
+            uuid::Uuid::new_v4(),
+            None,
+            config.clone(),
+        );
+        // Stored configurations keep the secret, which is what update_dataset_query returns
+        dataset.server_configuration = config.to_json();
+        assert!(dataset.server_configuration.to_string().contains(secret));
+
+        let updated = serde_json::to_string(&without_secrets(dataset.clone())).unwrap();
+        assert!(!updated.contains(secret));
+        assert!(!updated.contains("config_signing_secret"));
+
+        let listed = serde_json::to_string(&vec![DatasetAndUsage::from_components(
+            dataset.clone().into(),
+            DatasetUsageCount::from_details(dataset.id, 0),
+        )])
+        .unwrap();
+        assert!(!listed.contains(secret));
+    }
 }
@@ -29,7 +29,7 @@ use itertools::Itertools;
 use openai_dive::v1::{
     api::Client,
     resources::chat::{
-        ChatCompletionChoice, ChatCompletionParameters, ChatMessage, ChatMessageContent,
+        ChatCompletionParameters, ChatCompletionResponse, ChatMessage, ChatMessageContent,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -714,6 +714,42 @@ pub struct SuggestedQueriesReqPayload {
     pub filters: Option<ChunkFilter>,
 }
 
+/// Text of the first choice of a completion, empty if it has none.
+fn get_completion_text(completion: &ChatCompletionResponse) -> String {
+    match completion.choices.first().map(|choice| &choice.message) {
+        Some(ChatMessage::User {
+            content: ChatMessageContent::Text(content),
+            ..
+        })
+        | Some(ChatMessage::System {
+            content: ChatMessageContent::Text(content),
+            ..
+        })
+        | Some(ChatMessage::Assistant {
+            content: Some(ChatMessageContent::Text(content)),
+            ..
+        }) => content.clone(),
+        _ => "".to_string(),
+    }
+}
+
+/// Responds with the dataset's public page `default_search_queries` when the LLM misbehaves.
+fn fall_back_to_default_search_queries(
+    dataset_id: uuid::Uuid,
+    default_search_queries: Vec<String>,
+    reason: String,
+) -> HttpResponse {
+    log::warn!(
+        "Falling back to default_search_queries for suggested queries of dataset {}: {}",
+        dataset_id,
+        reason
+    );
+
+    HttpResponse::Ok().json(SuggestedQueriesResponse {
+        queries: default_search_queries,
+    })
+}
+
 #[derive(Deserialize, Serialize, Debug, ToSchema)]
 pub struct SuggestedQueriesResponse {
     pub queries: Vec<String>,
@@ -721,7 +757,7 @@ pub struct SuggestedQueriesResponse {
 
 /// Generate suggested queries
 ///
-/// This endpoint will generate 3 suggested queries based off a hybrid search using RAG with the query provided in the request body and return them as a JSON object.
+/// This endpoint will generate 3 suggested queries based off a hybrid search using RAG with the query provided in the request body and return them as a JSON object. The LLM call is limited to `SUGGESTED_QUERIES_LLM_TIMEOUT_MS` (10 seconds by default), `SUGGESTED_QUERIES_LLM_MAX_TOKENS` (512 by default) and responses of `SUGGESTED_QUERIES_LLM_MAX_RESPONSE_CHARS` (4000 by default), past which the dataset's public page `default_search_queries` are returned instead.
 #[utoipa::path(
     post,
     path = "/chunk/suggestions",
@@ -774,6 +810,12 @@ pub async fn get_suggested_queries(
     };
     let search_type = data.search_type.clone().unwrap_or(SearchMethod::Hybrid);
     let filters = data.filters.clone();
+    let default_search_queries = dataset_config
+        .PUBLIC_DATASET
+        .extra_params
+        .as_ref()
+        .and_then(|params| params.default_search_queries.clone())
+        .unwrap_or_default();
 
     let chunk_metadatas = match data.query.clone() {
         Some(query) => {
@@ -788,6 +830,10 @@ pub async fn get_suggested_queries(
                 query,
                 quote_words: None,
                 negated_words: None,
+                dense_prefix: None,
+                dense_suffix: None,
+                sparse_synonyms: None,
+                cache_dense_embedding: false,
             };
             match search_type {
                 SearchMethod::Hybrid => search_hybrid_chunks(
@@ -925,7 +971,12 @@ pub async fn get_suggested_queries(
         top_p: None,
         n: None,
         stop: None,
-        max_completion_tokens: None,
+        max_completion_tokens: Some(
+            std::env::var("SUGGESTED_QUERIES_LLM_MAX_TOKENS")
+                .ok()
+                .and_then(|max_tokens| max_tokens.parse::<u32>().ok())
+                .unwrap_or(512),
+        ),
         presence_penalty: Some(0.8),
         frequency_penalty: Some(0.8),
         logit_bias: None,
@@ -948,82 +999,65 @@ pub async fn get_suggested_queries(
         organization: None,
     };
 
-    let mut query = client
-        .chat()
-        .create(parameters.clone())
-        .await
-        .map_err(|err| ServiceError::BadRequest(err.to_string()))?;
-
-    let mut queries: Vec<String> = match &query
-        .choices
-        .first()
-        .unwrap_or(&ChatCompletionChoice {
-            logprobs: None,
-            index: 0,
-            message: ChatMessage::User {
-                content: ChatMessageContent::Text("".to_string()),
-                name: None,
-            },
-            finish_reason: None,
-        })
-        .message
-    {
-        ChatMessage::User {
-            content: ChatMessageContent::Text(content),
-            ..
-        }
-        | ChatMessage::System {
-            content: ChatMessageContent::Text(content),
-            ..
-        }
-        | ChatMessage::Assistant {
-            content: Some(ChatMessageContent::Text(content)),
-            ..
-        } => content.clone(),
-        _ => "".to_string(),
-    }
-    .split('\n')
-    .filter_map(|query| {
-        let cleaned_query = query.to_string().trim().trim_matches('\n').to_string();
-        if cleaned_query.is_empty() {
-            None
-        } else {
-            Some(cleaned_query)
+    let llm_timeout = std::time::Duration::from_millis(
+        std::env::var("SUGGESTED_QUERIES_LLM_TIMEOUT_MS")
+            .ok()
+            .and_then(|timeout_ms| timeout_ms.parse::<u64>().ok())
+            .unwrap_or(10000),
+    );
+    let max_response_chars = std::env::var("SUGGESTED_QUERIES_LLM_MAX_RESPONSE_CHARS")
+        .ok()
+        .and_then(|max_chars| max_chars.parse::<usize>().ok())
+        .unwrap_or(4000);
+
+    // Completions are retried until they have 3 queries, so the timeout covers every attempt
+    let generated_queries = tokio::time::timeout(llm_timeout, async {
+        let mut queries: Vec<String> = vec![];
+
+        while queries.len() < 3 {
+            let completion = client
+                .chat()
+                .create(parameters.clone())
+                .await
+                .map_err(|err| ServiceError::BadRequest(err.to_string()))?;
+
+            let content = get_completion_text(&completion);
+            if content.chars().count() > max_response_chars {
+                return Ok(None);
+            }
+
+            queries = content
+                .split('\n')
+                .map(|query| query.trim().to_string())
+                .filter(|query| !query.is_empty())
+                .collect();
         }
+
+        Ok::<Option<Vec<String>>, ServiceError>(Some(queries))
     })
-    .map(|query| query.to_string().trim().trim_matches('\n').to_string())
-    .collect();
-
-    while queries.len() < 3 {
-        query = client
-            .chat()
-            .create(parameters.clone())
-            .await
-            .expect("No LLM Completion for topic");
-        queries = match &query
-            .choices
-            .first()
-            .expect("No response for LLM completion")
-            .message
-        {
-            ChatMessage::User {
-                content: ChatMessageContent::Text(content),
-                ..
-            }
-            | ChatMessage::System {
-                content: ChatMessageContent::Text(content),
-                ..
-            }
-            | ChatMessage::Assistant {
-                content: Some(ChatMessageContent::Text(content)),
-                ..
-            } => content.clone(),
-            _ => "".to_string(),
+    .await;
+
+    let mut queries = match generated_queries {
+        Ok(Ok(Some(queries))) => queries,
+        Ok(Err(err)) => return Err(err),
+        Ok(Ok(None)) => {
+            return Ok(fall_back_to_default_search_queries(
+                dataset_id,
+                default_search_queries,
+                format!(
+                    "the LLM response was longer than {} characters",
+                    max_response_chars
+                ),
+            ))
         }
-        .split('\n')
-        .map(|query| query.to_string().trim().trim_matches('\n').to_string())
-        .collect();
-    }
+        Err(_) => {
+            return Ok(fall_back_to_default_search_queries(
+                dataset_id,
+                default_search_queries,
+                format!("the LLM took longer than {}ms", llm_timeout.as_millis()),
+            ))
+        }
+    };
 
     let mut engine: SimSearch<String> = SimSearch::new();
 
@@ -1,6 +1,14 @@
-use crate::{data::models::RedisPool, errors::ServiceError};
+use crate::{
+    data::models::{RedisPool, SearchMethod},
+    errors::ServiceError,
+};
 use actix_web::{web, HttpResponse};
-use prometheus::{opts, register_counter_vec, CounterVec, Encoder, Error, Gauge, Registry};
+use dashmap::DashMap;
+use prometheus::{
+    histogram_opts, opts, register_counter_vec, Counter, CounterVec, Encoder, Error, Gauge,
+    HistogramVec, Registry,
+};
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct Metrics {
@@ -16,6 +24,11 @@ pub struct Metrics {
     pub pgbulk_queue_gauge: Gauge,
     pub pgbulk_processing_gauge: Gauge,
     pub api_error_gauge: CounterVec,
+    pub public_search_shed_counter: Counter,
+    pub public_search_latency_histogram: HistogramVec,
+    pub public_search_results_histogram: HistogramVec,
+    pub public_search_error_counter: CounterVec,
+    public_search_dataset_labels: Arc<DashMap<uuid::Uuid, ()>>,
 }
 
 impl Metrics {
@@ -74,6 +87,41 @@ impl Metrics {
         )?;
         registry.register(Box::new(api_error_gauge.clone()))?;
 
+        let public_search_shed_counter = Counter::new(
+            "tr_public_search_shed",
+            "number of public searches rejected for exceeding PUBLIC_SEARCH_MAX_CONCURRENCY",
+        )?;
+        registry.register(Box::new(public_search_shed_counter.clone()))?;
+
+        let public_search_latency_histogram = HistogramVec::new(
+            histogram_opts!(
+                "tr_public_search_latency_seconds",
+                "latency of successful public searches",
+                vec![0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.5, 5.0, 10.0]
+            ),
+            &["dataset_id", "search_type"],
+        )?;
+        registry.register(Box::new(public_search_latency_histogram.clone()))?;
+
+        let public_search_results_histogram = HistogramVec::new(
+            histogram_opts!(
+                "tr_public_search_results",
+                "number of results returned by successful public searches",
+                vec![0.0, 1.0, 5.0, 10.0, 25.0, 50.0, 100.0]
+            ),
+            &["dataset_id", "search_type"],
+        )?;
+        registry.register(Box::new(public_search_results_histogram.clone()))?;
+
+        let public_search_error_counter = CounterVec::new(
+            opts!(
+                "tr_public_search_errors",
+                "number of failed public searches"
+            ),
+            &["dataset_id", "error_code"],
+        )?;
+        registry.register(Box::new(public_search_error_counter.clone()))?;
+
         Ok(Metrics {
             registry,
             ingest_queue_gauge,
@@ -87,9 +135,61 @@ impl Metrics {
             ingest_processing_gauge,
             group_update_processing_gauge,
             api_error_gauge,
+            public_search_shed_counter,
+            public_search_latency_histogram,
+            public_search_results_histogram,
+            public_search_error_counter,
+            public_search_dataset_labels: Arc::new(DashMap::new()),
         })
     }
 
+    /// Labels public search metrics with the dataset id for the first
+    /// `PUBLIC_SEARCH_METRICS_MAX_DATASETS` (100 by default) datasets searched since the server
+    /// started and with `other` for the rest, which keeps the number of series bounded.
+    fn public_search_dataset_label(&self, dataset_id: uuid::Uuid) -> String {
+        let max_datasets = std::env::var("PUBLIC_SEARCH_METRICS_MAX_DATASETS")
+            .ok()
+            .and_then(|max_datasets| max_datasets.parse::<usize>().ok())
+            .unwrap_or(100);
+
+        if self.public_search_dataset_labels.contains_key(&dataset_id) {
+            return dataset_id.to_string();
+        }
+
+        if self.public_search_dataset_labels.len() < max_datasets {
+            self.public_search_dataset_labels.insert(dataset_id, ());
+            return dataset_id.to_string();
+        }
+
+        "other".to_string()
+    }
+
+    pub fn record_public_search(
+        &self,
+        dataset_id: uuid::Uuid,
+        search_type: &SearchMethod,
+        latency: std::time::Duration,
+        result_count: usize,
+    ) {
+        let dataset_label = self.public_search_dataset_label(dataset_id);
+        let search_type = search_type.to_string();
+
+        self.public_search_latency_histogram
+            .with_label_values(&[&dataset_label, &search_type])
+            .observe(latency.as_secs_f64());
+        self.public_search_results_histogram
+            .with_label_values(&[&dataset_label, &search_type])
+            .observe(result_count as f64);
+    }
+
+    pub fn record_public_search_error(&self, dataset_id: uuid::Uuid, error_code: u16) {
+        let dataset_label = self.public_search_dataset_label(dataset_id);
+
+        self.public_search_error_counter
+            .with_label_values(&[&dataset_label, &error_code.to_string()])
+            .inc();
+    }
+
     pub fn register_error(&self, error_code: u16, method: String, route: String, base_url: String) {
         // register error
         self.api_error_gauge
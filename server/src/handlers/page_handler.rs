@@ -1,19 +1,64 @@
 use super::{
-    auth_handler::LoggedUser,
-    chunk_handler::{ChunkFilter, ScoringOptions},
+    auth_handler::{AdminOnly, LoggedUser},
+    chunk_handler::{
+        parse_query, ChunkFilter, ParsedQuery, ParsedQueryTypes, ScoringOptions,
+        SearchChunkQueryResponseBody, SearchChunksReqPayload,
+    },
+    dataset_handler::TagsWithCount,
+    metrics_handler::Metrics,
 };
 use crate::data::models::Templates;
 use crate::{
-    data::models::{DatasetConfiguration, Pool, SearchMethod, SortOptions, TypoOptions, UnifiedId},
-    errors::ServiceError,
+    data::models::{
+        escape_quotes, ChunkMetadata, ChunkMetadataStringTagSet, ChunkMetadataTypes, ConditionType,
+        Dataset, DatasetConfiguration, DistanceMetric, HasIDCondition, Pool, PublicDatasetOptions,
+        PublicEmptyQueryBehavior, QdrantSortBy, QueryTypes, RedisPool, ScoreChunk, ScoreChunkDTO,
+        SearchMethod, SearchQueryEventClickhouse, SortOptions, TypoOptions, UnifiedId,
+    },
+    errors::{ErrorResponseBody, ServiceError},
     get_env,
-    operators::dataset_operator::get_dataset_by_id_query,
+    middleware::auth_middleware::verify_admin,
+    operators::{
+        analytics_operator::{get_chunk_click_through_rates_query, get_popular_queries_query},
+        chunk_operator::{
+            dataset_has_metadata_key_query, get_metadata_from_ids_query, get_stop_words,
+        },
+        clickhouse_operator::{get_latency_from_header, ClickHouseEvent, EventQueue},
+        dataset_operator::{
+            get_dataset_by_id_query, get_datasets_by_ids_query, get_tag_facets_for_chunks_query,
+            update_dataset_query,
+        },
+        group_operator::{
+            get_groups_for_bookmark_query, get_groups_matching_title_query,
+            get_neighbor_chunk_ids_in_groups_query, GroupsForChunk,
+        },
+        model_operator::{get_cached_dense_vector, get_dense_vector, is_supported_bm25_language},
+        organization_operator::get_assumed_user_by_organization_api_key,
+        parse_operator::{convert_html_to_text, strip_markdown},
+        qdrant_operator::{
+            get_approximate_point_count_query, get_qdrant_dense_vectors_query,
+            qdrant_filter_to_json, scroll_dataset_points,
+        },
+        search_operator::{
+            assemble_qdrant_filter, get_component_scores_for_points, get_max_rerank_candidates,
+            retrieve_chunks_from_point_ids, search_chunks_query,
+            search_hybrid_chunks_before_rerank_deadline, SearchChunkQueryResult,
+        },
+        typo_operator::correct_query,
+        user_operator::get_user_from_api_key_query,
+    },
 };
-use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::{http::header, web, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder};
+use dashmap::DashMap;
+use itertools::Itertools;
+use lazy_static::lazy_static;
 use minijinja::context;
 use serde::{Deserialize, Serialize};
+use simple_server_timing_header::Timer;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use utoipa::ToSchema;
+use std::hash::{Hash, Hasher};
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema, Default)]
 pub enum PublicPageTheme {
@@ -25,7 +70,7 @@ pub enum PublicPageTheme {
 }
 
 // Duplicate of SearchChunksReqPayload but without "query"
-#[derive(Serialize, Clone, Debug, ToSchema, Deserialize)]
+#[derive(Serialize, Clone, Debug, ToSchema, Deserialize, Default)]
 #[schema(example = json!({
     "search_type": "semantic",
     "filters": {
@@ -53,7 +98,8 @@ pub enum PublicPageTheme {
             }
         ]
     },
-    "score_threshold": 0.5
+    "score_threshold": 0.5,
+    "fallback_search_type": "semantic"
 }))]
 pub struct PublicPageSearchOptions {
     /// Can be either "semantic", "fulltext", "hybrid, or "bm25". If specified as "hybrid", it will pull in one page of both semantic and full-text results then re-rank them using scores from a cross encoder model. "semantic" will pull in one page of the nearest cosine distant vectors. "fulltext" will pull in one page of full-text results based on SPLADE. "bm25" will get one page of results scored using BM25 with the terms OR'd together.
@@ -62,7 +108,7 @@ pub struct PublicPageSearchOptions {
     /// Page of chunks to fetch. Page is 1-indexed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page: Option<u64>,
-    /// Page size is the number of chunks to fetch. This can be used to fetch more than 10 chunks at a time.
+    /// Page size is the number of chunks to fetch. This can be used to fetch more than 10 chunks at a time. A page_size of 0 returns no chunks, only metadata such as `corrected_query`, `total_pages`, `approximate_total` and `tag_facets`. The query is then run with slim chunks and the default page size of 10, so `total_pages` and `tag_facets` are for pages of 10 chunks.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_size: Option<u64>,
     /// Get total page count for the query accounting for the applied filters. Defaults to false, but can be set to true when the latency penalty is acceptable (typically 50-200ms).
@@ -80,7 +126,7 @@ pub struct PublicPageSearchOptions {
     /// Set score_threshold to a float to filter out chunks with a score below the threshold for cosine distance metric. For Manhattan Distance, Euclidean Distance, and Dot Product, it will filter out scores above the threshold distance. This threshold applies before weight and bias modifications. If not specified, this defaults to no threshold. A threshold of 0 will default to no threshold.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub score_threshold: Option<f32>,
-    /// Set slim_chunks to true to avoid returning the content and chunk_html of the chunks. This is useful for when you want to reduce amount of data over the wire for latency improvement (typically 10-50ms). Default is false.
+    /// Set slim_chunks to true to avoid returning the content and chunk_html of the chunks. This is useful for when you want to reduce amount of data over the wire for latency improvement (typically 10-50ms). Slim chunks still include `image_urls`, a list of nullable url strings, so image widgets can render thumbnails without full chunks. Default is false.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub slim_chunks: Option<bool>,
     /// Set content_only to true to only returning the chunk_html of the chunks. This is useful for when you want to reduce amount of data over the wire for latency improvement (typically 10-50ms). Default is false.
@@ -89,9 +135,12 @@ pub struct PublicPageSearchOptions {
     /// If true, quoted and - prefixed words will be parsed from the queries and used as required and negated words respectively. Default is false.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_quote_negated_terms: Option<bool>,
-    /// If true, stop words (specified in server/src/stop-words.txt in the git repo) will be removed. Queries that are entirely stop words will be preserved.
+    /// If true, stop words (specified in server/src/stop-words.txt in the git repo) will be removed. Queries that would be left with fewer than `min_terms_after_stopword_removal` terms are preserved.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remove_stop_words: Option<bool>,
+    /// Minimum number of non stop words the query must contain for `remove_stop_words` to be applied, e.g. 2 keeps "the it" and "the docs" intact while still stripping stop words from longer queries. Default is 1, which only preserves queries that are entirely stop words.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_terms_after_stopword_removal: Option<usize>,
     /// User ID is the id of the user who is making the request. This is used to track user interactions with the search results.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_id: Option<String>,
@@ -101,6 +150,119 @@ pub struct PublicPageSearchOptions {
     /// Enables autocomplete on the search modal.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_autocomplete: Option<bool>,
+    /// Search method to retry with when the primary search returns no results above the score threshold. Only one fallback is attempted. If not specified, no fallback search is run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_search_type: Option<SearchMethod>,
+    /// Set explain to true to return a breakdown of the dense, fulltext, BM25 and rerank scores behind each result. Only honored for logged in members of the dataset's organization. Each component requires an extra Qdrant query and adds an entry per result to the response, so leave this off outside of relevance debugging. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<bool>,
+    /// Set include_group_metadata to true to return the groups each result belongs to, e.g. for rendering group headers. Only honored when the public page has `use_group_search` enabled. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_group_metadata: Option<bool>,
+    /// Set case_sensitive to true to match the query against the dataset's public `synonyms` without lowercasing it, e.g. for case-significant identifiers like code symbols. Only affects the fulltext and BM25 query; the dense query is never lowercased. The SPLADE and BM25 models still apply their own tokenization. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_sensitive: Option<bool>,
+    /// Ids of chunks which must not be returned, e.g. the results already shown before a "load more". They are added to the `must_not` filter, so excluded chunks don't take up slots and the next results move up: keep requesting page 1 while growing exclude_ids rather than also incrementing `page`. At most `PUBLIC_SEARCH_MAX_EXCLUDE_IDS` (200 by default) ids are accepted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_ids: Option<Vec<uuid::Uuid>>,
+    /// Set include_tag_facets to true to get the tags of the returned results with the number of results carrying each, read from the tag index. At most `PUBLIC_SEARCH_MAX_TAG_FACETS` (50 by default) tags are returned, most common first. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_tag_facets: Option<bool>,
+    /// Name of the embedding model to run the dense retrieval with. It must be one of the dataset's configured embedding models, otherwise a 400 is returned. Datasets currently have a single model, `EMBEDDING_MODEL_NAME` of the dataset's server configuration, which is also the default. Result ordering may change between models, so keep the same model for the length of a session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    /// Number of chunks before and after each result to return in `neighbor_chunks`, taken from the result's first group in the order its chunks were created, e.g. the neighboring sections of a file. Each result can add up to twice this many chunks to the response, so keep it small. Capped at `PUBLIC_SEARCH_MAX_NEIGHBOR_CHUNKS` (2 by default). Results outside of any group have no neighbors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub neighbor_chunks: Option<usize>,
+    /// Set approximate_count to true to get `approximate_total`, a cheap estimate of the number of results for e.g. a "~1,200 results" label, without the cost of `get_total_pages`. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approximate_count: Option<bool>,
+    /// Set include_matched_fields to true to get `matched_fields`, the fields of each result which contain a word of the query, e.g. to show "matched in: title". Only honored for fulltext, bm25 and hybrid searches. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_matched_fields: Option<bool>,
+    /// Weights for the fields a fulltext, bm25 or hybrid result matched the query in, e.g. {"metadata.title": 2.0, "chunk_html": 1.0}. Field names are the same as in `matched_fields`. The score of each result is multiplied by the mean weight of the fields it matched, so title matches can rank above body matches. Fields which aren't listed have a weight of 1, so by default all fields are weighted uniformly. Weights must be non-negative. Only results of the requested page are reordered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_query_weights: Option<HashMap<String, f32>>,
+    /// Seed for shuffling results whose scores are within `PUBLIC_SEARCH_ORDERING_SEED_SCORE_BAND` (0.01 by default) of each other, e.g. one seed per session for A/B testing result presentation. The same seed and results always give the same order. When unset, tied results are ordered by chunk id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ordering_seed: Option<u64>,
+    /// Trim the content of each result to its first preview_chars characters, cut at a word boundary with an ellipsis added, to keep the response small. The `chunk_html` of trimmed results is replaced with its text content. For fulltext, bm25 and hybrid searches the preview starts shortly before the first word of the result which matches the query, so the match stays in view. Unset returns the full content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview_chars: Option<usize>,
+    /// Set strip_markdown to true to return the content of each result as plain text without Markdown syntax, e.g. `**bold**` becomes `bold`, for widgets which render text as is. HTML tags are removed first, so content mixing HTML and Markdown comes back as plain text too, and `preview_chars` trims the stripped text. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_markdown: Option<bool>,
+    /// Top level metadata key to group the results by in `result_groups`, e.g. `product_id` to group the variants of a product. Unlike group search this does not use Trieve groups and does not change `chunks`. Results without the key are grouped under null. At most `PUBLIC_SEARCH_MAX_RESULT_GROUPS` (20 by default) groups are returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_results_by: Option<String>,
+    /// Set normalize_scores to true to return scores on a 0..1 relevance scale, e.g. for rendering relevance bars, instead of the raw scores whose range depends on the search type and the dataset's `DISTANCE_METRIC`. Semantic scores are mapped per metric: cosine and dot as `(score + 1) / 2` clamped to 0..1, which assumes normalized embeddings for dot, and euclidean and manhattan distances as `1 / (1 + distance)`. Fulltext and BM25 scores, which are unbounded and non negative, are mapped as `score / (1 + score)`, and hybrid scores, which come from the reranker, as `1 / (1 + e^-score)`. The mapping is monotonic, so result order is unchanged, and `explanations` keep their raw scores. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_scores: Option<bool>,
+    /// Set include_timings to true to get `timings`, how long embedding the query, retrieval and reranking took, to tell where a slow search spent its time without backend access. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_timings: Option<bool>,
+    /// Set dedupe_by_content to true to drop results whose content is identical to a higher scoring result, e.g. for datasets where the same content was ingested more than once under different ids. Content is compared by a hash of its text, lowercased with HTML tags removed and runs of whitespace collapsed to one space, so formatting differences don't count. Results up to the dataset's public `candidate_multiplier` times the end of the requested page are fetched and compared so the page stays full, pages beyond `PUBLIC_SEARCH_MAX_CANDIDATES` results only compare their own results and may have fewer than page_size results. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedupe_by_content: Option<bool>,
+    /// Set debug_filter to true to get `debug_filter`, the Qdrant filter the search's `filters` and quoted and negated query terms were compiled to, to debug unexpected results of complex filters. Words quoted by typo correction are not included. Only honored for logged in members of the dataset's organization. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_filter: Option<bool>,
+    /// Set search_document_titles to true to also return documents whose group name contains words of the query when none of their chunks matched, e.g. a page titled "Billing" for the query "billing". Each such document is represented by its first chunk and scored `0.9 * share of the query's words in the title * the best chunk score of the page`, so a title containing every word ranks just below the best content match and partial title matches rank lower. Results already on the page are not added twice and the page is cut back to page_size. Only honored on the first page of searches without `filters` or `exclude_ids`, and not for semantic searches of datasets with a euclidean or manhattan distance metric, where lower scores are better. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_document_titles: Option<bool>,
+    /// Set include_vectors to true to get `vectors`, the dense embedding of each result, e.g. for client side clustering or similarity maps. Each vector has `EMBEDDING_SIZE` floats, several KB of JSON per result, so the response is many times larger than without it. page_size is capped at `PUBLIC_SEARCH_MAX_VECTORS_PAGE_SIZE` (20 by default) when it is set, and setting that to 0 disables the option. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_vectors: Option<bool>,
+    /// Set check_next_page to true to get `has_next_page`, whether a result exists after the requested page, e.g. to disable a next button without the cost of `get_total_pages`. One more result than requested is fetched and dropped before returning. Pages ending beyond `PUBLIC_SEARCH_MAX_CANDIDATES` (100 by default) results are not checked since the results before them are fetched too. Default is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_next_page: Option<bool>,
+}
+
+impl PublicPageSearchOptions {
+    pub fn into_search_chunks_req_payload(
+        self,
+        query: String,
+        search_type: SearchMethod,
+    ) -> SearchChunksReqPayload {
+        SearchChunksReqPayload {
+            search_type,
+            query: QueryTypes::Single(query),
+            page: self.page,
+            page_size: self.page_size,
+            get_total_pages: self.get_total_pages,
+            filters: match self.exclude_ids {
+                Some(exclude_ids) if !exclude_ids.is_empty() => {
+                    let mut filters = self.filters.unwrap_or(ChunkFilter {
+                        should: None,
+                        must: None,
+                        must_not: None,
+                        jsonb_prefilter: None,
+                    });
+
+                    filters
+                        .must_not
+                        .get_or_insert_with(Vec::new)
+                        .push(ConditionType::HasID(HasIDCondition {
+                            ids: Some(exclude_ids),
+                            tracking_ids: None,
+                        }));
+
+                    Some(filters)
+                }
+                _ => self.filters,
+            },
+            sort_options: self.sort_options,
+            scoring_options: self.scoring_options,
+            highlight_options: None,
+            score_threshold: self.score_threshold,
+            slim_chunks: self.slim_chunks,
+            content_only: self.content_only,
+            use_quote_negated_terms: self.use_quote_negated_terms,
+            remove_stop_words: self.remove_stop_words,
+            user_id: self.user_id,
+            typo_options: self.typo_options,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema, Default)]
@@ -126,6 +288,16 @@ pub struct PublicPageTabMessage {
     show_component_code: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, Default)]
+pub struct PopularSuggestedQueriesOptions {
+    /// Number of popular queries to suggest. Defaults to 5.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_n: Option<u64>,
+    /// Number of days of search analytics to count queries over. Defaults to 30.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_days: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PublicPageParameters {
@@ -174,93 +346,4749 @@ pub struct PublicPageParameters {
     pub default_currency: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub currency_position: Option<String>,
+    /// BCP-47 locale, e.g. `de-DE`, the widget formats numbers and prices with. It is passed through to the widget as is, invalid locales fall back to `en-US`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_locale: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub debounce_ms: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hero_pattern: Option<HeroPattern>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tab_messages: Option<Vec<PublicPageTabMessage>>,
+    /// Where results link to: `link` for the chunk's link, or a top level key of the chunks' metadata holding the url. Metadata keys must exist on at least one chunk of the dataset. Defaults to `link`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_field: Option<String>,
+    /// Public capabilities of the dataset by name, e.g. `tag_facets` or `neighbor_chunks`, and whether they are enabled, so the widget can hide what it can't use. Features the server doesn't know about are absent rather than false. Set by the server when the page is served.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub features: Option<HashMap<String, bool>>,
+    /// Version of the dataset's configuration in milliseconds since the epoch of its last update. Compare it against `GET /api/public_page/{dataset_id}/version` to check whether cached parameters are stale. Set by the server when the page is served.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_version: Option<i64>,
+    /// These parameters as an HS256 JWT signed with the dataset's public `config_signing_secret`, to verify they came from Trieve unmodified. Its claims are `iss` (`trieve`), `sub` (the dataset id), `iat`, `exp` (`PUBLIC_PAGE_SIGNED_CONFIG_TTL_SECS`, 3600 by default, after `iat`) and `params`, the parameters without `apiKey` and `signedConfig`. Only present when the dataset sets a `config_signing_secret`. Set by the server when the page is served.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed_config: Option<String>,
+}
+
+/// Largest page_size of public searches which set `include_vectors`, 0 disables the option.
+fn get_max_public_vectors_page_size() -> u64 {
+    env::var("PUBLIC_SEARCH_MAX_VECTORS_PAGE_SIZE")
+        .ok()
+        .and_then(|max_page_size| max_page_size.parse::<u64>().ok())
+        .unwrap_or(20)
+}
+
+/// Most results public searches fetch to over-fetch for the dataset's public
+/// `candidate_multiplier`.
+fn get_max_public_candidates() -> u64 {
+    env::var("PUBLIC_SEARCH_MAX_CANDIDATES")
+        .ok()
+        .and_then(|max_candidates| max_candidates.parse::<u64>().ok())
+        .unwrap_or(100)
+}
+
+/// The page_size to fetch the first page with so the requested page stays full after results are
+/// dropped. None when the multiplier doesn't over-fetch or the requested page ends beyond
+/// `max_candidates`.
+fn get_candidate_page_size(
+    page: u64,
+    page_size: u64,
+    candidate_multiplier: f32,
+    max_candidates: u64,
+) -> Option<u64> {
+    if !candidate_multiplier.is_finite() || candidate_multiplier <= 1.0 {
+        return None;
+    }
+
+    let page_end = page.max(1) * page_size;
+    let candidates = ((page_end as f64) * candidate_multiplier as f64).ceil() as u64;
+
+    Some(candidates.min(max_candidates)).filter(|candidates| *candidates > page_end)
+}
+
+fn get_max_public_neighbor_chunks() -> usize {
+    env::var("PUBLIC_SEARCH_MAX_NEIGHBOR_CHUNKS")
+        .ok()
+        .and_then(|max_neighbor_chunks| max_neighbor_chunks.parse::<usize>().ok())
+        .unwrap_or(2)
+}
+
+/// Which public capabilities the dataset's config and the server's environment allow. Search
+/// methods other than a forced `force_search_type` are reported as disabled since they would be
+/// overridden.
+fn get_public_page_features(config: &DatasetConfiguration) -> HashMap<String, bool> {
+    let bm25_active = env::var("BM25_ACTIVE").unwrap_or("false".to_string()) == "true";
+    let forced_search_type = config.PUBLIC_DATASET.force_search_type.as_ref();
+    let search_type_allowed = |search_type: SearchMethod| {
+        forced_search_type.map_or(true, |forced_search_type| {
+            *forced_search_type == search_type
+        })
+    };
+
+    [
+        (
+            "semantic_search",
+            config.SEMANTIC_ENABLED && search_type_allowed(SearchMethod::Semantic),
+        ),
+        (
+            "fulltext_search",
+            config.FULLTEXT_ENABLED && search_type_allowed(SearchMethod::FullText),
+        ),
+        (
+            "bm25_search",
+            config.BM25_ENABLED && bm25_active && search_type_allowed(SearchMethod::BM25),
+        ),
+        (
+            "hybrid_search",
+            config.SEMANTIC_ENABLED
+                && config.FULLTEXT_ENABLED
+                && search_type_allowed(SearchMethod::Hybrid),
+        ),
+        ("suggested_queries", true),
+        ("correct_query", true),
+        ("tag_facets", true),
+        ("approximate_count", true),
+        ("matched_fields", true),
+        ("neighbor_chunks", get_max_public_neighbor_chunks() > 0),
+        ("include_vectors", get_max_public_vectors_page_size() > 0),
+    ]
+    .into_iter()
+    .map(|(feature, enabled)| (feature.to_string(), enabled))
+    .collect()
+}
+
+/// Config versions increase with every dataset update, so clients can compare them as integers.
+fn get_config_version(dataset: &Dataset) -> i64 {
+    dataset.updated_at.and_utc().timestamp_millis()
+}
+
+/// Rejects a public page `url_field` which is neither `link` nor a metadata key present on the
+/// dataset's chunks, so misconfigured pages don't render results which link nowhere.
+pub async fn validate_public_page_url_field(
+    params: &PublicPageParameters,
+    dataset_id: uuid::Uuid,
+    pool: web::Data<Pool>,
+) -> Result<(), ServiceError> {
+    let Some(url_field) = params.url_field.as_ref() else {
+        return Ok(());
+    };
+
+    if url_field == "link" {
+        return Ok(());
+    }
+
+    if url_field.trim().is_empty()
+        || !dataset_has_metadata_key_query(dataset_id, url_field.clone(), pool).await?
+    {
+        return Err(ServiceError::BadRequest(format!(
+            "url_field must be link or a metadata key of the dataset's chunks, {:?} is neither",
+            url_field
+        )));
+    }
+
+    Ok(())
+}
+
+/// Response for a public page whose dataset does not exist. Set `PUBLIC_PAGE_NOT_FOUND_FORMAT` to `html` to render a "search unavailable" page so embeds with a mistyped dataset id degrade gracefully, otherwise a JSON error body is returned.
+fn public_page_not_found(templates: &Templates<'_>) -> Result<HttpResponse, ServiceError> {
+    let message = "This search is unavailable. Check that the dataset id is correct.".to_string();
+
+    let format = env::var("PUBLIC_PAGE_NOT_FOUND_FORMAT").unwrap_or("json".to_string());
+
+    if format != "html" {
+        return Err(ServiceError::NotFound(message));
+    }
+
+    let templ = templates.get_template("not-found.html").unwrap();
+    let response_body = templ.render(context! { message }).unwrap();
+
+    Ok(HttpResponse::NotFound()
+        .content_type("text/html")
+        .body(response_body))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageRenderError {
+    /// The template error which made the public page fail to render.
+    pub message: String,
+    pub occurred_at: chrono::NaiveDateTime,
+}
+
+lazy_static! {
+    static ref LAST_RENDER_ERRORS: DashMap<uuid::Uuid, PublicPageRenderError> = DashMap::new();
+    static ref PUBLIC_SEARCH_PERMITS: Option<tokio::sync::Semaphore> =
+        env::var("PUBLIC_SEARCH_MAX_CONCURRENCY")
+            .ok()
+            .and_then(|max_concurrency| max_concurrency.parse::<usize>().ok())
+            .filter(|max_concurrency| *max_concurrency > 0)
+            .map(tokio::sync::Semaphore::new);
+    static ref PUBLIC_SEARCH_RATE_LIMITS: DashMap<uuid::Uuid, PublicSearchRateLimitBucket> =
+        DashMap::new();
+    static ref DEFAULT_QUERY_REDACTION_PATTERNS: Vec<regex::Regex> = vec![
+        regex::Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
+            .expect("Email redaction pattern should be valid"),
+        regex::Regex::new(r"\d{6,}").expect("Digit run redaction pattern should be valid"),
+    ];
+}
+
+/// Replaces emails, runs of 6 or more digits and matches of the dataset's public
+/// `query_redaction_patterns` with `[REDACTED]` so public queries can be stored in analytics.
+fn redact_public_query(query: &str, config: &DatasetConfiguration) -> String {
+    let dataset_patterns = config
+        .PUBLIC_DATASET
+        .query_redaction_patterns
+        .iter()
+        .flatten()
+        .filter_map(|pattern| match regex::Regex::new(pattern) {
+            Ok(pattern) => Some(pattern),
+            Err(err) => {
+                log::warn!(
+                    "Skipping invalid query_redaction_pattern {:?}: {}",
+                    pattern,
+                    err
+                );
+                None
+            }
+        })
+        .collect::<Vec<regex::Regex>>();
+
+    DEFAULT_QUERY_REDACTION_PATTERNS
+        .iter()
+        .chain(dataset_patterns.iter())
+        .fold(query.to_string(), |query, pattern| {
+            pattern.replace_all(&query, "[REDACTED]").into_owned()
+        })
+}
+
+/// Remembers the render error of the dataset's public page for `get_last_render_error`. At most
+/// `PUBLIC_PAGE_MAX_RENDER_ERRORS` (1000 by default) datasets are kept, dropping the oldest error.
+fn record_render_error(dataset_id: uuid::Uuid, err: minijinja::Error) -> ServiceError {
+    log::error!(
+        "Failed to render public page for dataset {}: {:?}",
+        dataset_id,
+        err
+    );
+
+    let max_render_errors = env::var("PUBLIC_PAGE_MAX_RENDER_ERRORS")
+        .ok()
+        .and_then(|max_render_errors| max_render_errors.parse::<usize>().ok())
+        .unwrap_or(1000);
+
+    if !LAST_RENDER_ERRORS.contains_key(&dataset_id)
+        && LAST_RENDER_ERRORS.len() >= max_render_errors
+    {
+        let oldest = LAST_RENDER_ERRORS
+            .iter()
+            .min_by_key(|entry| entry.occurred_at)
+            .map(|entry| *entry.key());
+
+        if let Some(oldest) = oldest {
+            LAST_RENDER_ERRORS.remove(&oldest);
+        }
+    }
+
+    LAST_RENDER_ERRORS.insert(
+        dataset_id,
+        PublicPageRenderError {
+            message: err.to_string(),
+            occurred_at: chrono::Utc::now().naive_utc(),
+        },
+    );
+
+    ServiceError::InternalServerError("Failed to render public page".to_string())
 }
 
+/// Get Last Public Page Render Error
+///
+/// Get the last error the dataset's public page failed to render with since the server started, if any. The auth'ed user must be an admin or owner of the dataset's organization.
 #[utoipa::path(
     get,
-    path = "/public_page/{dataset_id}",
+    path = "/public_page/{dataset_id}/last_render_error",
     context_path = "/api",
     tag = "Public",
     responses(
-        (status = 200, description = "Public Page associated to the dataset"),
-        (status = 400, description = "Service error relating to loading the public page", body = ErrorResponseBody),
+        (status = 200, description = "The last render error of the public page, null if it has not failed to render", body = PublicPageRenderError),
+        (status = 403, description = "The user is not an admin of the dataset's organization", body = ErrorResponseBody),
         (status = 404, description = "Dataset not found", body = ErrorResponseBody)
     ),
     params(
-        ("dataset_id" = uuid::Uuid, Path, description = "The id of the organization you want to fetch."),
+        ("TR-Organization" = uuid::Uuid, Header, description = "The organization id to use for the request"),
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to get the last render error of."),
     ),
+    security(
+        ("ApiKey" = ["admin"]),
+    )
 )]
-pub async fn public_page(
+pub async fn get_last_render_error(
     dataset_id: web::Path<uuid::Uuid>,
     pool: web::Data<Pool>,
-    templates: Templates<'_>,
+    user: AdminOnly,
+) -> Result<HttpResponse, ServiceError> {
+    let dataset =
+        get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id.into_inner()), pool).await?;
+
+    if !verify_admin(&user, &dataset.organization_id) {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let last_render_error = LAST_RENDER_ERRORS
+        .get(&dataset.id)
+        .map(|entry| entry.value().clone());
+
+    Ok(HttpResponse::Ok().json(last_render_error))
+}
+
+/// Replaces the string values of keys which look like they hold a secret, e.g. `apiKey`, with
+/// `[REDACTED]`.
+fn redact_secret_values(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase().replace(['_', '-'], "");
+                let is_secret = ["apikey", "secret", "token", "password", "credential"]
+                    .iter()
+                    .any(|secret| key.contains(secret));
+
+                if is_secret && value.is_string() {
+                    *value = serde_json::Value::String("[REDACTED]".to_string());
+                } else {
+                    redact_secret_values(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_secret_values),
+        _ => {}
+    }
+}
+
+/// Get Effective Public Page Config
+///
+/// Get the dataset's fully resolved public configuration, PUBLIC_DATASET of its server configuration with `extra_params` resolved exactly as the public page uses them. Secrets such as api keys are redacted. The auth'ed user must be an admin or owner of the dataset's organization.
+#[utoipa::path(
+    get,
+    path = "/public_page/{dataset_id}/effective_config",
+    context_path = "/api",
+    tag = "Public",
+    responses(
+        (status = 200, description = "The effective public configuration of the dataset", body = PublicDatasetOptions),
+        (status = 403, description = "The user is not an admin of the dataset's organization", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody)
+    ),
+    params(
+        ("TR-Organization" = uuid::Uuid, Header, description = "The organization id to use for the request"),
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to get the effective public configuration of."),
+    ),
+    security(
+        ("ApiKey" = ["admin"]),
+    )
+)]
+pub async fn get_effective_public_config(
+    dataset_id: web::Path<uuid::Uuid>,
+    pool: web::Data<Pool>,
+    user: AdminOnly,
     req: HttpRequest,
 ) -> Result<HttpResponse, ServiceError> {
-    let dataset_id = dataset_id.into_inner();
+    let dataset =
+        get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id.into_inner()), pool).await?;
 
-    let dataset = get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool).await?;
+    if !verify_admin(&user, &dataset.organization_id) {
+        return Err(ServiceError::Forbidden);
+    }
 
-    let config = DatasetConfiguration::from_json(dataset.server_configuration);
+    let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    let effective_config = PublicDatasetOptions {
+        extra_params: Some(get_public_page_parameters(&dataset, &config, &req)),
+        ..config.PUBLIC_DATASET
+    };
+
+    let mut effective_config = serde_json::to_value(effective_config).map_err(|err| {
+        ServiceError::InternalServerError(format!(
+            "Failed to serialize effective public config: {}",
+            err
+        ))
+    })?;
+    redact_secret_values(&mut effective_config);
+
+    Ok(HttpResponse::Ok().json(effective_config))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[schema(example = json!({
+    "enabled": false
+}))]
+pub struct PublicPageEnabledReqPayload {
+    /// Whether the dataset's public page and public search should be served.
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageEnabledResponse {
+    /// Whether the dataset's public page is enabled after the request.
+    pub enabled: bool,
+}
+
+/// Set Public Page Enabled
+///
+/// Turn the dataset's public page on or off by setting `PUBLIC_DATASET.enabled` of its server configuration, leaving the rest of the configuration untouched, e.g. as a kill switch for a misbehaving embed. Setting the current state again is a no-op. Pages already served may stay in shared caches for up to their 5 minute max-age. The auth'ed user must be an admin or owner of the dataset's organization.
+#[utoipa::path(
+    post,
+    path = "/public_page/{dataset_id}/enabled",
+    context_path = "/api",
+    tag = "Public",
+    request_body(content = PublicPageEnabledReqPayload, description = "JSON request payload to enable or disable the public page", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The public page state after the update", body = PublicPageEnabledResponse),
+        (status = 403, description = "The user is not an admin of the dataset's organization", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody)
+    ),
+    params(
+        ("TR-Organization" = uuid::Uuid, Header, description = "The organization id to use for the request"),
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to enable or disable the public page of."),
+    ),
+    security(
+        ("ApiKey" = ["admin"]),
+    )
+)]
+pub async fn set_public_page_enabled(
+    dataset_id: web::Path<uuid::Uuid>,
+    data: web::Json<PublicPageEnabledReqPayload>,
+    pool: web::Data<Pool>,
+    user: AdminOnly,
+) -> Result<HttpResponse, ServiceError> {
+    let dataset =
+        get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id.into_inner()), pool.clone())
+            .await?;
+
+    if !verify_admin(&user, &dataset.organization_id) {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let mut config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    if config.PUBLIC_DATASET.enabled != data.enabled {
+        config.PUBLIC_DATASET.enabled = data.enabled;
+        update_dataset_query(dataset.id, dataset.name, config, None, pool).await?;
+
+        log::info!(
+            "Public page of dataset {} was {}",
+            dataset.id,
+            if data.enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    Ok(HttpResponse::Ok().json(PublicPageEnabledResponse {
+        enabled: data.enabled,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[schema(example = json!({
+    "dataset_ids": ["e3e3e3e3-e3e3-e3e3-e3e3-e3e3e3e3e3e3"]
+}))]
+pub struct PublicPageConfigsReqPayload {
+    /// Ids of the datasets to get the public page configuration of. At most `PUBLIC_PAGE_MAX_BULK_CONFIGS` (100 by default) ids are accepted.
+    pub dataset_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PublicPageConfigEntry {
+    pub dataset_id: uuid::Uuid,
+    /// Whether the dataset's public page is enabled. Absent when `error` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// The parameters the public page is served with. Absent when `error` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<PublicPageParameters>,
+    /// Why the configuration could not be returned, e.g. because the dataset does not exist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PublicPageConfigsResponse {
+    /// One entry per requested dataset id, in the order they were requested.
+    pub configs: Vec<PublicPageConfigEntry>,
+}
+
+/// Get Public Page Configs
+///
+/// Get the public page parameters and enabled state of several datasets at once, e.g. for a dashboard listing the public page status of an organization's datasets. Datasets which do not exist or whose organization the auth'ed user is not an admin or owner of get an entry with an `error` instead. Duplicate ids are returned once.
+#[utoipa::path(
+    post,
+    path = "/public_page/configs",
+    context_path = "/api",
+    tag = "Public",
+    request_body(content = PublicPageConfigsReqPayload, description = "JSON request payload with the dataset ids to get the public page configuration of", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The public page configuration of each requested dataset", body = PublicPageConfigsResponse),
+        (status = 400, description = "Too many dataset ids were requested", body = ErrorResponseBody),
+    ),
+    params(
+        ("TR-Organization" = uuid::Uuid, Header, description = "The organization id to use for the request"),
+    ),
+    security(
+        ("ApiKey" = ["admin"]),
+    )
+)]
+pub async fn get_public_page_configs(
+    data: web::Json<PublicPageConfigsReqPayload>,
+    pool: web::Data<Pool>,
+    user: AdminOnly,
+    req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    let max_bulk_configs = env::var("PUBLIC_PAGE_MAX_BULK_CONFIGS")
+        .ok()
+        .and_then(|max_bulk_configs| max_bulk_configs.parse::<usize>().ok())
+        .unwrap_or(100);
+
+    let dataset_ids = data
+        .into_inner()
+        .dataset_ids
+        .into_iter()
+        .unique()
+        .collect::<Vec<uuid::Uuid>>();
+    if dataset_ids.len() > max_bulk_configs {
+        return Err(ServiceError::BadRequest(format!(
+            "At most {} dataset_ids can be requested at once",
+            max_bulk_configs
+        )));
+    }
+
+    let mut datasets = get_datasets_by_ids_query(dataset_ids.clone(), pool)
+        .await?
+        .into_iter()
+        .map(|dataset| (dataset.id, dataset))
+        .collect::<HashMap<uuid::Uuid, Dataset>>();
+
+    let configs = dataset_ids
+        .into_iter()
+        .map(|dataset_id| match datasets.remove(&dataset_id) {
+            // Datasets of other organizations are reported like missing ones so their existence
+            // is not leaked
+            Some(dataset) if verify_admin(&user, &dataset.organization_id) => {
+                let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+                PublicPageConfigEntry {
+                    dataset_id,
+                    enabled: Some(config.PUBLIC_DATASET.enabled),
+                    params: Some(get_public_page_parameters(&dataset, &config, &req)),
+                    error: None,
+                }
+            }
+            _ => PublicPageConfigEntry {
+                dataset_id,
+                enabled: None,
+                params: None,
+                error: Some("Dataset not found".to_string()),
+            },
+        })
+        .collect::<Vec<PublicPageConfigEntry>>();
+
+    Ok(HttpResponse::Ok().json(PublicPageConfigsResponse { configs }))
+}
+
+/// Removes the parameters listed in the comma separated `PUBLIC_PAGE_PARAMS_DENYLIST` before they
+/// reach the browser, e.g. `searchOptions,analytics`. Names are the camelCase keys of the
+/// serialized parameters. Nothing is removed when it is unset.
+fn strip_denied_public_page_params(
+    params: PublicPageParameters,
+    dataset_id: uuid::Uuid,
+) -> PublicPageParameters {
+    let denylist = env::var("PUBLIC_PAGE_PARAMS_DENYLIST").unwrap_or_default();
+    let denied_keys = denylist
+        .split(',')
+        .map(|key| key.trim())
+        .filter(|key| !key.is_empty())
+        .collect::<Vec<&str>>();
+
+    if denied_keys.is_empty() {
+        return params;
+    }
+
+    let mut params_json = match serde_json::to_value(&params) {
+        Ok(serde_json::Value::Object(params_json)) => params_json,
+        _ => return params,
+    };
 
+    let mut stripped = false;
+    for key in denied_keys {
+        if params_json.remove(key).is_some() {
+            log::info!(
+                "Stripped {} from the public page parameters of dataset {}",
+                key,
+                dataset_id
+            );
+            stripped = true;
+        }
+    }
+
+    if !stripped {
+        return params;
+    }
+
+    serde_json::from_value(serde_json::Value::Object(params_json)).unwrap_or_else(|err| {
+        log::error!(
+            "Failed to strip public page parameters of dataset {}: {:?}",
+            dataset_id,
+            err
+        );
+        PublicPageParameters::default()
+    })
+}
+
+/// Checks the shape of a BCP-47 tag: a 2 to 3 or 5 to 8 letter language followed by up to 8
+/// character alphanumeric subtags. The widget formats with it, so anything else falls back to
+/// `en-US` rather than breaking number formatting on the page.
+fn sanitize_number_locale(number_locale: String, dataset_id: uuid::Uuid) -> String {
+    let mut subtags = number_locale.split('-');
+    let valid_language = subtags.next().is_some_and(|language| {
+        matches!(language.len(), 2..=3 | 5..=8) && language.chars().all(|c| c.is_ascii_alphabetic())
+    });
+    let valid_subtags = subtags.all(|subtag| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+
+    if !valid_language || !valid_subtags || number_locale.len() > 35 {
+        log::warn!(
+            "Invalid number_locale {:?} for public page of dataset {}, falling back to en-US",
+            number_locale,
+            dataset_id
+        );
+        return "en-US".to_string();
+    }
+
+    number_locale
+}
+
+/// Strips control characters from the brand name and clamps it to
+/// `PUBLIC_PAGE_BRAND_NAME_MAX_LENGTH` characters (64 by default) so an overlong name can't break
+/// the page layout.
+fn sanitize_brand_name(brand_name: String, dataset_id: uuid::Uuid) -> String {
+    let max_length = env::var("PUBLIC_PAGE_BRAND_NAME_MAX_LENGTH")
+        .ok()
+        .and_then(|max_length| max_length.parse::<usize>().ok())
+        .unwrap_or(64);
+
+    let brand_name = brand_name
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+
+    if brand_name.chars().count() > max_length {
+        log::info!(
+            "Truncating brand_name for public page of dataset {} to {} characters",
+            dataset_id,
+            max_length
+        );
+        return brand_name.chars().take(max_length).collect();
+    }
+
+    brand_name
+}
+
+/// Derives the `base_url` injected into the page from the request's `Host` header when the host
+/// is listed in the comma separated `PUBLIC_PAGE_ALLOWED_HOSTS`, so embeds in multi-region
+/// deployments hit the region which served the page. Falls back to `BASE_SERVER_URL`.
+fn get_public_page_base_url(req: &HttpRequest, base_server_url: &str) -> String {
+    let allowed_hosts = env::var("PUBLIC_PAGE_ALLOWED_HOSTS").unwrap_or_default();
+
+    let connection_info = req.connection_info();
+    let host = connection_info.host().to_lowercase();
+
+    let is_allowed = allowed_hosts
+        .split(',')
+        .map(|allowed_host| allowed_host.trim().to_lowercase())
+        .any(|allowed_host| !allowed_host.is_empty() && allowed_host == host);
+
+    if is_allowed {
+        format!("{}://{}", connection_info.scheme(), host)
+    } else {
+        base_server_url.to_string()
+    }
+}
+
+/// The parameters the dataset's public page is rendered with, from its `extra_params` after
+/// defaults and sanitization.
+fn get_public_page_parameters(
+    dataset: &Dataset,
+    config: &DatasetConfiguration,
+    req: &HttpRequest,
+) -> PublicPageParameters {
+    let dataset_id = dataset.id;
     let base_server_url = get_env!(
         "BASE_SERVER_URL",
         "Server hostname for OpenID provider must be set"
     );
 
-    let logged_in = req.extensions().get::<LoggedUser>().is_some();
-    let dashboard_url =
-        env::var("ADMIN_DASHBOARD_URL").unwrap_or("https://dashboard.trieve.ai".to_string());
-
-    if config.PUBLIC_DATASET.enabled {
-        let templ = templates.get_template("page.html").unwrap();
-
-        let hero_pattern = config
+    let mut params = PublicPageParameters {
+        dataset_id: Some(dataset_id),
+        config_version: Some(get_config_version(dataset)),
+        features: Some(get_public_page_features(config)),
+        base_url: Some(get_public_page_base_url(req, base_server_url)),
+        api_key: Some(config.PUBLIC_DATASET.api_key.clone().unwrap_or_default()),
+        signed_config: None,
+        ..config
             .PUBLIC_DATASET
             .extra_params
-            .as_ref()
-            .and_then(|params| params.hero_pattern.clone());
+            .clone()
+            .unwrap_or_default()
+    };
 
-        let body_style = hero_pattern
-            .as_ref()
-            .and_then(|p| p.hero_pattern_svg.as_ref())
-            .map(|url| format!("background-image: url('{url}')"));
-        let background_color = hero_pattern
-            .as_ref()
-            .and_then(|p| p.background_color.as_ref())
-            .map(|color| format!("background-color: {color}"));
+    if params
+        .url_field
+        .as_ref()
+        .map_or(true, |url_field| url_field.trim().is_empty())
+    {
+        params.url_field = Some("link".to_string());
+    }
 
-        let tabs = config
-            .PUBLIC_DATASET
-            .extra_params
-            .as_ref()
-            .and_then(|p| p.tab_messages.clone())
-            .unwrap_or_default();
-
-        let response_body = templ
-            .render(context! {
-                logged_in,
-                dashboard_url,
-                background_color,
-                has_hero_pattern => hero_pattern.is_some(),
-                body_style,
-                tabs,
-                params => PublicPageParameters {
-                    dataset_id: Some(dataset_id),
-                    base_url: Some(base_server_url.to_string()),
-                    api_key: Some(config.PUBLIC_DATASET.api_key.unwrap_or_default()),
-                    ..config.PUBLIC_DATASET.extra_params.unwrap_or_default()
-                }
-            })
-            .unwrap();
+    params.brand_name = params
+        .brand_name
+        .map(|brand_name| sanitize_brand_name(brand_name, dataset_id));
 
-        Ok(HttpResponse::Ok().body(response_body))
-    } else {
-        Ok(HttpResponse::Forbidden().finish())
+    params.number_locale = params
+        .number_locale
+        .map(|number_locale| sanitize_number_locale(number_locale, dataset_id));
+
+    strip_denied_public_page_params(params, dataset_id)
+}
+
+/// How long the result of `public_api_key_is_active` is reused before the key is looked up again.
+const PUBLIC_API_KEY_CHECK_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Result of the last api key check of a dataset's public page, keyed by the dataset id.
+struct PublicApiKeyCheck {
+    api_key: String,
+    is_active: bool,
+    expiration: std::time::Instant,
+}
+
+lazy_static! {
+    static ref PUBLIC_API_KEY_CHECKS: DashMap<uuid::Uuid, PublicApiKeyCheck> = DashMap::new();
+}
+
+/// Checks that the api key configured in `PUBLIC_DATASET.api_key` still resolves to an active key
+/// which can read the dataset. Returns `None` when the check could not be completed, e.g. when
+/// postgres is unavailable, so that a transient failure does not take the page down. Completed
+/// checks are reused for `PUBLIC_API_KEY_CHECK_TTL` while the configured key is unchanged.
+async fn public_api_key_is_active(
+    api_key: &str,
+    dataset: &Dataset,
+    pool: web::Data<Pool>,
+) -> Option<bool> {
+    if let Some(entry) = PUBLIC_API_KEY_CHECKS
+        .get(&dataset.id)
+        .filter(|entry| entry.api_key == api_key && entry.expiration > std::time::Instant::now())
+    {
+        return Some(entry.is_active);
+    }
+
+    let lookup = match get_assumed_user_by_organization_api_key(api_key, pool.clone()).await {
+        Ok(lookup) => Ok(lookup),
+        Err(ServiceError::Unauthorized) => get_user_from_api_key_query(api_key, pool).await,
+        Err(e) => Err(e),
+    };
+
+    let restriction_allows = |restriction: &Option<Vec<Option<String>>>, id: uuid::Uuid| {
+        restriction.as_ref().map_or(true, |ids| {
+            ids.is_empty() || ids.contains(&Some(id.to_string()))
+        })
+    };
+
+    let is_active = match lookup {
+        Ok((user, user_api_key)) => {
+            user.user_orgs
+                .iter()
+                .any(|org| org.organization_id == dataset.organization_id)
+                && restriction_allows(&user_api_key.organization_ids, dataset.organization_id)
+                && restriction_allows(&user_api_key.dataset_ids, dataset.id)
+        }
+        Err(ServiceError::BadRequest(_)) | Err(ServiceError::Unauthorized) => false,
+        Err(_) => return None,
+    };
+
+    PUBLIC_API_KEY_CHECKS.insert(
+        dataset.id,
+        PublicApiKeyCheck {
+            api_key: api_key.to_string(),
+            is_active,
+            expiration: std::time::Instant::now() + PUBLIC_API_KEY_CHECK_TTL,
+        },
+    );
+
+    Some(is_active)
+}
+
+#[utoipa::path(
+    get,
+    path = "/public_page/{dataset_id}",
+    context_path = "/api",
+    tag = "Public",
+    responses(
+        (status = 200, description = "Public Page associated to the dataset. Returns the page's PublicPageParameters as JSON instead of HTML when the Accept header prefers application/json", body = PublicPageParameters),
+        (status = 400, description = "Service error relating to loading the public page", body = ErrorResponseBody),
+        (status = 403, description = "The public page is not enabled for this dataset"),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the organization you want to fetch."),
+    ),
+)]
+pub async fn public_page(
+    dataset_id: web::Path<uuid::Uuid>,
+    pool: web::Data<Pool>,
+    templates: Templates<'_>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    let dataset_id = dataset_id.into_inner();
+
+    let dataset =
+        match get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool.clone()).await {
+            Ok(dataset) => dataset,
+            Err(ServiceError::NotFound(_)) => return public_page_not_found(&templates),
+            Err(e) => return Err(e),
+        };
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    let logged_in = req.extensions().get::<LoggedUser>().is_some();
+    let dashboard_url =
+        env::var("ADMIN_DASHBOARD_URL").unwrap_or("https://dashboard.trieve.ai".to_string());
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Ok(HttpResponse::Forbidden()
+            .insert_header((header::VARY, "Accept"))
+            .finish());
+    }
+
+    // Clients which prefer JSON over HTML get the page parameters instead of the rendered page
+    let wants_json = req
+        .get_header::<header::Accept>()
+        .and_then(|accept| accept.ranked().into_iter().next())
+        .is_some_and(|mime| mime.essence_str() == "application/json");
+
+    let mut params = get_public_page_parameters(&dataset, &config, &req);
+
+    // A rotated or revoked key left in the config would make every search from the page 401,
+    // so surface the problem instead of shipping a dead key to browsers.
+    let api_key_error = match config.PUBLIC_DATASET.api_key.as_deref() {
+        Some(api_key) if !api_key.is_empty() => {
+            match public_api_key_is_active(api_key, &dataset, pool).await {
+                Some(false) => {
+                    log::error!(
+                        "PUBLIC_DATASET.api_key for dataset {} is not an active api key with access to the dataset; the public page will not be able to search",
+                        dataset_id
+                    );
+                    params.api_key = None;
+                    Some("This page's api key is no longer active. Please contact the owner of this dataset.".to_string())
+                }
+                Some(true) => None,
+                None => {
+                    log::warn!(
+                        "Could not verify PUBLIC_DATASET.api_key for dataset {}",
+                        dataset_id
+                    );
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    if let Some(secret) = config
+        .PUBLIC_DATASET
+        .config_signing_secret
+        .as_deref()
+        .filter(|secret| !secret.is_empty())
+    {
+        match sign_public_page_parameters(&params, dataset_id, secret) {
+            Ok(signed_config) => params.signed_config = Some(signed_config),
+            Err(err) => log::error!(
+                "Failed to sign public page parameters of dataset {}: {}",
+                dataset_id,
+                err
+            ),
+        }
+    }
+
+    if wants_json {
+        return Ok(HttpResponse::Ok()
+            .insert_header((header::VARY, "Accept"))
+            .json(params));
+    }
+
+    let templ = templates
+        .get_template("page.html")
+        .map_err(|err| record_render_error(dataset_id, err))?;
+
+    let hero_pattern = config
+        .PUBLIC_DATASET
+        .extra_params
+        .as_ref()
+        .and_then(|params| params.hero_pattern.clone());
+
+    let body_style = hero_pattern
+        .as_ref()
+        .and_then(|p| p.hero_pattern_svg.as_ref())
+        .map(|url| format!("background-image: url('{url}')"));
+    let background_color = hero_pattern
+        .as_ref()
+        .and_then(|p| p.background_color.as_ref())
+        .map(|color| format!("background-color: {color}"));
+
+    let tabs = config
+        .PUBLIC_DATASET
+        .extra_params
+        .as_ref()
+        .and_then(|p| p.tab_messages.clone())
+        .unwrap_or_default();
+
+    let csp_nonce = uuid::Uuid::new_v4().simple().to_string();
+    let csp_header = get_public_page_csp_header(
+        &config.PUBLIC_DATASET,
+        params.base_url.as_deref(),
+        &csp_nonce,
+    );
+
+    let response_body = templ
+        .render(context! {
+            logged_in,
+            dashboard_url,
+            background_color,
+            has_hero_pattern => hero_pattern.is_some(),
+            body_style,
+            tabs,
+            api_key_error,
+            csp_nonce,
+            params
+        })
+        .map_err(|err| record_render_error(dataset_id, err))?;
+
+    let response_body = if env::var("PUBLIC_PAGE_MINIFY").unwrap_or("false".to_string()) == "true" {
+        minify_html(&response_body)
+    } else {
+        response_body
+    };
+
+    let has_nonce = csp_header
+        .as_ref()
+        .is_some_and(|(_, policy)| policy.contains(&csp_nonce));
+
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header((header::VARY, "Accept"))
+        .insert_header(public_page_cache_control(logged_in, has_nonce))
+        .content_type("text/html; charset=utf-8");
+    if let Some(csp_header) = csp_header {
+        response.insert_header(csp_header);
+    }
+
+    let response_body =
+        encode_public_response_body(&req, &mut response, response_body.into_bytes(), 0);
+
+    Ok(response.body(response_body))
+}
+
+/// Signs the public page parameters as an HS256 JWT with the dataset's `config_signing_secret`.
+/// The api key is left out of the claims so the token does not expose it any further than the
+/// parameters already do.
+fn sign_public_page_parameters(
+    params: &PublicPageParameters,
+    dataset_id: uuid::Uuid,
+    secret: &str,
+) -> Result<String, ServiceError> {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    let ttl_secs = env::var("PUBLIC_PAGE_SIGNED_CONFIG_TTL_SECS")
+        .ok()
+        .and_then(|ttl_secs| ttl_secs.parse::<i64>().ok())
+        .unwrap_or(3600);
+    let issued_at = chrono::Utc::now().timestamp();
+
+    let claims = serde_json::json!({
+        "iss": "trieve",
+        "sub": dataset_id,
+        "iat": issued_at,
+        "exp": issued_at + ttl_secs,
+        "params": PublicPageParameters {
+            api_key: None,
+            signed_config: None,
+            ..params.clone()
+        },
+    });
+
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+
+    let signature = openssl::pkey::PKey::hmac(secret.as_bytes())
+        .and_then(|key| {
+            let mut signer =
+                openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), &key)?;
+            signer.update(signing_input.as_bytes())?;
+            signer.sign_to_vec()
+        })
+        .map_err(|err| {
+            ServiceError::InternalServerError(format!(
+                "Failed to sign public page parameters: {}",
+                err
+            ))
+        })?;
+
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// Content-Security-Policy of public pages unless the dataset sets `content_security_policy`.
+/// Scripts may only come from the packages the page and its tabs load or carry the per-response
+/// nonce, and the page may only connect to itself and its base_url. jsDelivr's `+esm` builds
+/// import their dependencies from other `/npm/` paths, so that prefix is allowed as a whole.
+/// Styles stay inline-able for the widget's styling.
+const PUBLIC_PAGE_DEFAULT_CSP: &str = "default-src 'self'; script-src 'self' 'nonce-{nonce}' https://unpkg.com/trieve-search-component@0.2.4/ https://unpkg.com/@highlightjs/cdn-assets@11.9.0/ https://unpkg.com/roughjs@latest/bundled/rough.js https://unpkg.com/rough-notation https://cdn.jsdelivr.net/npm/; style-src 'self' 'unsafe-inline' https://unpkg.com https://fonts.googleapis.com; font-src 'self' https://fonts.gstatic.com data:; img-src 'self' https: data:; connect-src 'self' {base_url}; object-src 'none'; base-uri 'self'";
+
+/// Builds the Content-Security-Policy header of a public page, `None` when the dataset disabled
+/// it with an empty `content_security_policy`.
+fn get_public_page_csp_header(
+    public_dataset: &PublicDatasetOptions,
+    base_url: Option<&str>,
+    nonce: &str,
+) -> Option<(header::HeaderName, String)> {
+    let policy = public_dataset
+        .content_security_policy
+        .as_deref()
+        .unwrap_or(PUBLIC_PAGE_DEFAULT_CSP);
+    if policy.trim().is_empty() {
+        return None;
+    }
+
+    // The base_url can come from the Host header, so it must not be able to add directives
+    let base_url = base_url
+        .filter(|base_url| {
+            !base_url
+                .chars()
+                .any(|c| c.is_whitespace() || [';', ',', '\'', '"'].contains(&c))
+        })
+        .unwrap_or_default();
+
+    let policy = policy
+        .replace("{nonce}", nonce)
+        .replace("{base_url}", base_url);
+
+    let header_name = if public_dataset
+        .content_security_policy_report_only
+        .unwrap_or(false)
+    {
+        header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+    } else {
+        header::CONTENT_SECURITY_POLICY
+    };
+
+    Some((header_name, policy))
+}
+
+const MINIFY_RAW_TAGS: [&str; 3] = ["pre", "script", "textarea"];
+
+/// Returns the byte offset and name of the next opening `<pre>`, `<script>` or `<textarea>` tag
+/// in the lowercased html at or after `from`.
+fn find_next_raw_tag(lowercase_html: &str, from: usize) -> Option<(usize, &'static str)> {
+    MINIFY_RAW_TAGS
+        .iter()
+        .filter_map(|tag| {
+            let opening = format!("<{}", tag);
+            let mut search_from = from;
+
+            while let Some(offset) = lowercase_html[search_from..].find(&opening) {
+                let start = search_from + offset;
+                let after = lowercase_html[start + opening.len()..].chars().next();
+
+                if after.map_or(true, |c| c == '>' || c.is_ascii_whitespace()) {
+                    return Some((start, *tag));
+                }
+
+                search_from = start + opening.len();
+            }
+
+            None
+        })
+        .min_by_key(|(start, _)| *start)
+}
+
+/// Collapses runs of whitespace in the rendered page into a single space, leaving the contents of
+/// `<pre>`, `<script>` and `<textarea>` tags untouched since whitespace is significant there.
+fn minify_html(html: &str) -> String {
+    // ASCII lowercasing keeps byte offsets identical to the original html
+    let lowercase_html = html.to_ascii_lowercase();
+    let mut minified = String::with_capacity(html.len());
+    let mut position = 0;
+
+    while position < html.len() {
+        let next_raw_tag = find_next_raw_tag(&lowercase_html, position);
+        let collapse_end = next_raw_tag.map_or(html.len(), |(start, _)| start);
+
+        let mut in_whitespace = false;
+        for c in html[position..collapse_end].chars() {
+            if c.is_whitespace() {
+                if !in_whitespace {
+                    minified.push(' ');
+                }
+                in_whitespace = true;
+            } else {
+                minified.push(c);
+                in_whitespace = false;
+            }
+        }
+
+        let Some((raw_start, tag)) = next_raw_tag else {
+            break;
+        };
+
+        let closing = format!("</{}>", tag);
+        let raw_end = lowercase_html[raw_start..]
+            .find(&closing)
+            .map_or(html.len(), |offset| raw_start + offset + closing.len());
+
+        minified.push_str(&html[raw_start..raw_end]);
+        position = raw_end;
+    }
+
+    minified
+}
+
+/// Logged in renders include the dashboard link and state, so they must never be stored by a
+/// shared cache where they could be served to anonymous visitors. Renders whose policy carries a
+/// nonce are not stored either, a cached copy would reuse the nonce across responses.
+fn public_page_cache_control(logged_in: bool, has_nonce: bool) -> header::CacheControl {
+    if logged_in || has_nonce {
+        header::CacheControl(vec![header::CacheDirective::NoStore])
+    } else {
+        header::CacheControl(vec![
+            header::CacheDirective::Public,
+            header::CacheDirective::MaxAge(300),
+        ])
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[schema(example = json!({
+    "query": "how do I get started",
+    "search_options": {
+        "search_type": "fulltext",
+        "fallback_search_type": "semantic",
+        "page_size": 10
+    }
+}))]
+pub struct PublicPageSearchReqPayload {
+    /// The search query typed into the public page.
+    pub query: String,
+    /// The dataset the client believes it is searching. If specified, it must match the dataset_id in the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset_id: Option<uuid::Uuid>,
+    /// Options for the search. If not specified, the `search_options` configured for the dataset's public page are used.
+    pub search_options: Option<PublicPageSearchOptions>,
+    /// Queries the user is likely to search next, e.g. extensions of the current query while typing. For semantic and hybrid searches their dense embeddings are computed in the background so the follow-up search can skip embedding the query. Only the first `PUBLIC_SEARCH_MAX_PREFETCH_QUERIES` (3 by default) are used, they are skipped when `PUBLIC_SEARCH_MAX_CONCURRENCY` is reached and failures are ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefetch_queries: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageScoreExplanation {
+    pub chunk_id: uuid::Uuid,
+    /// Score against the dense query vector, including any semantic_boost.
+    pub dense_score: Option<f32>,
+    /// Score against the SPLADE query vector, including any fulltext_boost.
+    pub fulltext_score: Option<f32>,
+    /// Score against the BM25 query vector, including any fulltext_boost.
+    pub bm25_score: Option<f32>,
+    /// Score assigned by the reranker when the results were reranked, e.g. by the cross encoder for hybrid search.
+    pub rerank_score: Option<f64>,
+    /// Boosts from scoring_options which were applied to the query vectors.
+    pub boosts: Option<ScoringOptions>,
+    /// The score the result was ranked by.
+    pub score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageMatchedFields {
+    pub chunk_id: uuid::Uuid,
+    /// Fields of the result containing a non stop word of the query: `chunk_html`, `tag_set` and `metadata.<key>` for top level string metadata values. Fields left out of slim or content only results are never matched.
+    pub matched_fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageSearchResponseBody {
+    /// Id of the search, generated server side. It is recorded as the id of the search analytics event when analytics is enabled, so click-throughs can be attributed to the search by sending it as the `request_id` of a `search` CTR event to `/analytics/ctr`.
+    pub id: uuid::Uuid,
+    pub chunks: Vec<ScoreChunk>,
+    pub corrected_query: Option<String>,
+    pub total_pages: i64,
+    /// Qdrant's estimate of the number of chunks matching the search's filters. It ignores the query itself and is read from the index rather than counted, so it may differ from the exact filtered count, especially on fulltext and BM25 searches where not every chunk matches the query. Only present when `approximate_count` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approximate_total: Option<u64>,
+    /// True when the primary search returned no results and the results come from `fallback_search_type` instead.
+    pub used_fallback: bool,
+    /// True when a hybrid search reached `PUBLIC_SEARCH_DEADLINE_MS` while reranking and returned the results it had already retrieved instead of a 408. Partial results are in retrieval order, are not reranked and are not filtered by score_threshold, and a warning is added to `warnings`. Clients may retry or show the results as provisional.
+    pub partial: bool,
+    /// The score_threshold the search was run with, after falling back to the page's configured `search_options` and treating 0 as no threshold. Like the request's score_threshold, it applies to the raw scores before weight, bias and `normalize_scores` modifications. Null when no threshold applied.
+    pub effective_score_threshold: Option<f32>,
+    /// Whether a result exists after the requested page. Only present when `check_next_page` was requested and the page could be checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_next_page: Option<bool>,
+    /// Per-result score breakdowns, in the same order as `chunks`. Only present when `explain` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanations: Option<Vec<PublicPageScoreExplanation>>,
+    /// The fields each result matched the query in, in the same order as `chunks`. Only present when `include_matched_fields` was requested on a fulltext, bm25 or hybrid search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_fields: Option<Vec<PublicPageMatchedFields>>,
+    /// The groups of each result which belongs to at least one group, keyed by `chunk_uuid` with the group's name, tracking_id, metadata and tag_set in `slim_groups`. Only present when `include_group_metadata` was requested on a page with `use_group_search` enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<GroupsForChunk>>,
+    /// The neighboring chunks of each result which belongs to a group. Only present when `neighbor_chunks` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub neighbor_chunks: Option<Vec<PublicPageNeighborChunks>>,
+    /// The dense vector of each result, in the same order as `chunks`. Results without a dense vector in Qdrant are left out. Only present when `include_vectors` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vectors: Option<Vec<PublicPageResultVector>>,
+    /// The results grouped by the value of their `group_results_by` metadata key, in the order of each group's best result. Only present when `group_results_by` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_groups: Option<Vec<PublicPageResultGroup>>,
+    /// How long the stages of the search took. Only present when `include_timings` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<PublicPageSearchTimings>,
+    /// The compiled Qdrant filter of the search in the JSON shape of Qdrant's REST API. Only present when `debug_filter` was requested by a member of the dataset's organization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_filter: Option<serde_json::Value>,
+    /// The tags of the returned results with the number of results carrying each, most common first. Only present when `include_tag_facets` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_facets: Option<Vec<TagsWithCount>>,
+    /// The search method enforced by the dataset's public `force_search_type`. Only present when the dataset forces a search method, in which case the requested `search_type` and `fallback_search_type` were ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forced_search_type: Option<SearchMethod>,
+    /// Adjustments the server made to the request, e.g. ignored or overridden options. Empty when the request was run as sent.
+    pub warnings: Vec<String>,
+    /// True when stop words were removed from the query. False when `remove_stop_words` was not requested or the query had fewer than `min_terms_after_stopword_removal` non stop words.
+    pub removed_stop_words: bool,
+}
+
+/// Lowercased non stop words of the query with surrounding punctuation removed.
+fn get_query_terms(query: &str) -> Vec<String> {
+    let stop_words = get_stop_words();
+
+    query
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty() && !stop_words.contains(word))
+        .unique()
+        .collect()
+}
+
+/// Trims text to at most `preview_chars` characters on word boundaries, adding an ellipsis where
+/// text was cut. When a word matches one of `terms`, the preview starts up to a quarter of
+/// `preview_chars` before it.
+fn get_content_preview(text: &str, preview_chars: usize, terms: &[String]) -> String {
+    if text.chars().count() <= preview_chars {
+        return text.to_string();
+    }
+
+    let words = text.split_whitespace().collect::<Vec<&str>>();
+
+    let mut start = 0;
+    if let Some(matched_word) = words.iter().position(|word| {
+        let word = word.to_lowercase();
+        terms.iter().any(|term| word.contains(term.as_str()))
+    }) {
+        start = matched_word;
+        let mut context_chars = 0;
+        while start > 0 && context_chars + words[start - 1].chars().count() < preview_chars / 4 {
+            context_chars += words[start - 1].chars().count() + 1;
+            start -= 1;
+        }
+    }
+
+    let mut end = start;
+    let mut length = 0;
+    while end < words.len() {
+        let word_length = words[end].chars().count() + usize::from(end > start);
+        if length + word_length > preview_chars {
+            break;
+        }
+        length += word_length;
+        end += 1;
+    }
+
+    let mut preview = if end == start {
+        // A single word longer than the preview is cut mid-word
+        words
+            .get(start)
+            .map(|word| word.chars().take(preview_chars).collect::<String>())
+            .unwrap_or_default()
+    } else {
+        words[start..end].join(" ")
+    };
+
+    if start > 0 {
+        preview.insert(0, '…');
+    }
+    if end < words.len() {
+        preview.push('…');
+    }
+
+    preview
+}
+
+/// Multiplies the score of each result by the mean weight of the fields it matched the query terms
+/// in, then re-sorts the results by their new scores. Unlisted fields weigh 1.
+fn apply_field_query_weights(
+    score_chunks: &mut [ScoreChunkDTO],
+    field_query_weights: &HashMap<String, f32>,
+    terms: &[String],
+) {
+    for score_chunk in score_chunks.iter_mut() {
+        let Some(chunk) = score_chunk.metadata.first().map(|chunk| chunk.metadata()) else {
+            continue;
+        };
+
+        let matched_fields = get_matched_fields(&chunk, terms);
+        if matched_fields.is_empty() {
+            continue;
+        }
+
+        let mean_weight = matched_fields
+            .iter()
+            .map(|field| field_query_weights.get(field).copied().unwrap_or(1.0) as f64)
+            .sum::<f64>()
+            / matched_fields.len() as f64;
+
+        score_chunk.score *= mean_weight;
+    }
+
+    score_chunks.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+/// Lists the fields of the chunk which contain any of the lowercased `terms`.
+fn get_matched_fields(chunk: &ChunkMetadata, terms: &[String]) -> Vec<String> {
+    let matches = |text: &str| {
+        let text = text.to_lowercase();
+        terms.iter().any(|term| text.contains(term.as_str()))
+    };
+
+    let mut matched_fields = vec![];
+
+    if chunk
+        .chunk_html
+        .as_ref()
+        .is_some_and(|chunk_html| matches(&convert_html_to_text(chunk_html)))
+    {
+        matched_fields.push("chunk_html".to_string());
+    }
+
+    if chunk
+        .tag_set
+        .iter()
+        .flatten()
+        .flatten()
+        .any(|tag| matches(tag))
+    {
+        matched_fields.push("tag_set".to_string());
+    }
+
+    if let Some(serde_json::Value::Object(metadata)) = chunk.metadata.as_ref() {
+        for (key, value) in metadata {
+            let value_matches = match value {
+                serde_json::Value::String(value) => matches(value),
+                serde_json::Value::Array(values) => values
+                    .iter()
+                    .filter_map(|value| value.as_str())
+                    .any(matches),
+                _ => false,
+            };
+
+            if value_matches {
+                matched_fields.push(format!("metadata.{}", key));
+            }
+        }
+    }
+
+    matched_fields
+}
+
+/// Maps a raw score onto a 0..1 relevance scale for `normalize_scores`, see its documentation
+/// for the formula used per search type and distance metric.
+fn normalize_public_score(
+    score: f64,
+    search_type: &SearchMethod,
+    distance_metric: &DistanceMetric,
+) -> f64 {
+    let normalized = match search_type {
+        SearchMethod::Semantic => match distance_metric {
+            DistanceMetric::Cosine | DistanceMetric::Dot => (score + 1.0) / 2.0,
+            DistanceMetric::Euclidean | DistanceMetric::Manhattan => 1.0 / (1.0 + score.max(0.0)),
+        },
+        SearchMethod::FullText | SearchMethod::BM25 => score.max(0.0) / (1.0 + score.max(0.0)),
+        SearchMethod::Hybrid => 1.0 / (1.0 + (-score).exp()),
+    };
+
+    normalized.clamp(0.0, 1.0)
+}
+
+/// Rounds a score to `precision` decimals for public responses. Only applied when serializing,
+/// after the results have been ordered by their full precision scores.
+fn round_score(score: f64, precision: u32) -> f64 {
+    let factor = 10_f64.powi(precision.min(15) as i32);
+    (score * factor).round() / factor
+}
+
+fn count_non_stop_words(query: &str) -> usize {
+    let stop_words = get_stop_words();
+
+    query
+        .split_whitespace()
+        .filter(|word| !stop_words.contains(&word.to_lowercase()))
+        .count()
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PublicPageResultVector {
+    /// Id of the result the vector belongs to.
+    pub chunk_id: uuid::Uuid,
+    /// Dense embedding of the result as stored in Qdrant.
+    pub vector: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PublicPageNeighborChunks {
+    /// Id of the result the neighbors belong to.
+    pub chunk_id: uuid::Uuid,
+    /// Id of the group the neighbors were taken from.
+    pub group_id: uuid::Uuid,
+    /// Chunks directly before the result, in group order.
+    pub before: Vec<ChunkMetadataStringTagSet>,
+    /// Chunks directly after the result, in group order.
+    pub after: Vec<ChunkMetadataStringTagSet>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageSearchTimings {
+    /// Time spent computing the query's embeddings, in milliseconds.
+    pub embed_ms: f64,
+    /// Time spent retrieving results from Qdrant and Postgres, including a fallback search, in milliseconds.
+    pub search_ms: f64,
+    /// Time spent reranking the results, in milliseconds.
+    pub rerank_ms: f64,
+    /// Time from receiving the request until the response was assembled, in milliseconds.
+    pub total_ms: f64,
+}
+
+/// Sums the durations of the Server-Timing entries of `timer` whose name satisfies `is_stage`.
+/// Names are lowercased with everything but letters and digits replaced by `_`.
+fn sum_timer_stages(timer: &Timer, is_stage: impl Fn(&str) -> bool) -> f64 {
+    timer
+        .header_value()
+        .split(", ")
+        .filter_map(|entry| {
+            let (name, duration) = entry.split_once(";dur=")?;
+            let name = name
+                .to_lowercase()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>();
+
+            is_stage(&name)
+                .then(|| duration.parse::<f64>().ok())
+                .flatten()
+        })
+        .sum()
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PublicPageResultGroup {
+    /// Value of the `group_results_by` metadata key shared by the group's results.
+    pub value: serde_json::Value,
+    /// Score of the group's best result.
+    pub top_score: f64,
+    /// Ids of the group's results, in the same order as `chunks`.
+    pub chunk_ids: Vec<uuid::Uuid>,
+}
+
+/// Groups the results by the value of their `group_results_by` metadata key, keeping the first
+/// `max_groups` groups in the order of their best result.
+fn group_results_by_metadata_key(
+    score_chunks: &[ScoreChunkDTO],
+    group_results_by: &str,
+    max_groups: usize,
+) -> (Vec<PublicPageResultGroup>, bool) {
+    let mut result_groups: Vec<PublicPageResultGroup> = vec![];
+    let mut capped = false;
+
+    for score_chunk in score_chunks {
+        let Some(chunk) = score_chunk.metadata.first().map(|chunk| chunk.metadata()) else {
+            continue;
+        };
+        let value = chunk
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(group_results_by))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        match result_groups.iter_mut().find(|group| group.value == value) {
+            Some(group) => {
+                group.top_score = group.top_score.max(score_chunk.score);
+                group.chunk_ids.push(chunk.id);
+            }
+            None if result_groups.len() < max_groups => result_groups.push(PublicPageResultGroup {
+                value,
+                top_score: score_chunk.score,
+                chunk_ids: vec![chunk.id],
+            }),
+            None => capped = true,
+        }
+    }
+
+    (result_groups, capped)
+}
+
+/// Maximum number of `default_search_queries` of a dataset embedded by the startup warmup.
+const PUBLIC_PAGE_WARM_MAX_QUERIES: usize = 10;
+
+/// Warms the datasets listed in the comma separated `PUBLIC_PAGE_WARM_DATASETS` in the background
+/// so the first public searches after a deploy don't pay for cold caches: the dataset and its
+/// config are loaded, which opens pooled postgres connections, and the page's
+/// `default_search_queries` are embedded into the query embedding cache, or a single warmup
+/// query when it has none to wake up the embedding server. Failures are only logged.
+pub fn spawn_public_page_warmup(pool: web::Data<Pool>) {
+    let dataset_ids = env::var("PUBLIC_PAGE_WARM_DATASETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|dataset_id| dataset_id.trim())
+        .filter(|dataset_id| !dataset_id.is_empty())
+        .filter_map(|dataset_id| match uuid::Uuid::parse_str(dataset_id) {
+            Ok(dataset_id) => Some(dataset_id),
+            Err(_) => {
+                log::warn!(
+                    "Ignoring invalid dataset id {:?} in PUBLIC_PAGE_WARM_DATASETS",
+                    dataset_id
+                );
+                None
+            }
+        })
+        .unique()
+        .collect::<Vec<uuid::Uuid>>();
+
+    if dataset_ids.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        for dataset_id in dataset_ids {
+            let started_at = std::time::Instant::now();
+
+            let dataset = match get_dataset_by_id_query(
+                UnifiedId::TrieveUuid(dataset_id),
+                pool.clone(),
+            )
+            .await
+            {
+                Ok(dataset) => dataset,
+                Err(err) => {
+                    log::warn!(
+                        "Failed to warm public page of dataset {}: {:?}",
+                        dataset_id,
+                        err
+                    );
+                    continue;
+                }
+            };
+            let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+            if !config.PUBLIC_DATASET.enabled || !config.SEMANTIC_ENABLED {
+                log::info!(
+                    "Warmed config of public page of dataset {} in {}ms, embeddings skipped as its {} disabled",
+                    dataset_id,
+                    started_at.elapsed().as_millis(),
+                    if config.PUBLIC_DATASET.enabled {
+                        "semantic search is"
+                    } else {
+                        "public page is"
+                    }
+                );
+                continue;
+            }
+
+            let queries = config
+                .PUBLIC_DATASET
+                .extra_params
+                .as_ref()
+                .and_then(|params| params.default_search_queries.clone())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|query| !query.trim().is_empty())
+                .take(PUBLIC_PAGE_WARM_MAX_QUERIES)
+                .collect::<Vec<String>>();
+
+            let embedded = if queries.is_empty() {
+                get_dense_vector("warmup".to_string(), None, "query", config.clone())
+                    .await
+                    .map(|_| 0)
+            } else {
+                futures::future::try_join_all(queries.iter().map(|query| {
+                    let parsed_query =
+                        parse_public_query(query.trim().to_string(), None, None, false, &config);
+                    get_cached_dense_vector(parsed_query.dense_query(), config.clone())
+                }))
+                .await
+                .map(|vectors| vectors.len())
+            };
+
+            match embedded {
+                Ok(embedded) => log::info!(
+                    "Warmed public page of dataset {} in {}ms, {} default search queries embedded",
+                    dataset_id,
+                    started_at.elapsed().as_millis(),
+                    embedded
+                ),
+                Err(err) => log::warn!(
+                    "Warmed config of public page of dataset {} but failed to embed its warmup queries: {:?}",
+                    dataset_id,
+                    err
+                ),
+            }
+        }
+    });
+}
+
+/// Splits text into the words synonym terms are matched on, so punctuation around a word, e.g.
+/// `shoes,` or `(tv)`, doesn't keep it from matching.
+fn get_synonym_words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Parses the query and applies the dataset's public query augmentations: `query_prefix` and
+/// `query_suffix` for the dense embedding and `synonyms` for the sparse vectors. Public queries
+/// reuse cached dense embeddings since popular pages see the same queries over and over. Synonym
+/// terms are matched case-insensitively unless `case_sensitive` is set.
+fn parse_public_query(
+    query: String,
+    use_quote_negated_terms: Option<bool>,
+    remove_stop_words: Option<bool>,
+    case_sensitive: bool,
+    config: &DatasetConfiguration,
+) -> ParsedQuery {
+    let mut parsed_query = parse_query(query, use_quote_negated_terms, remove_stop_words);
+
+    parsed_query
+        .dense_prefix
+        .clone_from(&config.PUBLIC_DATASET.query_prefix);
+    parsed_query
+        .dense_suffix
+        .clone_from(&config.PUBLIC_DATASET.query_suffix);
+    parsed_query.cache_dense_embedding = true;
+
+    if let Some(synonyms) = &config.PUBLIC_DATASET.synonyms {
+        let normalize = |text: &str| {
+            if case_sensitive {
+                text.to_string()
+            } else {
+                text.to_lowercase()
+            }
+        };
+
+        let normalized_query = normalize(&parsed_query.query);
+        let query_words = get_synonym_words(&normalized_query);
+        let contains_term = |term: &str| {
+            let normalized_term = normalize(term);
+            let term_words = get_synonym_words(&normalized_term);
+            !term_words.is_empty()
+                && query_words
+                    .windows(term_words.len())
+                    .any(|window| window == term_words.as_slice())
+        };
+
+        let query_synonyms = synonyms
+            .iter()
+            .filter(|(term, _)| contains_term(term))
+            .flat_map(|(_, term_synonyms)| term_synonyms.iter())
+            .filter(|synonym| !contains_term(synonym))
+            .unique()
+            .cloned()
+            .collect::<Vec<String>>();
+
+        parsed_query.sparse_synonyms = Some(query_synonyms);
+    }
+
+    parsed_query
+}
+
+/// Runs a public search, returning its results and whether they are partial because a hybrid
+/// search's reranking ran past `rerank_deadline`.
+#[allow(clippy::too_many_arguments)]
+async fn search_public_chunks(
+    mut data: SearchChunksReqPayload,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+    dataset: Dataset,
+    case_sensitive: bool,
+    config: &DatasetConfiguration,
+    timer: &mut Timer,
+    rerank_deadline: Option<tokio::time::Instant>,
+) -> Result<(SearchChunkQueryResponseBody, bool), actix_web::Error> {
+    let parsed_query = parse_public_query(
+        data.query.to_single_query()?,
+        data.use_quote_negated_terms,
+        data.remove_stop_words,
+        case_sensitive,
+        config,
+    );
+
+    data.score_threshold = data.score_threshold.filter(|threshold| *threshold != 0.0);
+
+    let presence_boosts = data
+        .scoring_options
+        .as_ref()
+        .and_then(|scoring_options| scoring_options.presence_boosts.clone());
+
+    let (mut result_chunks, partial) = match data.search_type {
+        SearchMethod::Hybrid => {
+            search_hybrid_chunks_before_rerank_deadline(
+                data,
+                parsed_query,
+                pool,
+                redis_pool,
+                dataset,
+                config,
+                timer,
+                rerank_deadline,
+            )
+            .await?
+        }
+        _ => (
+            search_chunks_query(
+                data,
+                ParsedQueryTypes::Single(parsed_query),
+                pool,
+                redis_pool,
+                dataset,
+                config,
+                timer,
+            )
+            .await?,
+            false,
+        ),
+    };
+
+    if let Some(presence_boosts) = presence_boosts {
+        apply_presence_boosts(&mut result_chunks.score_chunks, &presence_boosts);
+    }
+
+    break_score_ties(&mut result_chunks.score_chunks);
+
+    Ok((result_chunks, partial))
+}
+
+/// Results of a public search with an empty query under the `match_all` empty_query_behavior,
+/// the chunks matching its filters ordered by its `sort_by` field or by point id, scored 0. Only
+/// the first `PUBLIC_SEARCH_MAX_CANDIDATES` chunks can be paged through, since every earlier page
+/// is scrolled as well.
+async fn match_all_public_chunks(
+    data: &SearchChunksReqPayload,
+    dataset: &Dataset,
+    config: &DatasetConfiguration,
+    pool: web::Data<Pool>,
+) -> Result<SearchChunkQueryResponseBody, actix_web::Error> {
+    let page = data.page.unwrap_or(1).max(1);
+    let page_size = data.page_size.unwrap_or(10);
+
+    let max_candidates = get_max_public_candidates();
+    if page.saturating_mul(page_size) > max_candidates {
+        return Err(ServiceError::BadRequest(format!(
+            "Only the first {} chunks can be paged through when the query is empty",
+            max_candidates
+        ))
+        .into());
+    }
+
+    let filter =
+        assemble_qdrant_filter(data.filters.clone(), None, None, dataset.id, pool.clone()).await?;
+
+    let total_chunk_pages = if data.get_total_pages.unwrap_or(false) && page_size > 0 {
+        let count = get_approximate_point_count_query(filter.clone(), config).await?;
+        ((count as f64 / page_size as f64).ceil() as u64).min(max_candidates / page_size) as i64
+    } else {
+        0
+    };
+
+    let sort_by = match data
+        .sort_options
+        .as_ref()
+        .and_then(|sort_options| sort_options.sort_by.clone())
+    {
+        Some(QdrantSortBy::Field(sort_by)) => Some(sort_by),
+        _ => None,
+    };
+
+    // Ordered scrolls can't start from an offset point, so the earlier pages are scrolled too
+    let (search_results, _) =
+        scroll_dataset_points(page * page_size, None, sort_by, config.clone(), filter).await?;
+
+    let data = SearchChunksReqPayload {
+        highlight_options: None,
+        ..data.clone()
+    };
+
+    retrieve_chunks_from_point_ids(
+        SearchChunkQueryResult {
+            search_results: search_results
+                .into_iter()
+                .skip(((page - 1) * page_size) as usize)
+                .collect(),
+            total_chunk_pages,
+            batch_lengths: vec![],
+        },
+        None,
+        &data,
+        config.QDRANT_ONLY,
+        pool,
+    )
+    .await
+}
+
+/// Deadline for the search work of one public search request, read from
+/// `PUBLIC_SEARCH_DEADLINE_MS`. Unset means only the server-wide request timeout applies.
+fn get_public_search_deadline() -> Option<tokio::time::Instant> {
+    env::var("PUBLIC_SEARCH_DEADLINE_MS")
+        .ok()
+        .and_then(|deadline_ms| deadline_ms.parse::<u64>().ok())
+        .filter(|deadline_ms| *deadline_ms > 0)
+        .map(|deadline_ms| {
+            tokio::time::Instant::now() + std::time::Duration::from_millis(deadline_ms)
+        })
+}
+
+/// Runs `search` until `deadline`. The search operator does all of its embedding and qdrant work
+/// inline on the request future, so dropping it here drops the in-flight embedding and qdrant
+/// requests with it. Client disconnects aren't detected, so the deadline is what bounds the work
+/// of a search whose client went away.
+async fn run_before_public_search_deadline<T, E: From<ServiceError>>(
+    deadline: Option<tokio::time::Instant>,
+    search: impl std::future::Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, search)
+            .await
+            .map_err(|_| E::from(ServiceError::RequestTimeout))?,
+        None => search.await,
+    }
+}
+
+/// Orders runs of equal-score results by chunk id so repeated public searches return ties in the
+/// same order. Only adjacent equal scores are reordered, which keeps any primary sort intact.
+/// Scores are compared with total_cmp, so every run holds at least its first result, even a NaN.
+fn break_score_ties(score_chunks: &mut [ScoreChunkDTO]) {
+    let mut start = 0;
+    while start < score_chunks.len() {
+        let score = score_chunks[start].score;
+        let end = start
+            + score_chunks[start..]
+                .iter()
+                .take_while(|score_chunk| score_chunk.score.total_cmp(&score).is_eq())
+                .count();
+
+        score_chunks[start..end].sort_by_cached_key(|score_chunk| {
+            score_chunk
+                .metadata
+                .first()
+                .map(|chunk| chunk.metadata().id)
+        });
+
+        start = end;
+    }
+}
+
+/// Shuffles each run of results whose scores are within `score_band` of the run's first score with
+/// an rng seeded by `ordering_seed`. Runs are shuffled from the tie-broken order, so the same seed
+/// always gives the same order for the same results. A run always holds at least its first result,
+/// even when an infinite or NaN score falls outside the band of itself.
+fn shuffle_score_bands(score_chunks: &mut [ScoreChunkDTO], ordering_seed: u64, score_band: f64) {
+    use rand::{seq::SliceRandom, SeedableRng};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(ordering_seed);
+
+    let mut start = 0;
+    while start < score_chunks.len() {
+        let score = score_chunks[start].score;
+        let end = start
+            + score_chunks[start + 1..]
+                .iter()
+                .take_while(|score_chunk| (score - score_chunk.score).abs() <= score_band)
+                .count()
+            + 1;
+
+        score_chunks[start..end].shuffle(&mut rng);
+
+        start = end;
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+    let norm =
+        a.iter().map(|a| a * a).sum::<f32>().sqrt() * b.iter().map(|b| b * b).sum::<f32>().sqrt();
+
+    if norm == 0.0 {
+        0.0
+    } else {
+        dot / norm
+    }
+}
+
+/// Share of the best chunk score given to a document whose title contains every query word.
+const DOCUMENT_TITLE_MATCH_SCORE_FACTOR: f64 = 0.9;
+
+/// Why `search_document_titles` can't be honored for a search, if it can't.
+fn document_title_search_unsupported_reason(
+    search_payload: &SearchChunksReqPayload,
+    config: &DatasetConfiguration,
+) -> Option<&'static str> {
+    if search_payload.page.unwrap_or(1) > 1 {
+        Some("it is only honored on the first page")
+    } else if search_payload.filters.is_some() {
+        Some("it is not supported with filters or exclude_ids")
+    } else if search_payload.search_type == SearchMethod::Semantic
+        && matches!(
+            config.DISTANCE_METRIC,
+            DistanceMetric::Euclidean | DistanceMetric::Manhattan
+        )
+    {
+        Some("it is not supported for semantic searches with a euclidean or manhattan distance metric")
+    } else {
+        None
+    }
+}
+
+/// Adds the first chunk of each document whose title matched the query to the results, scored as
+/// documented on `search_document_titles`. Each one is placed before the first result with a
+/// lower score, so the order of the other results is kept, and the page is cut to `page_size`.
+async fn add_document_title_matches(
+    score_chunks: &mut Vec<ScoreChunkDTO>,
+    terms: &[String],
+    page_size: usize,
+    dataset_id: uuid::Uuid,
+    pool: web::Data<Pool>,
+) -> Result<(), ServiceError> {
+    if terms.is_empty() || page_size == 0 {
+        return Ok(());
+    }
+
+    let result_ids = score_chunks
+        .iter()
+        .filter_map(|score_chunk| score_chunk.metadata.first())
+        .map(|metadata| metadata.metadata().id)
+        .collect::<HashSet<uuid::Uuid>>();
+    let best_score = score_chunks
+        .iter()
+        .map(|score_chunk| score_chunk.score)
+        .fold(None, |best: Option<f64>, score| {
+            Some(best.map_or(score, |best| best.max(score)))
+        })
+        .unwrap_or(1.0);
+
+    let mut title_matches =
+        get_groups_matching_title_query(terms, page_size as i64, dataset_id, pool.clone())
+            .await?
+            .into_iter()
+            .filter(|title_match| !result_ids.contains(&title_match.first_chunk_id))
+            .unique_by(|title_match| title_match.first_chunk_id)
+            .map(|title_match| {
+                let title = title_match.name.to_lowercase();
+                let matched_terms = terms.iter().filter(|term| title.contains(*term)).count();
+                let score = DOCUMENT_TITLE_MATCH_SCORE_FACTOR
+                    * (matched_terms as f64 / terms.len() as f64)
+                    * best_score;
+
+                (title_match.first_chunk_id, score)
+            })
+            .collect::<Vec<(uuid::Uuid, f64)>>();
+    title_matches.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    title_matches.truncate(page_size);
+
+    if title_matches.is_empty() {
+        return Ok(());
+    }
+
+    let mut chunks = get_metadata_from_ids_query(
+        title_matches
+            .iter()
+            .map(|(chunk_id, _)| *chunk_id)
+            .collect(),
+        dataset_id,
+        pool,
+    )
+    .await?
+    .into_iter()
+    .map(|chunk| (chunk.id, chunk))
+    .collect::<HashMap<uuid::Uuid, ChunkMetadata>>();
+
+    // Title matches take the same shape as the other results, e.g. slim chunks
+    let to_result_metadata: fn(ChunkMetadata) -> ChunkMetadataTypes = match score_chunks
+        .first()
+        .and_then(|chunk| chunk.metadata.first())
+    {
+        Some(ChunkMetadataTypes::ID(_)) => {
+            |chunk: ChunkMetadata| ChunkMetadataTypes::ID(chunk.into())
+        }
+        Some(ChunkMetadataTypes::Content(_)) => {
+            |chunk: ChunkMetadata| ChunkMetadataTypes::Content(chunk.into())
+        }
+        _ => |chunk: ChunkMetadata| ChunkMetadataTypes::Metadata(chunk.into()),
+    };
+
+    for (chunk_id, score) in title_matches {
+        let Some(chunk) = chunks.remove(&chunk_id) else {
+            continue;
+        };
+
+        let position = score_chunks
+            .iter()
+            .position(|score_chunk| score_chunk.score < score)
+            .unwrap_or(score_chunks.len());
+        score_chunks.insert(
+            position,
+            ScoreChunkDTO {
+                metadata: vec![to_result_metadata(chunk)],
+                highlights: None,
+                score,
+            },
+        );
+    }
+
+    score_chunks.truncate(page_size);
+
+    Ok(())
+}
+
+/// Hash of a result's content for `dedupe_by_content`: its text lowercased with runs of whitespace
+/// collapsed. Results without content have none.
+fn get_content_hash(score_chunk: &ScoreChunkDTO) -> Option<u64> {
+    let chunk_html = match score_chunk.metadata.first()? {
+        ChunkMetadataTypes::Metadata(metadata) => metadata.chunk_html.as_ref(),
+        ChunkMetadataTypes::Content(metadata) => metadata.chunk_html.as_ref(),
+        ChunkMetadataTypes::ID(_) => None,
+    }?;
+
+    let normalized = convert_html_to_text(chunk_html)
+        .to_lowercase()
+        .split_whitespace()
+        .join(" ");
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Drops results with the same content hash as a higher scoring result, keeping the order of the
+/// remaining results. Returns the number of dropped results.
+fn dedupe_by_content_hash(score_chunks: &mut Vec<ScoreChunkDTO>) -> usize {
+    let hashes = score_chunks
+        .iter()
+        .map(get_content_hash)
+        .collect::<Vec<Option<u64>>>();
+
+    let mut by_score = (0..score_chunks.len()).collect::<Vec<usize>>();
+    by_score.sort_by(|a, b| score_chunks[*b].score.total_cmp(&score_chunks[*a].score));
+
+    let mut seen_hashes = HashSet::new();
+    let mut keep = vec![true; score_chunks.len()];
+    for index in by_score {
+        if let Some(hash) = hashes[index] {
+            keep[index] = seen_hashes.insert(hash);
+        }
+    }
+
+    let original_len = score_chunks.len();
+    let mut keep = keep.into_iter();
+    score_chunks.retain(|_| keep.next().unwrap_or(true));
+
+    original_len - score_chunks.len()
+}
+
+/// Reorders the results with Maximal Marginal Relevance, greedily picking the result with the
+/// highest `mmr_lambda * relevance - (1 - mmr_lambda) * max similarity to the picked results`.
+/// Results without a vector have no similarity to any other result. Scores are left unchanged.
+fn apply_mmr(
+    score_chunks: Vec<ScoreChunkDTO>,
+    vectors: &HashMap<uuid::Uuid, Vec<f32>>,
+    mmr_lambda: f32,
+) -> Vec<ScoreChunkDTO> {
+    let (min_score, max_score) = score_chunks
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), score_chunk| {
+            (min.min(score_chunk.score), max.max(score_chunk.score))
+        });
+    let score_range = max_score - min_score;
+
+    let mut candidates = score_chunks
+        .into_iter()
+        .map(|score_chunk| {
+            let relevance = if score_range > 0.0 {
+                ((score_chunk.score - min_score) / score_range) as f32
+            } else {
+                1.0
+            };
+            let vector = score_chunk
+                .metadata
+                .first()
+                .and_then(|chunk| vectors.get(&chunk.qdrant_point_id()));
+
+            (score_chunk, relevance, vector)
+        })
+        .collect::<Vec<_>>();
+
+    let mut picked: Vec<(ScoreChunkDTO, Option<&Vec<f32>>)> = Vec::with_capacity(candidates.len());
+    while !candidates.is_empty() {
+        let (best_index, _) = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, (_, relevance, vector))| {
+                let max_similarity = vector.map_or(0.0, |vector| {
+                    picked
+                        .iter()
+                        .filter_map(|(_, picked_vector)| *picked_vector)
+                        .map(|picked_vector| cosine_similarity(vector, picked_vector))
+                        .fold(0.0, f32::max)
+                });
+
+                (
+                    index,
+                    mmr_lambda * relevance - (1.0 - mmr_lambda) * max_similarity,
+                )
+            })
+            .fold((0, f32::MIN), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+
+        let (score_chunk, _, vector) = candidates.remove(best_index);
+        picked.push((score_chunk, vector));
+    }
+
+    picked
+        .into_iter()
+        .map(|(score_chunk, _)| score_chunk)
+        .collect()
+}
+
+/// Adds the boost for each metadata field present and non-null on a result, then re-sorts the
+/// results by their boosted score.
+fn apply_presence_boosts(
+    score_chunks: &mut [ScoreChunkDTO],
+    presence_boosts: &HashMap<String, f32>,
+) {
+    for score_chunk in score_chunks.iter_mut() {
+        let Some(metadata) = score_chunk
+            .metadata
+            .first()
+            .and_then(|chunk| chunk.metadata().metadata)
+        else {
+            continue;
+        };
+
+        let boost = presence_boosts
+            .iter()
+            .filter(|(field, _)| {
+                let field = field.strip_prefix("metadata.").unwrap_or(field);
+                metadata.get(field).is_some_and(|value| !value.is_null())
+            })
+            .map(|(_, boost)| *boost as f64)
+            .sum::<f64>();
+
+        score_chunk.score += boost;
+    }
+
+    score_chunks.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+/// Days of click events the click-through rates of `popularity_boost` are computed over.
+fn get_public_popularity_window_days() -> u32 {
+    env::var("PUBLIC_SEARCH_POPULARITY_WINDOW_DAYS")
+        .ok()
+        .and_then(|window_days| window_days.parse::<u32>().ok())
+        .filter(|window_days| *window_days > 0)
+        .unwrap_or(30)
+}
+
+/// Adds `popularity_boost` times the normalized click-through rate of each result to its score,
+/// then re-sorts the results by their boosted score. Results without clicks are not boosted.
+fn apply_popularity_boost(
+    score_chunks: &mut [ScoreChunkDTO],
+    click_through_rates: &HashMap<uuid::Uuid, f64>,
+    popularity_boost: f32,
+) {
+    for score_chunk in score_chunks.iter_mut() {
+        let Some(rate) = score_chunk
+            .metadata
+            .first()
+            .and_then(|chunk| click_through_rates.get(&chunk.metadata().id))
+        else {
+            continue;
+        };
+
+        score_chunk.score += popularity_boost as f64 * rate;
+    }
+
+    score_chunks.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+/// Embeds the likely next queries of an as-you-type search in the background so their dense
+/// vectors are in the query embedding cache when the user gets there. Only semantic and hybrid
+/// searches embed their query, and nothing else a public search does is cached, so no search is
+/// run. The embeddings share the `PUBLIC_SEARCH_MAX_CONCURRENCY` slots with searches and are
+/// skipped when none is free.
+fn spawn_prefetch_embeddings(
+    prefetch_queries: Vec<String>,
+    search_payload: &SearchChunksReqPayload,
+    case_sensitive: bool,
+    config: DatasetConfiguration,
+) {
+    let embeds_query = matches!(
+        search_payload.search_type,
+        SearchMethod::Semantic | SearchMethod::Hybrid
+    );
+    // Semantically boosted queries skip the embedding cache
+    let uses_cache = search_payload
+        .scoring_options
+        .as_ref()
+        .map_or(true, |scoring_options| {
+            scoring_options.semantic_boost.is_none()
+        });
+    if !embeds_query || !uses_cache {
+        return;
+    }
+
+    let max_prefetch_queries = env::var("PUBLIC_SEARCH_MAX_PREFETCH_QUERIES")
+        .ok()
+        .and_then(|max_prefetch_queries| max_prefetch_queries.parse::<usize>().ok())
+        .unwrap_or(3);
+    let max_query_length = config.PUBLIC_DATASET.max_query_length.unwrap_or(512);
+
+    let prefetch_queries = prefetch_queries
+        .into_iter()
+        .map(|query| query.trim().to_string())
+        .filter(|query| !query.is_empty() && query.chars().count() <= max_query_length)
+        .unique()
+        .take(max_prefetch_queries)
+        .collect::<Vec<String>>();
+
+    if prefetch_queries.is_empty() {
+        return;
+    }
+
+    let use_quote_negated_terms = search_payload.use_quote_negated_terms;
+    let remove_stop_words = search_payload.remove_stop_words;
+
+    actix_web::rt::spawn(async move {
+        let _permit = match PUBLIC_SEARCH_PERMITS
+            .as_ref()
+            .map(|permits| permits.try_acquire())
+        {
+            Some(Ok(permit)) => Some(permit),
+            Some(Err(_)) => return,
+            None => None,
+        };
+
+        for query in prefetch_queries {
+            let parsed_query = parse_public_query(
+                query,
+                use_quote_negated_terms,
+                remove_stop_words,
+                case_sensitive,
+                &config,
+            );
+
+            if let Err(err) =
+                get_cached_dense_vector(parsed_query.dense_query(), config.clone()).await
+            {
+                log::info!("Failed to prefetch public search embedding {:?}", err);
+            }
+        }
+    });
+}
+
+/// Whether an `Origin` header value matches an entry of a dataset's public `allowed_origins`.
+/// Schemes and hosts compare case insensitively and ports must match exactly.
+fn matches_allowed_origin(origin: &str, allowed_origin: &str) -> bool {
+    let allowed_origin = allowed_origin.trim().trim_end_matches('/');
+    if allowed_origin == "*" {
+        return true;
+    }
+
+    let Some((scheme, host)) = origin.split_once("://") else {
+        return false;
+    };
+    let allowed_host = match allowed_origin.split_once("://") {
+        Some((allowed_scheme, allowed_host)) => {
+            if !allowed_scheme.eq_ignore_ascii_case(scheme) {
+                return false;
+            }
+            allowed_host
+        }
+        None => allowed_origin,
+    };
+
+    let host = host.to_ascii_lowercase();
+    let allowed_host = allowed_host.to_ascii_lowercase();
+    match allowed_host.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+        None => host == allowed_host,
+    }
+}
+
+/// Checks the request's `Origin` against the dataset's public `allowed_origins`, returning the
+/// origin to echo in `Access-Control-Allow-Origin` when the dataset restricts origins.
+fn check_public_search_origin(
+    req: &HttpRequest,
+    config: &DatasetConfiguration,
+) -> Result<Option<String>, actix_web::Error> {
+    let Some(allowed_origins) = config
+        .PUBLIC_DATASET
+        .allowed_origins
+        .as_ref()
+        .filter(|allowed_origins| !allowed_origins.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    req.headers()
+        .get(header::ORIGIN)
+        .and_then(|origin| origin.to_str().ok())
+        .filter(|origin| {
+            allowed_origins
+                .iter()
+                .any(|allowed_origin| matches_allowed_origin(origin, allowed_origin))
+        })
+        .map(|origin| Some(origin.to_string()))
+        .ok_or_else(|| {
+            actix_web::error::InternalError::from_response(
+                "Public search origin not allowed",
+                HttpResponse::Forbidden().json(ErrorResponseBody {
+                    message: "This origin is not allowed to search this public page".to_string(),
+                }),
+            )
+            .into()
+        })
+}
+
+/// Takes a token from the dataset's public `max_rps` bucket, rejecting the search with a 429 when
+/// it is empty. None when the dataset has no rate limit.
+fn check_public_search_rate_limit(
+    dataset_id: uuid::Uuid,
+    config: &DatasetConfiguration,
+) -> Result<Option<PublicSearchRateLimit>, actix_web::Error> {
+    let rate_limit = config
+        .PUBLIC_DATASET
+        .max_rps
+        .filter(|max_rps| *max_rps > 0)
+        .map(|max_rps| take_public_search_rate_limit_token(dataset_id, max_rps));
+
+    if let Some(rate_limit) = rate_limit
+        .as_ref()
+        .filter(|rate_limit| rate_limit.retry_after_secs.is_some())
+    {
+        let mut response = HttpResponse::TooManyRequests();
+        rate_limit.insert_headers(&mut response);
+
+        return Err(actix_web::error::InternalError::from_response(
+            "Public search rate limit exceeded",
+            response.json(ErrorResponseBody {
+                message: format!(
+                    "Too many searches, this public page allows {} searches per second",
+                    rate_limit.limit
+                ),
+            }),
+        )
+        .into());
+    }
+
+    Ok(rate_limit)
+}
+
+/// Token bucket of a dataset's public `max_rps`, refilled continuously at max_rps tokens a second
+/// up to max_rps tokens.
+struct PublicSearchRateLimitBucket {
+    tokens: f64,
+    refilled_at: std::time::Instant,
+}
+
+/// State of a dataset's rate limit after a public search tried to take a token from it.
+struct PublicSearchRateLimit {
+    limit: u32,
+    remaining: u32,
+    /// Seconds until the bucket is full again.
+    reset_secs: u64,
+    /// Seconds until a token is available, set when the search was rejected.
+    retry_after_secs: Option<u64>,
+}
+
+impl PublicSearchRateLimit {
+    fn insert_headers(&self, response: &mut HttpResponseBuilder) {
+        response
+            .insert_header(("X-RateLimit-Limit", self.limit.to_string()))
+            .insert_header(("X-RateLimit-Remaining", self.remaining.to_string()))
+            .insert_header(("X-RateLimit-Reset", self.reset_secs.to_string()));
+        if let Some(retry_after_secs) = self.retry_after_secs {
+            response.insert_header((header::RETRY_AFTER, retry_after_secs.to_string()));
+        }
+    }
+}
+
+/// Takes a token from the dataset's rate limit bucket, which starts full.
+fn take_public_search_rate_limit_token(
+    dataset_id: uuid::Uuid,
+    max_rps: u32,
+) -> PublicSearchRateLimit {
+    let capacity = max_rps as f64;
+    let now = std::time::Instant::now();
+
+    let mut bucket = PUBLIC_SEARCH_RATE_LIMITS
+        .entry(dataset_id)
+        .or_insert_with(|| PublicSearchRateLimitBucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+
+    let elapsed_secs = now.duration_since(bucket.refilled_at).as_secs_f64();
+    // A lowered max_rps takes effect on the next search rather than after the burst is spent
+    bucket.tokens = (bucket.tokens + elapsed_secs * capacity).min(capacity);
+    bucket.refilled_at = now;
+
+    let allowed = bucket.tokens >= 1.0;
+    if allowed {
+        bucket.tokens -= 1.0;
+    }
+
+    PublicSearchRateLimit {
+        limit: max_rps,
+        remaining: bucket.tokens.floor() as u32,
+        reset_secs: ((capacity - bucket.tokens) / capacity).ceil() as u64,
+        retry_after_secs: (!allowed)
+            .then(|| (((1.0 - bucket.tokens) / capacity).ceil() as u64).max(1)),
+    }
+}
+
+/// Takes one of the `PUBLIC_SEARCH_MAX_CONCURRENCY` public search slots, failing with a 503 when
+/// all of them are in use. No limit applies when it is unset.
+fn acquire_public_search_permit(
+    metrics: &Metrics,
+) -> Result<Option<tokio::sync::SemaphorePermit<'static>>, ServiceError> {
+    match PUBLIC_SEARCH_PERMITS.as_ref() {
+        Some(permits) => permits.try_acquire().map(Some).map_err(|_| {
+            metrics.public_search_shed_counter.inc();
+            log::warn!("Shedding public search, PUBLIC_SEARCH_MAX_CONCURRENCY reached");
+            ServiceError::ServiceUnavailable(
+                "Too many concurrent public searches, please retry shortly".to_string(),
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+/// Checks a public search passed before any embedding or Qdrant work, held until its response is
+/// built: the dataset's `allowed_origins`, its `max_rps` and a `PUBLIC_SEARCH_MAX_CONCURRENCY`
+/// slot, checked in that order, along with the `PUBLIC_SEARCH_DEADLINE_MS` the search runs under.
+struct PublicSearchGuards {
+    allowed_origin: Option<String>,
+    rate_limit: Option<PublicSearchRateLimit>,
+    deadline: Option<tokio::time::Instant>,
+    _permit: Option<tokio::sync::SemaphorePermit<'static>>,
+}
+
+impl PublicSearchGuards {
+    fn check(
+        req: &HttpRequest,
+        dataset_id: uuid::Uuid,
+        config: &DatasetConfiguration,
+        metrics: &Metrics,
+    ) -> Result<Self, actix_web::Error> {
+        let allowed_origin = check_public_search_origin(req, config)?;
+        let rate_limit = check_public_search_rate_limit(dataset_id, config)?;
+        let permit = acquire_public_search_permit(metrics)?;
+
+        Ok(PublicSearchGuards {
+            allowed_origin,
+            rate_limit,
+            deadline: get_public_search_deadline(),
+            _permit: permit,
+        })
+    }
+
+    /// Adds the rate limit headers and, when the dataset restricts origins, the CORS headers.
+    fn insert_headers(&self, response: &mut HttpResponseBuilder) {
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.insert_headers(response);
+        }
+        if let Some(allowed_origin) = &self.allowed_origin {
+            response
+                .insert_header((header::ACCESS_CONTROL_ALLOW_ORIGIN, allowed_origin.clone()))
+                .insert_header((header::VARY, "Origin"));
+        }
+    }
+}
+
+/// Rejects public search options which can't be honored for the dataset or exceed the public caps.
+fn validate_public_search_options(
+    search_options: &PublicPageSearchOptions,
+    config: &DatasetConfiguration,
+) -> Result<(), ServiceError> {
+    let max_exclude_ids = env::var("PUBLIC_SEARCH_MAX_EXCLUDE_IDS")
+        .ok()
+        .and_then(|max_exclude_ids| max_exclude_ids.parse::<usize>().ok())
+        .unwrap_or(200);
+    if search_options
+        .exclude_ids
+        .as_ref()
+        .is_some_and(|exclude_ids| exclude_ids.len() > max_exclude_ids)
+    {
+        return Err(ServiceError::BadRequest(format!(
+            "At most {} exclude_ids can be specified",
+            max_exclude_ids
+        )));
+    }
+
+    if search_options
+        .field_query_weights
+        .as_ref()
+        .is_some_and(|weights| {
+            weights
+                .values()
+                .any(|weight| !weight.is_finite() || *weight < 0.0)
+        })
+    {
+        return Err(ServiceError::BadRequest(
+            "field_query_weights must be non-negative numbers".to_string(),
+        ));
+    }
+
+    if search_options
+        .scoring_options
+        .as_ref()
+        .and_then(|scoring_options| scoring_options.mmr_lambda)
+        .is_some_and(|mmr_lambda| !(0.0..=1.0).contains(&mmr_lambda))
+    {
+        return Err(ServiceError::BadRequest(
+            "mmr_lambda must be between 0 and 1".to_string(),
+        ));
+    }
+
+    if search_options
+        .scoring_options
+        .as_ref()
+        .and_then(|scoring_options| scoring_options.popularity_boost)
+        .is_some_and(|popularity_boost| !popularity_boost.is_finite() || popularity_boost < 0.0)
+    {
+        return Err(ServiceError::BadRequest(
+            "popularity_boost must be non-negative".to_string(),
+        ));
+    }
+
+    if search_options
+        .scoring_options
+        .as_ref()
+        .and_then(|scoring_options| scoring_options.presence_boosts.as_ref())
+        .is_some_and(|boosts| boosts.values().any(|boost| !boost.is_finite()))
+    {
+        return Err(ServiceError::BadRequest(
+            "presence_boosts must be finite numbers".to_string(),
+        ));
+    }
+
+    // prefix_length can't be negative, it is unsigned and rejected while deserializing
+    if search_options
+        .typo_options
+        .as_ref()
+        .and_then(|typo_options| typo_options.max_edit_distance)
+        .is_some_and(|max_edit_distance| max_edit_distance > 2)
+    {
+        return Err(ServiceError::BadRequest(
+            "typo_options.max_edit_distance must be between 0 and 2".to_string(),
+        ));
+    }
+
+    if let Some(embedding_model) = search_options.embedding_model.as_ref() {
+        // Points are only embedded with the dataset's configured model, vectors from any other
+        // model would not be comparable to them.
+        if *embedding_model != config.EMBEDDING_MODEL_NAME {
+            return Err(ServiceError::BadRequest(format!(
+                "Unknown embedding_model {}, this dataset is configured with {}",
+                embedding_model, config.EMBEDDING_MODEL_NAME
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Search Public Page
+///
+/// Search a dataset through its public page. No API key is required, but the dataset must have its public page enabled. Queries are trimmed and rejected with a 400 when longer than the dataset's public `max_query_length` (512 characters by default). Empty queries are handled by the dataset's public `empty_query_behavior`: by default they return no results with a warning, `error` rejects them with a 400 and `match_all` returns the chunks matching the filters unranked, e.g. for a browse mode. If `fallback_search_type` is set and the primary search returns no results, the search is retried once with the fallback method and `used_fallback` is set on the response. The dataset's public `query_prefix` and `query_suffix` are added to the query for the dense vector only, and its `synonyms` are OR'd into the fulltext and BM25 query only. Every response has an `id` which identifies the search in analytics for click attribution. Queries are stored in analytics with emails, runs of 6 or more digits and matches of the dataset's public `query_redaction_patterns` replaced with `[REDACTED]`. When `PUBLIC_SEARCH_MAX_CONCURRENCY` is set, searches beyond that many in flight across all datasets are rejected with a 503 and a `Retry-After` header instead of being queued. When `PUBLIC_SEARCH_DEADLINE_MS` is set, searches which run past it are cancelled, along with their in-flight embedding and Qdrant requests, and rejected with a 408, unless a hybrid search reached the deadline while reranking results it had already retrieved, in which case those results are returned with `partial` set. When the dataset sets public `allowed_origins`, searches from other origins are rejected with a 403. When the dataset sets a public `max_rps`, responses carry `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` (seconds until the limit is fully replenished) headers and searches beyond it are rejected with a 429.
+#[utoipa::path(
+    post,
+    path = "/public_page/{dataset_id}/search",
+    context_path = "/api",
+    tag = "Public",
+    request_body(content = PublicPageSearchReqPayload, description = "JSON request payload to search a public dataset", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Chunks which match the query", body = PublicPageSearchResponseBody),
+        (status = 400, description = "Service error relating to searching", body = ErrorResponseBody),
+        (status = 403, description = "The dataset's public page is disabled or the request's Origin is not in its allowed_origins", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody),
+        (status = 408, description = "The search ran past PUBLIC_SEARCH_DEADLINE_MS and was cancelled", body = ErrorResponseBody),
+        (status = 429, description = "The dataset's public max_rps was exceeded, retry after the Retry-After header", body = ErrorResponseBody),
+        (status = 503, description = "Too many public searches are in flight, retry after the Retry-After header", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset you want to search."),
+    ),
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn public_search(
+    dataset_id: web::Path<uuid::Uuid>,
+    data: web::Json<PublicPageSearchReqPayload>,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+    event_queue: web::Data<EventQueue>,
+    clickhouse_client: web::Data<clickhouse::Client>,
+    metrics: web::Data<Metrics>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let started_at = std::time::Instant::now();
+    let metrics_dataset_id = *dataset_id;
+
+    match search_public_page(
+        dataset_id,
+        data,
+        pool,
+        redis_pool,
+        event_queue,
+        clickhouse_client,
+        metrics.clone(),
+        req,
+    )
+    .await
+    {
+        Ok((response, search_type, result_count)) => {
+            metrics.record_public_search(
+                metrics_dataset_id,
+                &search_type,
+                started_at.elapsed(),
+                result_count,
+            );
+            Ok(response)
+        }
+        Err(err) => {
+            metrics.record_public_search_error(
+                metrics_dataset_id,
+                err.as_response_error().status_code().as_u16(),
+            );
+            Err(err)
+        }
+    }
+}
+
+/// Runs a public search, returning the response with the search method it was searched with and
+/// its number of results for metrics.
+#[allow(clippy::too_many_arguments)]
+async fn search_public_page(
+    dataset_id: web::Path<uuid::Uuid>,
+    data: web::Json<PublicPageSearchReqPayload>,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+    event_queue: web::Data<EventQueue>,
+    clickhouse_client: web::Data<clickhouse::Client>,
+    metrics: web::Data<Metrics>,
+    req: HttpRequest,
+) -> Result<(HttpResponse, SearchMethod, usize), actix_web::Error> {
+    let started_at = std::time::Instant::now();
+
+    let dataset_id = dataset_id.into_inner();
+    let data = data.into_inner();
+
+    if data
+        .dataset_id
+        .is_some_and(|payload_dataset_id| payload_dataset_id != dataset_id)
+    {
+        return Err(ServiceError::BadRequest(
+            "dataset_id in the request does not match the dataset_id in the path".to_string(),
+        )
+        .into());
+    }
+
+    let dataset = get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool.clone()).await?;
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Err(ServiceError::Forbidden.into());
+    }
+
+    let guards = PublicSearchGuards::check(&req, dataset_id, &config, &metrics)?;
+
+    let query = data.query.trim().to_string();
+    let max_query_length = config.PUBLIC_DATASET.max_query_length.unwrap_or(512);
+    if query.chars().count() > max_query_length {
+        return Err(ServiceError::BadRequest(format!(
+            "Query must be at most {} characters",
+            max_query_length
+        ))
+        .into());
+    }
+
+    let empty_query_behavior = query.is_empty().then(|| {
+        config
+            .PUBLIC_DATASET
+            .empty_query_behavior
+            .unwrap_or_default()
+    });
+    if empty_query_behavior == Some(PublicEmptyQueryBehavior::Error) {
+        return Err(ServiceError::BadRequest("Query must not be empty".to_string()).into());
+    }
+
+    let mut search_options = data
+        .search_options
+        .or(config
+            .PUBLIC_DATASET
+            .extra_params
+            .clone()
+            .and_then(|params| params.search_options))
+        .unwrap_or_default();
+
+    validate_public_search_options(&search_options, &config)?;
+
+    let mut warnings = vec![];
+
+    let explain_requested = search_options.explain.unwrap_or(false);
+    let explain = explain_requested
+        && req.extensions().get::<LoggedUser>().is_some_and(|user| {
+            user.user_orgs
+                .iter()
+                .any(|user_org| user_org.organization_id == dataset.organization_id)
+        });
+    if explain_requested && !explain {
+        warnings.push(
+            "explain was ignored, it is only honored for logged in members of the dataset's organization"
+                .to_string(),
+        );
+    }
+
+    let debug_filter_requested = search_options.debug_filter.unwrap_or(false);
+    let debug_filter_allowed = debug_filter_requested
+        && req.extensions().get::<LoggedUser>().is_some_and(|user| {
+            user.user_orgs
+                .iter()
+                .any(|user_org| user_org.organization_id == dataset.organization_id)
+        });
+    if debug_filter_requested && !debug_filter_allowed {
+        warnings.push(
+            "debug_filter was ignored, it is only honored for logged in members of the dataset's organization"
+                .to_string(),
+        );
+    }
+
+    let group_metadata_requested = search_options.include_group_metadata.unwrap_or(false);
+    let include_group_metadata = group_metadata_requested
+        && config
+            .PUBLIC_DATASET
+            .extra_params
+            .as_ref()
+            .and_then(|params| params.use_group_search)
+            .unwrap_or(false);
+    if group_metadata_requested && !include_group_metadata {
+        warnings.push(
+            "include_group_metadata was ignored, the public page does not have use_group_search enabled"
+                .to_string(),
+        );
+    }
+
+    // A page_size of 0 searches a page of slim chunks for the metadata and drops the chunks
+    let metadata_only = search_options.page_size == Some(0);
+    if metadata_only {
+        search_options.page_size = None;
+        search_options.slim_chunks = Some(true);
+    }
+
+    let case_sensitive = search_options.case_sensitive.unwrap_or(false);
+    let include_tag_facets = search_options.include_tag_facets.unwrap_or(false);
+    let approximate_count = search_options.approximate_count.unwrap_or(false);
+    let matched_fields_requested = search_options.include_matched_fields.unwrap_or(false);
+    let field_query_weights = search_options.field_query_weights.clone();
+    let ordering_seed = search_options.ordering_seed;
+    let preview_chars = search_options.preview_chars;
+    let strip_markdown_requested = search_options.strip_markdown.unwrap_or(false);
+    let normalize_scores = search_options.normalize_scores.unwrap_or(false);
+    let include_timings = search_options.include_timings.unwrap_or(false);
+    let dedupe_by_content = search_options.dedupe_by_content.unwrap_or(false);
+    let check_next_page = search_options.check_next_page.unwrap_or(false);
+    let search_document_titles = search_options.search_document_titles.unwrap_or(false);
+    let max_vectors_page_size = get_max_public_vectors_page_size();
+    let include_vectors_requested = search_options.include_vectors.unwrap_or(false);
+    let include_vectors = include_vectors_requested && max_vectors_page_size > 0;
+    if include_vectors_requested && !include_vectors {
+        warnings.push(
+            "include_vectors was ignored, it is disabled by PUBLIC_SEARCH_MAX_VECTORS_PAGE_SIZE"
+                .to_string(),
+        );
+    }
+    let group_results_by = search_options
+        .group_results_by
+        .clone()
+        .filter(|group_results_by| !group_results_by.trim().is_empty());
+    let mmr_lambda = search_options
+        .scoring_options
+        .as_ref()
+        .and_then(|scoring_options| scoring_options.mmr_lambda);
+    let popularity_boost = search_options
+        .scoring_options
+        .as_ref()
+        .and_then(|scoring_options| scoring_options.popularity_boost)
+        .filter(|popularity_boost| *popularity_boost > 0.0);
+    let max_neighbor_chunks = get_max_public_neighbor_chunks();
+    let neighbor_chunks_requested = search_options.neighbor_chunks.filter(|count| *count > 0);
+    if neighbor_chunks_requested.is_some_and(|count| count > max_neighbor_chunks) {
+        warnings.push(format!(
+            "neighbor_chunks was capped at {}",
+            max_neighbor_chunks
+        ));
+    }
+    let neighbor_chunk_count = neighbor_chunks_requested
+        .map(|count| count.min(max_neighbor_chunks))
+        .filter(|count| *count > 0);
+    let removed_stop_words = search_options.remove_stop_words.unwrap_or(false)
+        && count_non_stop_words(&query)
+            >= search_options.min_terms_after_stopword_removal.unwrap_or(1);
+    search_options.remove_stop_words = Some(removed_stop_words);
+    let forced_search_type = config.PUBLIC_DATASET.force_search_type.clone();
+    let (search_type, fallback_search_type) = match forced_search_type.clone() {
+        Some(forced_search_type) => {
+            log::debug!(
+                "Forcing search_type {} for public search of dataset {}",
+                forced_search_type,
+                dataset_id
+            );
+
+            if let Some(requested_search_type) = search_options
+                .search_type
+                .as_ref()
+                .filter(|requested| **requested != forced_search_type)
+            {
+                warnings.push(format!(
+                    "search_type {} was overridden by the dataset's force_search_type {}",
+                    requested_search_type, forced_search_type
+                ));
+            }
+            if search_options.fallback_search_type.is_some() {
+                warnings.push(
+                    "fallback_search_type was ignored, the dataset forces its search_type"
+                        .to_string(),
+                );
+            }
+
+            (forced_search_type, None)
+        }
+        None => (
+            search_options.search_type.clone().unwrap_or_default(),
+            search_options.fallback_search_type.clone(),
+        ),
+    };
+    let mut search_payload =
+        search_options.into_search_chunks_req_payload(query.clone(), search_type);
+    if include_vectors && search_payload.page_size.unwrap_or(10) > max_vectors_page_size {
+        warnings.push(format!(
+            "page_size was capped at {} because include_vectors was requested",
+            max_vectors_page_size
+        ));
+        search_payload.page_size = Some(max_vectors_page_size);
+    }
+    let requested_page = search_payload.page.unwrap_or(1).max(1);
+    let requested_page_size = search_payload.page_size.unwrap_or(10);
+    let max_candidates = get_max_public_candidates();
+    let dedupe_candidates = if dedupe_by_content && !metadata_only && !query.is_empty() {
+        get_candidate_page_size(
+            requested_page,
+            requested_page_size,
+            config.PUBLIC_DATASET.candidate_multiplier.unwrap_or(2.0),
+            max_candidates,
+        )
+    } else {
+        None
+    };
+    // Pages are fetched by page number, so checking a later page fetches every result before it
+    let next_page_candidates = if check_next_page && !metadata_only && requested_page_size > 0 {
+        let page_end = requested_page * requested_page_size;
+        if requested_page == 1 || page_end < max_candidates {
+            Some(page_end + 1)
+        } else {
+            warnings.push(format!(
+                "check_next_page was ignored, it is only honored for pages ending within the first {} results",
+                max_candidates
+            ));
+            None
+        }
+    } else {
+        None
+    };
+    // The first results up to the end of the requested page and the candidates beyond it are
+    // fetched as one page, then cut back to the requested page
+    let candidate_page_size = dedupe_candidates.max(next_page_candidates);
+    if let Some(candidate_page_size) = candidate_page_size {
+        search_payload.page = Some(1);
+        search_payload.page_size = Some(candidate_page_size);
+    }
+    if let Some(bm25_language) = config
+        .BM25_LANGUAGE
+        .as_ref()
+        .filter(|language| !is_supported_bm25_language(language))
+    {
+        let uses_bm25 = [Some(&search_type), fallback_search_type.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|search_type| *search_type == SearchMethod::BM25);
+        if uses_bm25 {
+            warnings.push(format!(
+                "BM25_LANGUAGE {} is not supported, BM25 used the default English tokenizer",
+                bm25_language
+            ));
+        }
+    }
+    if let Some(scoring_options) = search_payload
+        .scoring_options
+        .as_ref()
+        .filter(|scoring_options| scoring_options.rerank_candidates.is_some())
+    {
+        let max_rerank_candidates = get_max_rerank_candidates();
+        if search_type != SearchMethod::Hybrid {
+            warnings.push(format!(
+                "rerank_candidates was ignored, it is only honored for hybrid searches, not {}",
+                search_type
+            ));
+        } else if scoring_options.dense_weight.is_some() || scoring_options.sparse_weight.is_some()
+        {
+            warnings.push(
+                "rerank_candidates was ignored, dense_weight and sparse_weight replace the rerank"
+                    .to_string(),
+            );
+        } else if scoring_options
+            .rerank_candidates
+            .is_some_and(|rerank_candidates| rerank_candidates as u64 > max_rerank_candidates)
+        {
+            warnings.push(format!(
+                "rerank_candidates was capped at {}",
+                max_rerank_candidates
+            ));
+        }
+    }
+
+    let mut timer = Timer::new();
+    let deadline = guards.deadline;
+    let search_started_at = std::time::Instant::now();
+
+    let (mut result_chunks, mut partial) = match empty_query_behavior {
+        Some(PublicEmptyQueryBehavior::MatchAll) => {
+            warnings.push(
+                "the query was empty, results match the filters and are not ranked by relevance"
+                    .to_string(),
+            );
+
+            let result_chunks = run_before_public_search_deadline(
+                deadline,
+                match_all_public_chunks(&search_payload, &dataset, &config, pool.clone()),
+            )
+            .await?;
+
+            (result_chunks, false)
+        }
+        Some(_) => {
+            warnings.push("the query was empty, no results were returned".to_string());
+
+            (
+                SearchChunkQueryResponseBody {
+                    score_chunks: vec![],
+                    corrected_query: None,
+                    total_chunk_pages: 0,
+                },
+                false,
+            )
+        }
+        // The rerank deadline is the search deadline itself. The timeout below polls the search
+        // before its own timer, so a search returning partial results at the deadline wins the
+        // race.
+        None => {
+            run_before_public_search_deadline(
+                deadline,
+                search_public_chunks(
+                    search_payload.clone(),
+                    pool.clone(),
+                    redis_pool.clone(),
+                    dataset.clone(),
+                    case_sensitive,
+                    &config,
+                    &mut timer,
+                    deadline,
+                ),
+            )
+            .await?
+        }
+    };
+
+    timer.add("search_chunks");
+
+    let mut used_fallback = false;
+    if let Some(fallback_search_type) = fallback_search_type.filter(|_| !query.is_empty()) {
+        if result_chunks.score_chunks.is_empty()
+            && fallback_search_type != search_payload.search_type
+        {
+            search_payload.search_type = fallback_search_type;
+
+            (result_chunks, partial) = run_before_public_search_deadline(
+                deadline,
+                search_public_chunks(
+                    search_payload.clone(),
+                    pool.clone(),
+                    redis_pool.clone(),
+                    dataset.clone(),
+                    case_sensitive,
+                    &config,
+                    &mut timer,
+                    deadline,
+                ),
+            )
+            .await?;
+            used_fallback = true;
+
+            timer.add("fallback_search_chunks");
+        }
+    }
+
+    if partial {
+        warnings.push(
+            "the search reached PUBLIC_SEARCH_DEADLINE_MS while reranking, results are partial: in retrieval order, not reranked and not filtered by score_threshold"
+                .to_string(),
+        );
+    }
+
+    if let Some(candidate_page_size) = candidate_page_size {
+        search_payload.page = Some(requested_page);
+        search_payload.page_size = Some(requested_page_size);
+        result_chunks.total_chunk_pages = (result_chunks.total_chunk_pages
+            * candidate_page_size as i64
+            + requested_page_size as i64
+            - 1)
+            / requested_page_size as i64;
+    }
+
+    if let Some(popularity_boost) = popularity_boost {
+        if env::var("USE_ANALYTICS").unwrap_or("false".to_string()) == "true" {
+            match get_chunk_click_through_rates_query(
+                dataset.id,
+                get_public_popularity_window_days(),
+                clickhouse_client.get_ref(),
+            ) {
+                Some(click_through_rates) => {
+                    apply_popularity_boost(
+                        &mut result_chunks.score_chunks,
+                        &click_through_rates,
+                        popularity_boost,
+                    );
+                    break_score_ties(&mut result_chunks.score_chunks);
+                }
+                None => warnings.push(
+                    "popularity_boost was ignored, the dataset's click-through rates are still loading"
+                        .to_string(),
+                ),
+            }
+
+            timer.add("popularity_boost");
+        } else {
+            warnings.push(
+                "popularity_boost was ignored, analytics is disabled on this server".to_string(),
+            );
+        }
+    }
+
+    if search_document_titles && !metadata_only && !query.is_empty() {
+        match document_title_search_unsupported_reason(&search_payload, &config) {
+            Some(reason) => {
+                warnings.push(format!("search_document_titles was ignored, {}", reason))
+            }
+            None => {
+                add_document_title_matches(
+                    &mut result_chunks.score_chunks,
+                    &get_query_terms(&query),
+                    candidate_page_size.unwrap_or(requested_page_size) as usize,
+                    dataset.id,
+                    pool.clone(),
+                )
+                .await?;
+
+                timer.add("document_titles");
+            }
+        }
+    }
+
+    let timings = include_timings.then(|| {
+        let embed_ms = sum_timer_stages(&timer, |name| {
+            name.starts_with("computed") && (name.contains("vector") || name.contains("embedding"))
+        });
+        let rerank_ms = sum_timer_stages(&timer, |name| name == "reranking");
+        let search_phase_ms = search_started_at.elapsed().as_secs_f64() * 1000.0;
+
+        PublicPageSearchTimings {
+            embed_ms,
+            search_ms: (search_phase_ms - embed_ms - rerank_ms).max(0.0),
+            rerank_ms,
+            total_ms: 0.0,
+        }
+    });
+
+    let tag_facet_chunk_ids = result_chunks
+        .score_chunks
+        .iter()
+        .filter_map(|score_chunk| score_chunk.metadata.first())
+        .map(|metadata| metadata.metadata().id)
+        .collect::<Vec<uuid::Uuid>>();
+
+    if metadata_only {
+        result_chunks.score_chunks.clear();
+    }
+
+    let matches_query_terms = matches!(
+        search_payload.search_type,
+        SearchMethod::FullText | SearchMethod::BM25 | SearchMethod::Hybrid
+    );
+    let terms = get_query_terms(result_chunks.corrected_query.as_ref().unwrap_or(&query));
+
+    if let Some(field_query_weights) = field_query_weights {
+        if matches_query_terms {
+            apply_field_query_weights(
+                &mut result_chunks.score_chunks,
+                &field_query_weights,
+                &terms,
+            );
+            break_score_ties(&mut result_chunks.score_chunks);
+        } else {
+            warnings.push(format!(
+                "field_query_weights was ignored, it is only honored for fulltext, bm25 and hybrid searches, not {}",
+                search_payload.search_type
+            ));
+        }
+    }
+
+    if dedupe_by_content {
+        let deduped = dedupe_by_content_hash(&mut result_chunks.score_chunks);
+        log::debug!(
+            "Dropped {} results with duplicate content from public search of dataset {}",
+            deduped,
+            dataset_id
+        );
+    }
+
+    let has_next_page = next_page_candidates
+        .map(|_| result_chunks.score_chunks.len() as u64 > requested_page * requested_page_size);
+    if candidate_page_size.is_some() {
+        result_chunks.score_chunks = std::mem::take(&mut result_chunks.score_chunks)
+            .into_iter()
+            .skip(((requested_page - 1) * requested_page_size) as usize)
+            .take(requested_page_size as usize)
+            .collect();
+    }
+
+    if let Some(mmr_lambda) = mmr_lambda {
+        if search_payload.search_type == SearchMethod::Semantic {
+            let point_ids = result_chunks
+                .score_chunks
+                .iter()
+                .filter_map(|score_chunk| score_chunk.metadata.first())
+                .map(|chunk| chunk.qdrant_point_id())
+                .collect::<Vec<uuid::Uuid>>();
+            let vectors = get_qdrant_dense_vectors_query(point_ids, &config).await?;
+
+            result_chunks.score_chunks = apply_mmr(
+                std::mem::take(&mut result_chunks.score_chunks),
+                &vectors,
+                mmr_lambda,
+            );
+
+            timer.add("mmr");
+        } else {
+            warnings.push(format!(
+                "mmr_lambda was ignored, it is only honored for semantic searches, not {}",
+                search_payload.search_type
+            ));
+        }
+    }
+
+    if let Some(ordering_seed) = ordering_seed {
+        let score_band = env::var("PUBLIC_SEARCH_ORDERING_SEED_SCORE_BAND")
+            .ok()
+            .and_then(|score_band| score_band.parse::<f64>().ok())
+            .filter(|score_band| score_band.is_finite() && *score_band >= 0.0)
+            .unwrap_or(0.01);
+
+        shuffle_score_bands(&mut result_chunks.score_chunks, ordering_seed, score_band);
+    }
+
+    let matched_fields = if matched_fields_requested && matches_query_terms {
+        Some(
+            result_chunks
+                .score_chunks
+                .iter()
+                .filter_map(|score_chunk| score_chunk.metadata.first())
+                .map(|chunk| {
+                    let chunk = chunk.metadata();
+
+                    PublicPageMatchedFields {
+                        chunk_id: chunk.id,
+                        matched_fields: get_matched_fields(&chunk, &terms),
+                    }
+                })
+                .collect(),
+        )
+    } else {
+        if matched_fields_requested {
+            warnings.push(format!(
+                "include_matched_fields was ignored, it is only honored for fulltext, bm25 and hybrid searches, not {}",
+                search_payload.search_type
+            ));
+        }
+
+        None
+    };
+
+    let debug_filter = if debug_filter_allowed {
+        let parsed_query = parse_public_query(
+            query.clone(),
+            search_payload.use_quote_negated_terms,
+            search_payload.remove_stop_words,
+            case_sensitive,
+            &config,
+        );
+        let filter = assemble_qdrant_filter(
+            search_payload.filters.clone(),
+            parsed_query.quote_words,
+            parsed_query.negated_words,
+            dataset.id,
+            pool.clone(),
+        )
+        .await?;
+
+        Some(qdrant_filter_to_json(&filter))
+    } else {
+        None
+    };
+
+    let approximate_total = if approximate_count {
+        let filter = assemble_qdrant_filter(
+            search_payload.filters.clone(),
+            None,
+            None,
+            dataset.id,
+            pool.clone(),
+        )
+        .await?;
+
+        let approximate_total = get_approximate_point_count_query(filter, &config).await?;
+
+        timer.add("approximate_count");
+
+        Some(approximate_total)
+    } else {
+        None
+    };
+
+    let explanations = if explain {
+        let parsed_query = parse_public_query(
+            result_chunks
+                .corrected_query
+                .clone()
+                .unwrap_or(query.clone()),
+            search_payload.use_quote_negated_terms,
+            search_payload.remove_stop_words,
+            case_sensitive,
+            &config,
+        );
+
+        let point_ids = result_chunks
+            .score_chunks
+            .iter()
+            .filter_map(|score_chunk| score_chunk.metadata.first())
+            .map(|metadata| metadata.qdrant_point_id())
+            .collect::<Vec<uuid::Uuid>>();
+
+        let component_scores = get_component_scores_for_points(
+            parsed_query,
+            point_ids,
+            search_payload.scoring_options.clone(),
+            &config,
+        )
+        .await?;
+
+        timer.add("explain_scores");
+
+        let reranked = search_payload.search_type == SearchMethod::Hybrid
+            || search_payload
+                .sort_options
+                .as_ref()
+                .and_then(|sort_options| sort_options.sort_by.as_ref())
+                .is_some_and(|sort_by| matches!(sort_by, QdrantSortBy::SearchType(_)));
+
+        let component_score = |search_method: SearchMethod, point_id: uuid::Uuid| {
+            component_scores
+                .get(&search_method)
+                .and_then(|scores| scores.get(&point_id))
+                .copied()
+        };
+
+        Some(
+            result_chunks
+                .score_chunks
+                .iter()
+                .filter_map(|score_chunk| {
+                    let metadata = score_chunk.metadata.first()?;
+                    let point_id = metadata.qdrant_point_id();
+
+                    Some(PublicPageScoreExplanation {
+                        chunk_id: metadata.metadata().id,
+                        dense_score: component_score(SearchMethod::Semantic, point_id),
+                        fulltext_score: component_score(SearchMethod::FullText, point_id),
+                        bm25_score: component_score(SearchMethod::BM25, point_id),
+                        rerank_score: reranked.then_some(score_chunk.score),
+                        boosts: search_payload.scoring_options.clone(),
+                        score: score_chunk.score,
+                    })
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let groups = if include_group_metadata {
+        let chunk_ids = result_chunks
+            .score_chunks
+            .iter()
+            .filter_map(|score_chunk| score_chunk.metadata.first())
+            .map(|metadata| metadata.metadata().id)
+            .collect::<Vec<uuid::Uuid>>();
+
+        let groups = get_groups_for_bookmark_query(chunk_ids, dataset.id, pool.clone()).await?;
+
+        timer.add("group_metadata");
+
+        Some(groups)
+    } else {
+        None
+    };
+
+    let neighbor_chunks = if let Some(neighbor_chunk_count) = neighbor_chunk_count {
+        let chunk_ids = result_chunks
+            .score_chunks
+            .iter()
+            .filter_map(|score_chunk| score_chunk.metadata.first())
+            .map(|metadata| metadata.metadata().id)
+            .collect::<Vec<uuid::Uuid>>();
+
+        let chunk_neighbor_ids = get_neighbor_chunk_ids_in_groups_query(
+            chunk_ids,
+            neighbor_chunk_count as i64,
+            dataset.id,
+            pool.clone(),
+        )
+        .await?;
+
+        let neighbor_ids = chunk_neighbor_ids
+            .iter()
+            .flat_map(|neighbors| neighbors.before.iter().chain(neighbors.after.iter()))
+            .copied()
+            .unique()
+            .collect::<Vec<uuid::Uuid>>();
+        let neighbor_metadatas: HashMap<uuid::Uuid, ChunkMetadataStringTagSet> =
+            get_metadata_from_ids_query(neighbor_ids, dataset.id, pool.clone())
+                .await?
+                .into_iter()
+                .map(|chunk_metadata| (chunk_metadata.id, chunk_metadata.into()))
+                .collect();
+        let get_neighbors = |ids: &[uuid::Uuid]| {
+            ids.iter()
+                .filter_map(|id| neighbor_metadatas.get(id).cloned())
+                .collect::<Vec<ChunkMetadataStringTagSet>>()
+        };
+
+        timer.add("neighbor_chunks");
+
+        Some(
+            chunk_neighbor_ids
+                .iter()
+                .map(|neighbors| PublicPageNeighborChunks {
+                    chunk_id: neighbors.chunk_id,
+                    group_id: neighbors.group_id,
+                    before: get_neighbors(&neighbors.before),
+                    after: get_neighbors(&neighbors.after),
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let vectors = if include_vectors {
+        let point_ids = result_chunks
+            .score_chunks
+            .iter()
+            .filter_map(|score_chunk| score_chunk.metadata.first())
+            .map(|chunk| chunk.qdrant_point_id())
+            .collect::<Vec<uuid::Uuid>>();
+        let mut vectors = get_qdrant_dense_vectors_query(point_ids, &config).await?;
+
+        timer.add("vectors");
+
+        Some(
+            result_chunks
+                .score_chunks
+                .iter()
+                .filter_map(|score_chunk| score_chunk.metadata.first())
+                .filter_map(|chunk| {
+                    Some(PublicPageResultVector {
+                        chunk_id: chunk.metadata().id,
+                        vector: vectors.remove(&chunk.qdrant_point_id())?,
+                    })
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let tag_facets = if include_tag_facets {
+        let max_tag_facets = env::var("PUBLIC_SEARCH_MAX_TAG_FACETS")
+            .ok()
+            .and_then(|max_tag_facets| max_tag_facets.parse::<i64>().ok())
+            .unwrap_or(50);
+
+        let tag_facets = get_tag_facets_for_chunks_query(
+            dataset.id,
+            tag_facet_chunk_ids,
+            max_tag_facets,
+            pool.clone(),
+        )
+        .await?;
+
+        timer.add("tag_facets");
+
+        Some(tag_facets)
+    } else {
+        None
+    };
+
+    if let Some(prefetch_queries) = data.prefetch_queries {
+        spawn_prefetch_embeddings(
+            prefetch_queries,
+            &search_payload,
+            case_sensitive,
+            config.clone(),
+        );
+    }
+
+    let search_id = uuid::Uuid::new_v4();
+
+    let redacted_query = redact_public_query(&query, &config);
+    let redacted_search_payload = SearchChunksReqPayload {
+        query: QueryTypes::Single(redacted_query.clone()),
+        ..search_payload.clone()
+    };
+
+    // Marks the search as a public page search, popular suggested queries are only taken from these
+    let mut request_params = serde_json::to_value(&redacted_search_payload).unwrap_or_default();
+    if let Some(request_params) = request_params.as_object_mut() {
+        request_params.insert("public_page".to_string(), serde_json::Value::Bool(true));
+    }
+
+    let clickhouse_event = SearchQueryEventClickhouse {
+        id: search_id,
+        search_type: String::from("search"),
+        query: redacted_query,
+        request_params: request_params.to_string(),
+        latency: get_latency_from_header(timer.header_value()),
+        top_score: result_chunks
+            .score_chunks
+            .first()
+            .map(|x| x.score as f32)
+            .unwrap_or(0.0),
+        results: result_chunks
+            .score_chunks
+            .clone()
+            .into_iter()
+            .map(|x| {
+                let mut json = serde_json::to_value(&x).unwrap_or_default();
+                escape_quotes(&mut json);
+                json.to_string()
+            })
+            .collect(),
+        dataset_id: dataset.id,
+        created_at: time::OffsetDateTime::now_utc(),
+        query_rating: String::from(""),
+        user_id: search_payload.user_id.clone().unwrap_or_default(),
+    };
+
+    event_queue
+        .send(ClickHouseEvent::SearchQueryEvent(clickhouse_event))
+        .await;
+
+    timer.add("send_to_clickhouse");
+
+    if preview_chars.is_some() || strip_markdown_requested {
+        let preview_terms: &[String] = if matches_query_terms {
+            terms.as_slice()
+        } else {
+            &[]
+        };
+
+        for score_chunk in result_chunks.score_chunks.iter_mut() {
+            for metadata in score_chunk.metadata.iter_mut() {
+                let chunk_html = match metadata {
+                    ChunkMetadataTypes::Metadata(metadata) => &mut metadata.chunk_html,
+                    ChunkMetadataTypes::Content(metadata) => &mut metadata.chunk_html,
+                    ChunkMetadataTypes::ID(_) => continue,
+                };
+
+                if let Some(content) = chunk_html.as_mut() {
+                    let mut text = convert_html_to_text(content);
+                    let mut changed = false;
+
+                    if strip_markdown_requested {
+                        text = strip_markdown(&text);
+                        changed = true;
+                    }
+
+                    if let Some(preview_chars) =
+                        preview_chars.filter(|preview_chars| text.chars().count() > *preview_chars)
+                    {
+                        text = get_content_preview(&text, preview_chars, preview_terms);
+                        changed = true;
+                    }
+
+                    if changed {
+                        *content = text;
+                    }
+                }
+            }
+        }
+    }
+
+    let score_precision = config.PUBLIC_DATASET.score_precision.unwrap_or(4);
+    let public_score = |score: f64| {
+        let score = if normalize_scores {
+            normalize_public_score(score, &search_payload.search_type, &config.DISTANCE_METRIC)
+        } else {
+            score
+        };
+
+        round_score(score, score_precision)
+    };
+    let explanations = explanations.map(|explanations| {
+        explanations
+            .into_iter()
+            .map(|explanation| PublicPageScoreExplanation {
+                dense_score: explanation
+                    .dense_score
+                    .map(|score| round_score(score as f64, score_precision) as f32),
+                fulltext_score: explanation
+                    .fulltext_score
+                    .map(|score| round_score(score as f64, score_precision) as f32),
+                bm25_score: explanation
+                    .bm25_score
+                    .map(|score| round_score(score as f64, score_precision) as f32),
+                rerank_score: explanation
+                    .rerank_score
+                    .map(|score| round_score(score, score_precision)),
+                score: round_score(explanation.score, score_precision),
+                ..explanation
+            })
+            .collect::<Vec<PublicPageScoreExplanation>>()
+    });
+
+    let result_groups = group_results_by.map(|group_results_by| {
+        let max_result_groups = env::var("PUBLIC_SEARCH_MAX_RESULT_GROUPS")
+            .ok()
+            .and_then(|max_result_groups| max_result_groups.parse::<usize>().ok())
+            .unwrap_or(20);
+
+        let (result_groups, capped) = group_results_by_metadata_key(
+            &result_chunks.score_chunks,
+            &group_results_by,
+            max_result_groups,
+        );
+        if capped {
+            warnings.push(format!(
+                "result_groups was capped at {} groups",
+                max_result_groups
+            ));
+        }
+
+        result_groups
+            .into_iter()
+            .map(|group| PublicPageResultGroup {
+                top_score: public_score(group.top_score),
+                ..group
+            })
+            .collect::<Vec<PublicPageResultGroup>>()
+    });
+
+    let result_count = result_chunks.score_chunks.len();
+    let timings = timings.map(|timings| PublicPageSearchTimings {
+        total_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+        ..timings
+    });
+
+    let response_body = serde_json::to_vec(&PublicPageSearchResponseBody {
+        id: search_id,
+        chunks: result_chunks
+            .score_chunks
+            .into_iter()
+            .map(|chunk| {
+                let mut chunk: ScoreChunk = chunk.into();
+                chunk.score = public_score(chunk.score as f64) as f32;
+                chunk
+            })
+            .collect(),
+        corrected_query: result_chunks.corrected_query,
+        total_pages: result_chunks.total_chunk_pages,
+        approximate_total,
+        used_fallback,
+        partial,
+        effective_score_threshold: search_payload
+            .score_threshold
+            .filter(|threshold| *threshold != 0.0),
+        has_next_page,
+        explanations,
+        matched_fields,
+        groups,
+        neighbor_chunks,
+        vectors,
+        result_groups,
+        timings,
+        debug_filter,
+        tag_facets,
+        forced_search_type,
+        warnings,
+        removed_stop_words,
+    })
+    .map_err(|err| {
+        ServiceError::InternalServerError(format!("Failed to serialize search response: {}", err))
+    })?;
+
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header((Timer::header_key(), timer.header_value()))
+        .content_type("application/json");
+    guards.insert_headers(&mut response);
+
+    let response_body = encode_public_response_body(
+        &req,
+        &mut response,
+        response_body,
+        public_search_compress_min_bytes(),
+    );
+
+    Ok((
+        response.body(response_body),
+        search_payload.search_type,
+        result_count,
+    ))
+}
+
+/// Encodes public responses with zstd at `PUBLIC_COMPRESS_ZSTD_LEVEL` (3 by default) when it is
+/// the client's most preferred encoding. Bodies smaller than `min_bytes` are sent as is and all
+/// other encodings are left to the compress middleware, which skips responses that already have
+/// a Content-Encoding.
+fn encode_public_response_body(
+    req: &HttpRequest,
+    response: &mut HttpResponseBuilder,
+    body: Vec<u8>,
+    min_bytes: usize,
+) -> Vec<u8> {
+    if body.len() < min_bytes {
+        response.insert_header(header::ContentEncoding::Identity);
+        return body;
+    }
+
+    let prefers_zstd = req
+        .get_header::<header::AcceptEncoding>()
+        .and_then(|accept_encoding| {
+            accept_encoding.negotiate(
+                [
+                    header::Encoding::zstd(),
+                    header::Encoding::brotli(),
+                    header::Encoding::gzip(),
+                    header::Encoding::identity(),
+                ]
+                .iter(),
+            )
+        })
+        .is_some_and(|encoding| encoding == header::Encoding::zstd());
+
+    if !prefers_zstd {
+        return body;
+    }
+
+    let level = env::var("PUBLIC_COMPRESS_ZSTD_LEVEL")
+        .ok()
+        .and_then(|level| level.parse::<i32>().ok())
+        .unwrap_or(3);
+
+    match zstd::encode_all(body.as_slice(), level) {
+        Ok(encoded_body) => {
+            response
+                .insert_header(header::ContentEncoding::Zstd)
+                .insert_header((header::VARY, "Accept-Encoding"));
+            encoded_body
+        }
+        Err(err) => {
+            log::error!("Failed to zstd encode public response {:?}", err);
+            body
+        }
+    }
+}
+
+/// Public search responses smaller than `PUBLIC_SEARCH_COMPRESS_MIN_BYTES` (1KB by default) are
+/// sent uncompressed since compressing them costs more CPU than it saves on the wire.
+fn public_search_compress_min_bytes() -> usize {
+    env::var("PUBLIC_SEARCH_COMPRESS_MIN_BYTES")
+        .ok()
+        .and_then(|min_bytes| min_bytes.parse::<usize>().ok())
+        .unwrap_or(1024)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestedQueriesSource {
+    Analytics,
+    Default,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageSuggestedQueriesResponse {
+    pub queries: Vec<String>,
+    /// Whether the queries are the dataset's popular analytics queries or its `default_search_queries`.
+    pub source: SuggestedQueriesSource,
+}
+
+/// Get Public Page Suggested Queries
+///
+/// Get the suggested queries for a dataset's public page. If the dataset's public config has `popular_suggested_queries` set, these are its most frequent public page search queries from analytics, leaving out queries its redaction patterns match. Falls back to the page's `default_search_queries` when analytics is disabled or has no queries yet.
+#[utoipa::path(
+    get,
+    path = "/public_page/{dataset_id}/suggested_queries",
+    context_path = "/api",
+    tag = "Public",
+    responses(
+        (status = 200, description = "Suggested queries for the public page", body = PublicPageSuggestedQueriesResponse),
+        (status = 403, description = "The public page is not enabled for this dataset", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to get suggested queries for."),
+    ),
+)]
+pub async fn public_suggested_queries(
+    dataset_id: web::Path<uuid::Uuid>,
+    pool: web::Data<Pool>,
+    clickhouse_client: web::Data<clickhouse::Client>,
+) -> Result<HttpResponse, ServiceError> {
+    let dataset_id = dataset_id.into_inner();
+
+    let dataset = get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool).await?;
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration);
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let analytics_enabled = env::var("USE_ANALYTICS").unwrap_or("false".to_string()) == "true";
+
+    if let Some(popular_options) = config
+        .PUBLIC_DATASET
+        .popular_suggested_queries
+        .clone()
+        .filter(|_| analytics_enabled)
+    {
+        let popular_queries = get_popular_queries_query(
+            dataset_id,
+            popular_options.window_days.unwrap_or(30),
+            popular_options.top_n.unwrap_or(5),
+            clickhouse_client.get_ref(),
+        )
+        .await
+        .unwrap_or_else(|err| {
+            log::error!("Failed to get popular queries for suggestions {:?}", err);
+            vec![]
+        })
+        .into_iter()
+        // Queries stored before the dataset's current query_redaction_patterns may still hold
+        // text they redact, and a suggestion with a [REDACTED] hole in it is no use anyway
+        .filter(|query| redact_public_query(query, &config) == *query)
+        .collect::<Vec<String>>();
+
+        if !popular_queries.is_empty() {
+            return Ok(HttpResponse::Ok().json(PublicPageSuggestedQueriesResponse {
+                queries: popular_queries,
+                source: SuggestedQueriesSource::Analytics,
+            }));
+        }
+    }
+
+    let default_queries = config
+        .PUBLIC_DATASET
+        .extra_params
+        .and_then(|params| params.default_search_queries)
+        .unwrap_or_default();
+
+    Ok(HttpResponse::Ok().json(PublicPageSuggestedQueriesResponse {
+        queries: default_queries,
+        source: SuggestedQueriesSource::Default,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageConfigVersionResponse {
+    /// Version of the dataset's configuration, increasing with every update. Matches the `configVersion` of the public page parameters when they are current.
+    pub config_version: i64,
+}
+
+/// Get Public Page Config Version
+///
+/// Get the version of a dataset's public page configuration. Embeds which cache the page parameters can poll this to cheaply check whether their cached `configVersion` is stale and the parameters should be refetched.
+#[utoipa::path(
+    get,
+    path = "/public_page/{dataset_id}/version",
+    context_path = "/api",
+    tag = "Public",
+    responses(
+        (status = 200, description = "The current config version of the public page", body = PublicPageConfigVersionResponse),
+        (status = 403, description = "The public page is not enabled for this dataset", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to get the config version of."),
+    ),
+)]
+pub async fn public_config_version(
+    dataset_id: web::Path<uuid::Uuid>,
+    pool: web::Data<Pool>,
+) -> Result<HttpResponse, ServiceError> {
+    let dataset =
+        get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id.into_inner()), pool).await?;
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Err(ServiceError::Forbidden);
+    }
+
+    Ok(HttpResponse::Ok()
+        .insert_header(header::CacheControl(vec![header::CacheDirective::NoCache]))
+        .json(PublicPageConfigVersionResponse {
+            config_version: get_config_version(&dataset),
+        }))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[schema(example = json!({
+    "query": "how do I get startd"
+}))]
+pub struct PublicPageCorrectQueryReqPayload {
+    /// The query to spell check.
+    pub query: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageCorrectQueryResponse {
+    /// The corrected query, or the original query unchanged when no correction applies.
+    pub corrected_query: String,
+    /// True when at least one word of the query was corrected.
+    pub corrected: bool,
+    /// Mean confidence, from 0 to 1, of the corrections. Only present when the query was corrected.
+    pub confidence: Option<f32>,
+}
+
+/// Correct Public Page Query
+///
+/// Spell check a query against the dataset without searching, e.g. to show a "did you mean" suggestion. Applies the `typo_options` of the public page's `search_options`, with typo correction turned on, and returns the original query unchanged when no correction applies.
+#[utoipa::path(
+    post,
+    path = "/public_page/{dataset_id}/correct_query",
+    context_path = "/api",
+    tag = "Public",
+    request_body(content = PublicPageCorrectQueryReqPayload, description = "JSON request payload to correct a query", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The corrected query", body = PublicPageCorrectQueryResponse),
+        (status = 400, description = "Service error relating to correcting the query", body = ErrorResponseBody),
+        (status = 403, description = "The public page is not enabled for this dataset", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to spell check the query against."),
+    ),
+)]
+pub async fn public_correct_query(
+    dataset_id: web::Path<uuid::Uuid>,
+    data: web::Json<PublicPageCorrectQueryReqPayload>,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let dataset_id = dataset_id.into_inner();
+
+    let dataset = get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool).await?;
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration);
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let query = data.into_inner().query.trim().to_string();
+    let max_query_length = config.PUBLIC_DATASET.max_query_length.unwrap_or(512);
+    if query.chars().count() > max_query_length {
+        return Err(ServiceError::BadRequest(format!(
+            "Query must be at most {} characters",
+            max_query_length
+        )));
+    }
+
+    // Domain specific words are only quoted to require them in search results
+    let typo_options = TypoOptions {
+        correct_typos: Some(true),
+        prioritize_domain_specifc_words: Some(false),
+        ..config
+            .PUBLIC_DATASET
+            .extra_params
+            .and_then(|params| params.search_options)
+            .and_then(|search_options| search_options.typo_options)
+            .unwrap_or_default()
+    };
+
+    let corrected = correct_query(
+        parse_query(query.clone(), None, None),
+        dataset.id,
+        redis_pool,
+        &typo_options,
+    )
+    .await?;
+
+    let response = match (corrected.corrected, corrected.query) {
+        (true, Some(corrected_query)) => PublicPageCorrectQueryResponse {
+            corrected_query: corrected_query.query,
+            corrected: true,
+            confidence: corrected.confidence,
+        },
+        _ => PublicPageCorrectQueryResponse {
+            corrected_query: query,
+            corrected: false,
+            confidence: None,
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[schema(example = json!({
+    "query": "how do I get started",
+    "search_options": {"filters": {"must": [{"field": "tag_set", "match_all": ["docs"]}]}},
+    "preview": true
+}))]
+pub struct PublicPageValidateOptionsReqPayload {
+    /// Query to validate, and to preview the results of when `preview` is set.
+    pub query: Option<String>,
+    /// The search options to validate. Defaults to the public page's `search_options`, like public search does.
+    pub search_options: Option<PublicPageSearchOptions>,
+    /// Set preview to true to also run the search and get the ids of the results. Requires a `query`. Default is false, which only validates the options without searching.
+    pub preview: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageSearchPreview {
+    /// Number of results on the requested page.
+    pub result_count: usize,
+    /// Ids of the results on the requested page, in result order.
+    pub chunk_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PublicPageValidateOptionsResponse {
+    /// The search method the options would be searched with, after the dataset's `force_search_type`.
+    pub search_type: SearchMethod,
+    /// The results of the search. Only present when `preview` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<PublicPageSearchPreview>,
+}
+
+/// Validate Public Page Search Options
+///
+/// Check public search options against the dataset before shipping them to an embed. Options are rejected with a 400 for the same reasons as public search, e.g. too many `exclude_ids` or an unknown `embedding_model`. With `preview` set, the search is also run with slim chunks and only the ids of the results are returned. Previews are subject to the same `allowed_origins`, `max_rps`, `PUBLIC_SEARCH_MAX_CONCURRENCY` and `PUBLIC_SEARCH_DEADLINE_MS` limits as public search and carry the same headers, but are not recorded in analytics.
+#[utoipa::path(
+    post,
+    path = "/public_page/{dataset_id}/validate_options",
+    context_path = "/api",
+    tag = "Public",
+    request_body(content = PublicPageValidateOptionsReqPayload, description = "JSON request payload to validate public search options", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The options are valid", body = PublicPageValidateOptionsResponse),
+        (status = 400, description = "The options are invalid for this dataset", body = ErrorResponseBody),
+        (status = 403, description = "The public page is not enabled for this dataset, or a preview's Origin is not in its allowed_origins", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody),
+        (status = 408, description = "The preview search ran past PUBLIC_SEARCH_DEADLINE_MS and was cancelled", body = ErrorResponseBody),
+        (status = 429, description = "The preview exceeded the dataset's public max_rps, retry after the Retry-After header", body = ErrorResponseBody),
+        (status = 503, description = "Too many public searches are in flight, retry after the Retry-After header", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to validate the options against."),
+    ),
+)]
+pub async fn public_validate_options(
+    dataset_id: web::Path<uuid::Uuid>,
+    data: web::Json<PublicPageValidateOptionsReqPayload>,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+    metrics: web::Data<Metrics>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let dataset_id = dataset_id.into_inner();
+    let data = data.into_inner();
+
+    let dataset = get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool.clone()).await?;
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Err(ServiceError::Forbidden.into());
+    }
+
+    let query = data.query.map(|query| query.trim().to_string());
+    let max_query_length = config.PUBLIC_DATASET.max_query_length.unwrap_or(512);
+    if query
+        .as_ref()
+        .is_some_and(|query| query.chars().count() > max_query_length)
+    {
+        return Err(ServiceError::BadRequest(format!(
+            "Query must be at most {} characters",
+            max_query_length
+        ))
+        .into());
+    }
+
+    let search_options = data
+        .search_options
+        .or(config
+            .PUBLIC_DATASET
+            .extra_params
+            .clone()
+            .and_then(|params| params.search_options))
+        .unwrap_or_default();
+
+    validate_public_search_options(&search_options, &config)?;
+
+    let search_type = config
+        .PUBLIC_DATASET
+        .force_search_type
+        .clone()
+        .or(search_options.search_type.clone())
+        .unwrap_or_default();
+
+    let mut guards = None;
+    let preview = if data.preview.unwrap_or(false) {
+        let query = query.ok_or_else(|| {
+            ServiceError::BadRequest("A query is required to preview the search".to_string())
+        })?;
+
+        let preview_guards = PublicSearchGuards::check(&req, dataset_id, &config, &metrics)?;
+        let deadline = preview_guards.deadline;
+
+        // Searches with a page_size of 0 return no chunks, see public_search
+        let metadata_only = search_options.page_size == Some(0);
+        let case_sensitive = search_options.case_sensitive.unwrap_or(false);
+        let mut search_payload =
+            search_options.into_search_chunks_req_payload(query, search_type.clone());
+        search_payload.slim_chunks = Some(true);
+        if metadata_only {
+            search_payload.page_size = None;
+        }
+
+        let (result_chunks, _) = run_before_public_search_deadline(
+            deadline,
+            search_public_chunks(
+                search_payload,
+                pool,
+                redis_pool,
+                dataset,
+                case_sensitive,
+                &config,
+                &mut Timer::new(),
+                deadline,
+            ),
+        )
+        .await?;
+
+        let chunk_ids = result_chunks
+            .score_chunks
+            .iter()
+            .filter(|_| !metadata_only)
+            .filter_map(|score_chunk| score_chunk.metadata.first())
+            .map(|metadata| metadata.metadata().id)
+            .collect::<Vec<uuid::Uuid>>();
+
+        guards = Some(preview_guards);
+
+        Some(PublicPageSearchPreview {
+            result_count: chunk_ids.len(),
+            chunk_ids,
+        })
+    } else {
+        None
+    };
+
+    let mut response = HttpResponse::Ok();
+    if let Some(guards) = &guards {
+        guards.insert_headers(&mut response);
+    }
+
+    Ok(response.json(PublicPageValidateOptionsResponse {
+        search_type,
+        preview,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, Default)]
+pub struct PublicPageFeedOptions {
+    /// Title of the feed. Defaults to the dataset's name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Top level metadata key holding the title of each item. Defaults to the chunk's tracking_id, then the start of its content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title_field: Option<String>,
+    /// Top level metadata key holding the link of each item. Defaults to the chunk's link.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_field: Option<String>,
+    /// Top level metadata key holding the description of each item. Defaults to the chunk's content as plain text, trimmed to 300 characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_field: Option<String>,
+    /// Number of top results in the feed. Defaults to 20, at most 50.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_count: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicPageFeedFormat {
+    Rss,
+    Atom,
+}
+
+#[derive(Deserialize, Debug, ToSchema, IntoParams)]
+pub struct PublicPageFeedQuery {
+    /// The query whose top results are the items of the feed.
+    pub query: String,
+    /// Format of the feed, `rss` or `atom`. Defaults to Atom when the `Accept` header asks for `application/atom+xml` and to RSS 2.0 otherwise.
+    pub format: Option<PublicPageFeedFormat>,
+}
+
+/// How long a rendered feed is served from memory before its search is run again.
+const PUBLIC_FEED_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Most rendered feeds kept in memory, feeds beyond it are rendered on every request until
+/// expired entries are dropped.
+const PUBLIC_FEED_CACHE_MAX_ENTRIES: usize = 1000;
+
+/// Dataset id, config version, base url, format and query of a rendered feed.
+type PublicFeedCacheKey = (uuid::Uuid, i64, String, PublicPageFeedFormat, String);
+
+struct PublicFeedCacheEntry {
+    body: String,
+    expiration: std::time::Instant,
+}
+
+lazy_static! {
+    static ref PUBLIC_FEED_CACHE: DashMap<PublicFeedCacheKey, PublicFeedCacheEntry> =
+        DashMap::new();
+}
+
+struct PublicFeedItem {
+    id: uuid::Uuid,
+    title: String,
+    link: Option<String>,
+    description: String,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .filter(|c| matches!(c, '\t' | '\n' | '\r') || !c.is_control())
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+/// The value of a top level metadata key of the chunk as text, non-string values as JSON.
+fn get_feed_metadata_field(chunk: &ChunkMetadata, field: Option<&String>) -> Option<String> {
+    let field = field?;
+    let field = field.strip_prefix("metadata.").unwrap_or(field);
+
+    match chunk.metadata.as_ref()?.get(field)? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(value) => Some(value.clone()),
+        value => Some(value.to_string()),
+    }
+    .filter(|value| !value.trim().is_empty())
+}
+
+fn get_public_feed_item(
+    chunk: ChunkMetadata,
+    feed_options: &PublicPageFeedOptions,
+) -> PublicFeedItem {
+    let content = convert_html_to_text(chunk.chunk_html.as_deref().unwrap_or_default());
+
+    let title = get_feed_metadata_field(&chunk, feed_options.title_field.as_ref())
+        .or(chunk.tracking_id.clone())
+        .unwrap_or_else(|| get_content_preview(&content, 80, &[]));
+    let link = get_feed_metadata_field(&chunk, feed_options.link_field.as_ref())
+        .or(chunk.link.clone())
+        .filter(|link| !link.trim().is_empty());
+    let description = get_feed_metadata_field(&chunk, feed_options.description_field.as_ref())
+        .unwrap_or_else(|| get_content_preview(&content, 300, &[]));
+
+    PublicFeedItem {
+        id: chunk.id,
+        title,
+        link,
+        description,
+        updated_at: chunk.time_stamp.unwrap_or(chunk.updated_at).and_utc(),
+    }
+}
+
+fn render_rss_feed(title: &str, link: &str, query: &str, items: &[PublicFeedItem]) -> String {
+    let mut feed = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n<title>{}</title>\n<link>{}</link>\n<description>{}</description>\n",
+        escape_xml(title),
+        escape_xml(link),
+        escape_xml(&format!("Top results for \"{}\"", query)),
+    );
+
+    for item in items {
+        feed.push_str("<item>\n");
+        feed.push_str(&format!("<title>{}</title>\n", escape_xml(&item.title)));
+        if let Some(link) = item.link.as_ref() {
+            feed.push_str(&format!("<link>{}</link>\n", escape_xml(link)));
+        }
+        feed.push_str(&format!(
+            "<description>{}</description>\n<guid isPermaLink=\"false\">{}</guid>\n<pubDate>{}</pubDate>\n",
+            escape_xml(&item.description),
+            item.id,
+            item.updated_at.to_rfc2822()
+        ));
+        feed.push_str("</item>\n");
+    }
+
+    feed.push_str("</channel>\n</rss>\n");
+    feed
+}
+
+fn render_atom_feed(
+    title: &str,
+    link: &str,
+    self_link: &str,
+    dataset_id: uuid::Uuid,
+    items: &[PublicFeedItem],
+) -> String {
+    let updated_at = items
+        .iter()
+        .map(|item| item.updated_at)
+        .max()
+        .unwrap_or_else(chrono::Utc::now);
+
+    let mut feed = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n<id>urn:uuid:{}</id>\n<title>{}</title>\n<link href=\"{}\"/>\n<link rel=\"self\" href=\"{}\"/>\n<updated>{}</updated>\n",
+        dataset_id,
+        escape_xml(title),
+        escape_xml(link),
+        escape_xml(self_link),
+        updated_at.to_rfc3339(),
+    );
+
+    for item in items {
+        feed.push_str("<entry>\n");
+        feed.push_str(&format!(
+            "<id>urn:uuid:{}</id>\n<title>{}</title>\n",
+            item.id,
+            escape_xml(&item.title)
+        ));
+        if let Some(link) = item.link.as_ref() {
+            feed.push_str(&format!("<link href=\"{}\"/>\n", escape_xml(link)));
+        }
+        feed.push_str(&format!(
+            "<summary>{}</summary>\n<updated>{}</updated>\n",
+            escape_xml(&item.description),
+            item.updated_at.to_rfc3339()
+        ));
+        feed.push_str("</entry>\n");
+    }
+
+    feed.push_str("</feed>\n");
+    feed
+}
+
+/// Get Public Page Feed
+///
+/// Get the top public search results for a query as an RSS 2.0 or Atom feed, e.g. to follow a saved search in a feed reader. The search is run with the public page's `search_options` and is subject to the same checks and limits as public search: `max_query_length`, `allowed_origins`, `max_rps`, `force_search_type`, `PUBLIC_SEARCH_MAX_CONCURRENCY` and `PUBLIC_SEARCH_DEADLINE_MS`. Since feed readers don't send an `Origin` header, feeds of datasets with `allowed_origins` can only be read from those origins. The limits apply to cached feeds as well. Item titles, links and descriptions are mapped from the metadata fields set in the dataset's public `feed_options`. Feeds are cached for 60 seconds and are not recorded in analytics.
+#[utoipa::path(
+    get,
+    path = "/public_page/{dataset_id}/feed",
+    context_path = "/api",
+    tag = "Public",
+    responses(
+        (status = 200, description = "RSS 2.0 or Atom feed of the top results for the query", content_type = "application/rss+xml", body = String),
+        (status = 400, description = "The query is empty, too long or the page's search options are invalid", body = ErrorResponseBody),
+        (status = 403, description = "The dataset's public page is disabled or the request's Origin is not in its allowed_origins", body = ErrorResponseBody),
+        (status = 404, description = "Dataset not found", body = ErrorResponseBody),
+        (status = 408, description = "The search ran past PUBLIC_SEARCH_DEADLINE_MS and was cancelled", body = ErrorResponseBody),
+        (status = 429, description = "The dataset's public max_rps was exceeded, retry after the Retry-After header", body = ErrorResponseBody),
+        (status = 503, description = "Too many public searches are in flight, retry after the Retry-After header", body = ErrorResponseBody)
+    ),
+    params(
+        ("dataset_id" = uuid::Uuid, Path, description = "The id of the dataset to get the feed of."),
+        PublicPageFeedQuery,
+    ),
+)]
+pub async fn public_feed(
+    dataset_id: web::Path<uuid::Uuid>,
+    feed_query: web::Query<PublicPageFeedQuery>,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+    metrics: web::Data<Metrics>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let dataset_id = dataset_id.into_inner();
+    let feed_query = feed_query.into_inner();
+
+    let dataset = get_dataset_by_id_query(UnifiedId::TrieveUuid(dataset_id), pool.clone()).await?;
+
+    let config = DatasetConfiguration::from_json(dataset.server_configuration.clone());
+
+    if !config.PUBLIC_DATASET.enabled {
+        return Err(ServiceError::Forbidden.into());
+    }
+
+    let guards = PublicSearchGuards::check(&req, dataset_id, &config, &metrics)?;
+
+    let query = feed_query.query.trim().to_string();
+    let max_query_length = config.PUBLIC_DATASET.max_query_length.unwrap_or(512);
+    if query.chars().count() > max_query_length {
+        return Err(ServiceError::BadRequest(format!(
+            "Query must be at most {} characters",
+            max_query_length
+        ))
+        .into());
+    }
+    if query.is_empty() {
+        return Err(ServiceError::BadRequest("Query must not be empty".to_string()).into());
+    }
+
+    let format = feed_query.format.unwrap_or_else(|| {
+        let accepts_atom = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains("application/atom+xml"));
+
+        if accepts_atom {
+            PublicPageFeedFormat::Atom
+        } else {
+            PublicPageFeedFormat::Rss
+        }
+    });
+
+    let base_server_url = get_env!(
+        "BASE_SERVER_URL",
+        "Server hostname for OpenID provider must be set"
+    );
+    let base_url = get_public_page_base_url(&req, base_server_url);
+
+    let cache_key = (
+        dataset_id,
+        get_config_version(&dataset),
+        base_url.clone(),
+        format,
+        query.clone(),
+    );
+    let cached_body = PUBLIC_FEED_CACHE
+        .get(&cache_key)
+        .filter(|entry| entry.expiration > std::time::Instant::now())
+        .map(|entry| entry.body.clone());
+
+    let body = match cached_body {
+        Some(body) => body,
+        None => {
+            let search_options = config
+                .PUBLIC_DATASET
+                .extra_params
+                .clone()
+                .and_then(|params| params.search_options)
+                .unwrap_or_default();
+
+            validate_public_search_options(&search_options, &config)?;
+
+            let feed_options = config
+                .PUBLIC_DATASET
+                .feed_options
+                .clone()
+                .unwrap_or_default();
+            let search_type = config
+                .PUBLIC_DATASET
+                .force_search_type
+                .clone()
+                .or(search_options.search_type.clone())
+                .unwrap_or_default();
+            let case_sensitive = search_options.case_sensitive.unwrap_or(false);
+
+            let mut search_payload =
+                search_options.into_search_chunks_req_payload(query.clone(), search_type);
+            search_payload.page = Some(1);
+            search_payload.page_size = Some(feed_options.item_count.unwrap_or(20).clamp(1, 50));
+            search_payload.get_total_pages = Some(false);
+            search_payload.slim_chunks = Some(false);
+            search_payload.content_only = Some(false);
+
+            let deadline = guards.deadline;
+            let (result_chunks, _) = run_before_public_search_deadline(
+                deadline,
+                search_public_chunks(
+                    search_payload,
+                    pool,
+                    redis_pool,
+                    dataset.clone(),
+                    case_sensitive,
+                    &config,
+                    &mut Timer::new(),
+                    deadline,
+                ),
+            )
+            .await?;
+
+            let items = result_chunks
+                .score_chunks
+                .into_iter()
+                .filter_map(|score_chunk| score_chunk.metadata.into_iter().next())
+                .map(|chunk| get_public_feed_item(chunk.metadata(), &feed_options))
+                .collect::<Vec<PublicFeedItem>>();
+
+            let title = feed_options
+                .title
+                .clone()
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or(dataset.name.clone());
+            let link = format!("{}/public_page/{}", base_url, dataset_id);
+
+            let body = match format {
+                PublicPageFeedFormat::Rss => render_rss_feed(&title, &link, &query, &items),
+                PublicPageFeedFormat::Atom => {
+                    let self_link = match reqwest::Url::parse_with_params(
+                        &format!("{}/api/public_page/{}/feed", base_url, dataset_id),
+                        &[("query", query.as_str()), ("format", "atom")],
+                    ) {
+                        Ok(self_link) => self_link.to_string(),
+                        Err(_) => link.clone(),
+                    };
+
+                    render_atom_feed(&title, &link, &self_link, dataset_id, &items)
+                }
+            };
+
+            if PUBLIC_FEED_CACHE.len() >= PUBLIC_FEED_CACHE_MAX_ENTRIES {
+                let now = std::time::Instant::now();
+                PUBLIC_FEED_CACHE.retain(|_, entry| entry.expiration > now);
+            }
+            if PUBLIC_FEED_CACHE.len() < PUBLIC_FEED_CACHE_MAX_ENTRIES {
+                PUBLIC_FEED_CACHE.insert(
+                    cache_key,
+                    PublicFeedCacheEntry {
+                        body: body.clone(),
+                        expiration: std::time::Instant::now() + PUBLIC_FEED_CACHE_TTL,
+                    },
+                );
+            }
+
+            body
+        }
+    };
+
+    let mut response = HttpResponse::Ok();
+    response
+        .content_type(match format {
+            PublicPageFeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            PublicPageFeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        })
+        .insert_header(header::CacheControl(vec![
+            header::CacheDirective::Public,
+            header::CacheDirective::MaxAge(PUBLIC_FEED_CACHE_TTL.as_secs() as u32),
+        ]));
+    guards.insert_headers(&mut response);
+
+    Ok(response.body(body))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_public_page_cache_control_differs_by_logged_in() {
+        let logged_in = public_page_cache_control(true, false).to_string();
+        let logged_out = public_page_cache_control(false, false).to_string();
+
+        assert_eq!(logged_in, "no-store");
+        assert_eq!(logged_out, "public, max-age=300");
+        assert_ne!(logged_in, logged_out);
+        assert_eq!(
+            public_page_cache_control(false, true).to_string(),
+            "no-store"
+        );
+    }
+
+    #[test]
+    pub fn test_break_score_ties_is_deterministic() {
+        let score_chunk = |id: uuid::Uuid, score: f64| ScoreChunkDTO {
+            metadata: vec![crate::data::models::ChunkMetadataTypes::ID(
+                crate::data::models::ChunkMetadata {
+                    id,
+                    ..Default::default()
+                }
+                .into(),
+            )],
+            highlights: None,
+            score,
+        };
+        let ids = (0..4).map(|_| uuid::Uuid::new_v4()).collect::<Vec<_>>();
+        let ordered_ids = |score_chunks: &[ScoreChunkDTO]| {
+            score_chunks
+                .iter()
+                .map(|score_chunk| score_chunk.metadata[0].metadata().id)
+                .collect::<Vec<_>>()
+        };
+
+        let mut first_run = vec![
+            score_chunk(ids[0], 0.9),
+            score_chunk(ids[1], 0.5),
+            score_chunk(ids[2], 0.5),
+            score_chunk(ids[3], 0.5),
+        ];
+        let mut second_run = vec![
+            score_chunk(ids[0], 0.9),
+            score_chunk(ids[3], 0.5),
+            score_chunk(ids[1], 0.5),
+            score_chunk(ids[2], 0.5),
+        ];
+        break_score_ties(&mut first_run);
+        break_score_ties(&mut second_run);
+
+        assert_eq!(ordered_ids(&first_run), ordered_ids(&second_run));
+        assert_eq!(ordered_ids(&first_run)[0], ids[0]);
+        let mut tied_ids = ids[1..].to_vec();
+        tied_ids.sort();
+        assert_eq!(ordered_ids(&first_run)[1..], tied_ids[..]);
+
+        let mut nan_run = vec![
+            score_chunk(ids[0], f64::NAN),
+            score_chunk(ids[2], 0.5),
+            score_chunk(ids[1], 0.5),
+            score_chunk(ids[3], f64::NAN),
+        ];
+        break_score_ties(&mut nan_run);
+
+        let mut tied_ids = vec![ids[1], ids[2]];
+        tied_ids.sort();
+        assert_eq!(ordered_ids(&nan_run)[0], ids[0]);
+        assert_eq!(ordered_ids(&nan_run)[1..3], tied_ids[..]);
+        assert_eq!(ordered_ids(&nan_run)[3], ids[3]);
+    }
+
+    #[test]
+    pub fn test_shuffle_score_bands_handles_non_finite_scores() {
+        let score_chunk = |score: f64| ScoreChunkDTO {
+            metadata: vec![crate::data::models::ChunkMetadataTypes::ID(
+                crate::data::models::ChunkMetadata::default().into(),
+            )],
+            highlights: None,
+            score,
+        };
+
+        let mut score_chunks = vec![
+            score_chunk(f64::INFINITY),
+            score_chunk(f64::NAN),
+            score_chunk(0.5),
+            score_chunk(f64::NEG_INFINITY),
+        ];
+        shuffle_score_bands(&mut score_chunks, 42, 0.1);
+
+        assert_eq!(score_chunks.len(), 4);
+        assert_eq!(score_chunks[0].score, f64::INFINITY);
+        assert!(score_chunks[1].score.is_nan());
+        assert_eq!(score_chunks[2].score, 0.5);
+        assert_eq!(score_chunks[3].score, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    pub fn test_redact_public_query() {
+        let mut config = DatasetConfiguration::default();
+
+        assert_eq!(
+            redact_public_query("email jane.doe@example.com about it", &config),
+            "email [REDACTED] about it"
+        );
+        assert_eq!(
+            redact_public_query("call +15551234567", &config),
+            "call +[REDACTED]"
+        );
+        assert_eq!(
+            redact_public_query("card 4111111111111111 declined", &config),
+            "card [REDACTED] declined"
+        );
+        assert_eq!(
+            redact_public_query("iphone 15 case", &config),
+            "iphone 15 case"
+        );
+
+        config.PUBLIC_DATASET.query_redaction_patterns =
+            Some(vec![r"\d{4}([ -]\d{4}){3}".to_string(), "(".to_string()]);
+        assert_eq!(
+            redact_public_query("card 4111 1111 1111 1111 declined", &config),
+            "card [REDACTED] declined"
+        );
+    }
+
+    #[test]
+    pub fn test_public_search_rate_limit_bursts_and_refills() {
+        let dataset_id = uuid::Uuid::new_v4();
+
+        for remaining in (0..3).rev() {
+            let rate_limit = take_public_search_rate_limit_token(dataset_id, 3);
+            assert_eq!(rate_limit.limit, 3);
+            assert_eq!(rate_limit.remaining, remaining);
+            assert!(rate_limit.retry_after_secs.is_none());
+        }
+
+        let rejected = take_public_search_rate_limit_token(dataset_id, 3);
+        assert_eq!(rejected.remaining, 0);
+        assert_eq!(rejected.retry_after_secs, Some(1));
+        assert_eq!(rejected.reset_secs, 1);
+
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        let refilled = take_public_search_rate_limit_token(dataset_id, 3);
+        assert!(refilled.retry_after_secs.is_none());
+    }
+
+    #[test]
+    pub fn test_matches_allowed_origin() {
+        assert!(matches_allowed_origin("https://example.com", "*"));
+        assert!(matches_allowed_origin(
+            "https://docs.example.com",
+            "https://*.example.com"
+        ));
+        assert!(!matches_allowed_origin(
+            "https://example.com",
+            "https://*.example.com"
+        ));
+        assert!(!matches_allowed_origin(
+            "https://evilexample.com",
+            "*.example.com"
+        ));
+
+        assert!(matches_allowed_origin(
+            "https://example.com:8443",
+            "https://example.com:8443"
+        ));
+        assert!(!matches_allowed_origin(
+            "https://example.com:8443",
+            "https://example.com"
+        ));
+
+        assert!(matches_allowed_origin(
+            "HTTPS://Example.com",
+            "https://example.com/"
+        ));
+        assert!(matches_allowed_origin("http://example.com", "example.com"));
+        assert!(!matches_allowed_origin(
+            "http://example.com",
+            "https://example.com"
+        ));
+    }
+
+    #[test]
+    pub fn test_escape_xml() {
+        assert_eq!(
+            escape_xml("Q&A <b>\"quoted\" 'text'</b>\u{0}\n"),
+            "Q&amp;A &lt;b&gt;&quot;quoted&quot; &apos;text&apos;&lt;/b&gt;\n"
+        );
+    }
+
+    #[test]
+    pub fn test_minify_html_keeps_raw_tags() {
+        let html =
+            "<p>  a \n b </p><pre>  x\n y</pre>  <script> var a  = 1;</script><pref>  c </pref>";
+
+        assert_eq!(
+            minify_html(html),
+            "<p> a b </p><pre>  x\n y</pre> <script> var a  = 1;</script><pref> c </pref>"
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_public_search_deadline_drops_the_search() {
+        struct DropFlag(std::sync::Arc<std::sync::atomic::AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+
+        let dropped = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let drop_flag = DropFlag(dropped.clone());
+        let search = async move {
+            let _drop_flag = drop_flag;
+            std::future::pending::<Result<(), ServiceError>>().await
+        };
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(10);
+        let result = run_before_public_search_deadline(Some(deadline), search).await;
+
+        assert!(matches!(result, Err(ServiceError::RequestTimeout)));
+        assert!(dropped.load(std::sync::atomic::Ordering::SeqCst));
     }
 }
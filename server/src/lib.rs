@@ -262,7 +262,17 @@ impl Modify for SecurityAddon {
         handlers::analytics_handler::get_all_events,
         handlers::analytics_handler::get_event_by_id,
         handlers::metrics_handler::get_metrics,
-        handlers::page_handler::public_page
+        handlers::page_handler::public_page,
+        handlers::page_handler::public_search,
+        handlers::page_handler::public_suggested_queries,
+        handlers::page_handler::public_correct_query,
+        handlers::page_handler::public_config_version,
+        handlers::page_handler::get_last_render_error,
+        handlers::page_handler::get_effective_public_config,
+        handlers::page_handler::set_public_page_enabled,
+        handlers::page_handler::get_public_page_configs,
+        handlers::page_handler::public_validate_options,
+        handlers::page_handler::public_feed,
     ),
     components(
         schemas(
@@ -270,6 +280,32 @@ impl Modify for SecurityAddon {
             handlers::page_handler::PublicPageParameters,
             handlers::page_handler::PublicPageTabMessage,
             handlers::page_handler::HeroPattern,
+            handlers::page_handler::PublicPageSearchReqPayload,
+            handlers::page_handler::PublicPageSearchResponseBody,
+            handlers::page_handler::PublicPageScoreExplanation,
+            handlers::page_handler::PopularSuggestedQueriesOptions,
+            handlers::page_handler::SuggestedQueriesSource,
+            handlers::page_handler::PublicPageSuggestedQueriesResponse,
+            handlers::page_handler::PublicPageCorrectQueryReqPayload,
+            handlers::page_handler::PublicPageCorrectQueryResponse,
+            handlers::page_handler::PublicPageConfigVersionResponse,
+            handlers::page_handler::PublicPageRenderError,
+            handlers::page_handler::PublicPageNeighborChunks,
+            handlers::page_handler::PublicPageResultVector,
+            handlers::page_handler::PublicPageResultGroup,
+            handlers::page_handler::PublicPageSearchTimings,
+            handlers::page_handler::PublicPageEnabledReqPayload,
+            handlers::page_handler::PublicPageEnabledResponse,
+            handlers::page_handler::PublicPageConfigsReqPayload,
+            handlers::page_handler::PublicPageConfigEntry,
+            handlers::page_handler::PublicPageConfigsResponse,
+            handlers::page_handler::PublicPageMatchedFields,
+            handlers::page_handler::PublicPageValidateOptionsReqPayload,
+            handlers::page_handler::PublicPageSearchPreview,
+            handlers::page_handler::PublicPageValidateOptionsResponse,
+            handlers::page_handler::PublicPageFeedOptions,
+            handlers::page_handler::PublicPageFeedFormat,
+            handlers::page_handler::PublicPageFeedQuery,
             handlers::auth_handler::AuthQuery,
             handlers::topic_handler::CreateTopicReqPayload,
             handlers::topic_handler::CloneTopicReqPayload,
@@ -521,6 +557,7 @@ impl Modify for SecurityAddon {
             data::models::HasIDCondition,
             data::models::DistanceMetric,
             data::models::PublicDatasetOptions,
+            data::models::PublicEmptyQueryBehavior,
             data::models::Invitation,
             errors::ErrorResponseBody,
             middleware::api_version::APIVersion,
@@ -678,6 +715,8 @@ pub fn main() -> std::io::Result<()> {
 
         BKTreeCache::enforce_cache_ttl();
 
+        handlers::page_handler::spawn_public_page_warmup(web::Data::new(pool.clone()));
+
 
         let metrics = Metrics::new().map_err(|e| {
             std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to create metrics {:?}", e))
@@ -769,12 +808,57 @@ pub fn main() -> std::io::Result<()> {
                 )
                 .service(
                     web::resource("/public_page/{dataset_id}")
+                        .wrap(Compress::default())
                         .route(web::get().to(handlers::page_handler::public_page))
                 )
                 .service(actix_files::Files::new("/static", "./static").prefer_utf8(true))
                 // everything under '/api/' route
                 .service(
                     web::scope("/api")
+                        .service(
+                            web::scope("/public_page")
+                                .service(
+                                    web::resource("/configs")
+                                        .route(web::post().to(handlers::page_handler::get_public_page_configs))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/search")
+                                        .wrap(Compress::default())
+                                        .route(web::post().to(handlers::page_handler::public_search))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/suggested_queries")
+                                        .route(web::get().to(handlers::page_handler::public_suggested_queries))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/last_render_error")
+                                        .route(web::get().to(handlers::page_handler::get_last_render_error))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/effective_config")
+                                        .route(web::get().to(handlers::page_handler::get_effective_public_config))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/enabled")
+                                        .route(web::post().to(handlers::page_handler::set_public_page_enabled))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/version")
+                                        .route(web::get().to(handlers::page_handler::public_config_version))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/correct_query")
+                                        .route(web::post().to(handlers::page_handler::public_correct_query))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/validate_options")
+                                        .route(web::post().to(handlers::page_handler::public_validate_options))
+                                )
+                                .service(
+                                    web::resource("/{dataset_id}/feed")
+                                        .route(web::get().to(handlers::page_handler::public_feed))
+                                )
+                        )
                         .service(
                             web::scope("/chunks")
                                 .service(
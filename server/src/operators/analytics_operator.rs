@@ -1,15 +1,15 @@
 use crate::{
     data::models::{
-        ClusterAnalyticsFilter, ClusterTopicsClickhouse, DatasetAnalytics, EventAnalyticsFilter,
-        EventData, EventDataClickhouse, GetEventsResponseBody, Granularity, HeadQueries, Pool,
-        PopularFilters, PopularFiltersClickhouse, RAGAnalyticsFilter, RAGSortBy,
-        RAGUsageGraphResponse, RAGUsageResponse, RagQueryEvent, RagQueryEventClickhouse,
-        RecommendationAnalyticsFilter, RecommendationCTRMetrics, RecommendationEvent,
-        RecommendationEventClickhouse, RecommendationsWithClicksCTRResponse,
-        RecommendationsWithClicksCTRResponseClickhouse, RecommendationsWithoutClicksCTRResponse,
-        RecommendationsWithoutClicksCTRResponseClickhouse, SearchAnalyticsFilter, SearchCTRMetrics,
-        SearchCTRMetricsClickhouse, SearchClusterTopics, SearchLatencyGraph,
-        SearchLatencyGraphClickhouse, SearchQueriesWithClicksCTRResponse,
+        ChunkClickThroughClickhouse, ClusterAnalyticsFilter, ClusterTopicsClickhouse,
+        DatasetAnalytics, EventAnalyticsFilter, EventData, EventDataClickhouse,
+        GetEventsResponseBody, Granularity, HeadQueries, Pool, PopularFilters,
+        PopularFiltersClickhouse, RAGAnalyticsFilter, RAGSortBy, RAGUsageGraphResponse,
+        RAGUsageResponse, RagQueryEvent, RagQueryEventClickhouse, RecommendationAnalyticsFilter,
+        RecommendationCTRMetrics, RecommendationEvent, RecommendationEventClickhouse,
+        RecommendationsWithClicksCTRResponse, RecommendationsWithClicksCTRResponseClickhouse,
+        RecommendationsWithoutClicksCTRResponse, RecommendationsWithoutClicksCTRResponseClickhouse,
+        SearchAnalyticsFilter, SearchCTRMetrics, SearchCTRMetricsClickhouse, SearchClusterTopics,
+        SearchLatencyGraph, SearchLatencyGraphClickhouse, SearchQueriesWithClicksCTRResponse,
         SearchQueriesWithClicksCTRResponseClickhouse, SearchQueriesWithoutClicksCTRResponse,
         SearchQueriesWithoutClicksCTRResponseClickhouse, SearchQueryEvent,
         SearchQueryEventClickhouse, SearchQueryRating, SearchSortBy, SearchTypeCount, SortOrder,
@@ -20,13 +20,48 @@ use crate::{
     handlers::analytics_handler::{GetTopDatasetsRequestBody, RateQueryRequest},
 };
 use actix_web::web;
+use dashmap::{DashMap, DashSet};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use futures::future::join_all;
 use itertools::Itertools;
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use utoipa::ToSchema;
 
+/// How long the popular queries for a dataset are cached before being recomputed.
+const POPULAR_QUERIES_CACHE_TTL: Duration = Duration::from_secs(600);
+
+struct PopularQueriesCacheEntry {
+    queries: Vec<String>,
+    expiration: Instant,
+}
+
+/// How long the click-through rates of a dataset's chunks are cached before being recomputed.
+const CLICK_THROUGH_RATES_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Searches added to the number of searches every chunk was returned in, so a chunk shown a
+/// handful of times can't reach a high click-through rate from a single click.
+const CLICK_THROUGH_RATE_PRIOR_IMPRESSIONS: f64 = 10.0;
+
+/// How long loading the click-through rates of a dataset may take before it is abandoned.
+const CLICK_THROUGH_RATES_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct ClickThroughRatesCacheEntry {
+    rates: HashMap<uuid::Uuid, f64>,
+    expiration: Instant,
+}
+
+lazy_static! {
+    static ref POPULAR_QUERIES_CACHE: DashMap<(uuid::Uuid, u32, u64), PopularQueriesCacheEntry> =
+        DashMap::new();
+    static ref CLICK_THROUGH_RATES_CACHE: DashMap<(uuid::Uuid, u32), ClickThroughRatesCacheEntry> =
+        DashMap::new();
+    static ref CLICK_THROUGH_RATES_LOADS: DashSet<(uuid::Uuid, u32)> = DashSet::new();
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[schema(title = "SearchClusterResponse")]
 pub struct SearchClusterResponse {
@@ -257,6 +292,195 @@ pub async fn get_head_queries_query(
     })
 }
 
+/// Returns the dataset's most frequent public page search queries over the last `window_days`
+/// days. Searches made through the API are left out, they may hold queries never meant to be
+/// shown to visitors. Results are cached per dataset for `POPULAR_QUERIES_CACHE_TTL`.
+pub async fn get_popular_queries_query(
+    dataset_id: uuid::Uuid,
+    window_days: u32,
+    limit: u64,
+    clickhouse_client: &clickhouse::Client,
+) -> Result<Vec<String>, ServiceError> {
+    let cache_key = (dataset_id, window_days, limit);
+
+    if let Some(entry) = POPULAR_QUERIES_CACHE.get(&cache_key) {
+        if entry.expiration > Instant::now() {
+            return Ok(entry.queries.clone());
+        }
+    }
+
+    let popular_queries = clickhouse_client
+        .query(
+            "SELECT 
+                query, 
+                count(*) AS count
+            FROM 
+                search_queries
+            WHERE dataset_id = ?
+                AND search_queries.is_duplicate = 0
+                AND JSONExtractBool(request_params, 'public_page') = 1
+                AND query != ''
+                AND created_at >= now() - INTERVAL ? DAY
+            GROUP BY 
+                query
+            ORDER BY 
+                count DESC
+            LIMIT ?",
+        )
+        .bind(dataset_id)
+        .bind(window_days)
+        .bind(limit)
+        .fetch_all::<HeadQueries>()
+        .await
+        .map_err(|e| {
+            log::error!("Error fetching query: {:?}", e);
+            ServiceError::InternalServerError("Error fetching query".to_string())
+        })?
+        .into_iter()
+        .map(|head_query| head_query.query)
+        .collect::<Vec<String>>();
+
+    POPULAR_QUERIES_CACHE.insert(
+        cache_key,
+        PopularQueriesCacheEntry {
+            queries: popular_queries.clone(),
+            expiration: Instant::now() + POPULAR_QUERIES_CACHE_TTL,
+        },
+    );
+
+    Ok(popular_queries)
+}
+
+/// Click-through rates of the chunks of a dataset clicked in the last `window_days`, keyed by
+/// chunk id and divided by the highest rate so they range from 0 to 1. A chunk's rate is its
+/// clicks divided by the searches it was returned in plus `CLICK_THROUGH_RATE_PRIOR_IMPRESSIONS`.
+/// Rates are cached per dataset for `CLICK_THROUGH_RATES_CACHE_TTL` and never loaded on the
+/// caller's request: a missing or expired entry starts one background load per dataset, and until
+/// it finishes the expired rates are returned, or `None` when there are none yet.
+pub fn get_chunk_click_through_rates_query(
+    dataset_id: uuid::Uuid,
+    window_days: u32,
+    clickhouse_client: &clickhouse::Client,
+) -> Option<HashMap<uuid::Uuid, f64>> {
+    let cache_key = (dataset_id, window_days);
+
+    let cached = CLICK_THROUGH_RATES_CACHE
+        .get(&cache_key)
+        .map(|entry| (entry.rates.clone(), entry.expiration > Instant::now()));
+    if let Some((rates, true)) = cached {
+        return Some(rates);
+    }
+
+    if CLICK_THROUGH_RATES_LOADS.insert(cache_key) {
+        let clickhouse_client = clickhouse_client.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(
+                CLICK_THROUGH_RATES_QUERY_TIMEOUT,
+                load_chunk_click_through_rates(dataset_id, window_days, &clickhouse_client),
+            )
+            .await
+            {
+                Ok(Ok(rates)) => {
+                    CLICK_THROUGH_RATES_CACHE.insert(
+                        cache_key,
+                        ClickThroughRatesCacheEntry {
+                            rates,
+                            expiration: Instant::now() + CLICK_THROUGH_RATES_CACHE_TTL,
+                        },
+                    );
+                }
+                Ok(Err(err)) => {
+                    log::error!(
+                        "Failed to load click-through rates of dataset {}: {:?}",
+                        dataset_id,
+                        err
+                    );
+                }
+                Err(_) => {
+                    log::error!(
+                        "Loading click-through rates of dataset {} timed out",
+                        dataset_id
+                    );
+                }
+            }
+
+            CLICK_THROUGH_RATES_LOADS.remove(&cache_key);
+        });
+    }
+
+    cached.map(|(rates, _)| rates)
+}
+
+async fn load_chunk_click_through_rates(
+    dataset_id: uuid::Uuid,
+    window_days: u32,
+    clickhouse_client: &clickhouse::Client,
+) -> Result<HashMap<uuid::Uuid, f64>, ServiceError> {
+    let click_throughs = clickhouse_client
+        .query(
+            "SELECT
+                clicks.chunk_id AS chunk_id,
+                clicks.clicks AS clicks,
+                impressions.impressions AS impressions
+            FROM (
+                SELECT
+                    JSONExtractString(metadata, 'chunk_id') AS chunk_id,
+                    count(*) AS clicks
+                FROM events
+                WHERE dataset_id = ?
+                    AND event_type = 'click'
+                    AND created_at >= now() - INTERVAL ? DAY
+                GROUP BY chunk_id
+            ) AS clicks
+            LEFT JOIN (
+                SELECT
+                    JSONExtractString(result, 'metadata', 1, 'id') AS chunk_id,
+                    count(*) AS impressions
+                FROM search_queries
+                ARRAY JOIN results AS result
+                WHERE dataset_id = ?
+                    AND is_duplicate = 0
+                    AND created_at >= now() - INTERVAL ? DAY
+                GROUP BY chunk_id
+            ) AS impressions ON clicks.chunk_id = impressions.chunk_id
+            WHERE clicks.chunk_id != ''
+            ORDER BY clicks DESC
+            LIMIT 10000",
+        )
+        .bind(dataset_id)
+        .bind(window_days)
+        .bind(dataset_id)
+        .bind(window_days)
+        .fetch_all::<ChunkClickThroughClickhouse>()
+        .await
+        .map_err(|e| {
+            log::error!("Error fetching query: {:?}", e);
+            ServiceError::InternalServerError("Error fetching query".to_string())
+        })?;
+
+    // Clicks are sent by clients, so a chunk can have more clicks than recorded impressions
+    let rates = click_throughs
+        .into_iter()
+        .filter_map(|click_through| {
+            let chunk_id = uuid::Uuid::parse_str(&click_through.chunk_id).ok()?;
+            let impressions = click_through.impressions.max(click_through.clicks) as f64;
+
+            Some((
+                chunk_id,
+                click_through.clicks as f64 / (impressions + CLICK_THROUGH_RATE_PRIOR_IMPRESSIONS),
+            ))
+        })
+        .collect::<HashMap<uuid::Uuid, f64>>();
+
+    let max_rate = rates.values().copied().fold(0.0, f64::max);
+    let rates = rates
+        .into_iter()
+        .map(|(chunk_id, rate)| (chunk_id, if max_rate > 0.0 { rate / max_rate } else { 0.0 }))
+        .collect::<HashMap<uuid::Uuid, f64>>();
+
+    Ok(rates)
+}
+
 pub async fn get_low_confidence_queries_query(
     dataset_id: uuid::Uuid,
     filter: Option<SearchAnalyticsFilter>,
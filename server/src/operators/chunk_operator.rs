@@ -2615,6 +2615,220 @@ pub async fn get_pg_point_ids_from_qdrant_point_ids(
     Ok(chunk_ids)
 }
 
+/// Returns up to `limit` `(id, qdrant_point_id)` pairs of the dataset's chunks created at or
+/// before `created_before`, ordered by id and starting after `after_id`, for offline sweeps.
+pub async fn scroll_chunk_point_ids_query(
+    dataset_id: uuid::Uuid,
+    created_before: NaiveDateTime,
+    after_id: Option<uuid::Uuid>,
+    limit: i64,
+    pool: web::Data<Pool>,
+) -> Result<Vec<(uuid::Uuid, uuid::Uuid)>, ServiceError> {
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    chunk_metadata_columns::chunk_metadata
+        .filter(chunk_metadata_columns::dataset_id.eq(dataset_id))
+        .filter(chunk_metadata_columns::created_at.le(created_before))
+        .filter(chunk_metadata_columns::id.gt(after_id.unwrap_or(uuid::Uuid::nil())))
+        .select((
+            chunk_metadata_columns::id,
+            chunk_metadata_columns::qdrant_point_id,
+        ))
+        .order_by(chunk_metadata_columns::id)
+        .limit(limit)
+        .load(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Failed to scroll chunk point ids".to_string()))
+}
+
+/// Deletes chunk rows of the dataset whose Qdrant points are gone, along with their bookmarks,
+/// in one transaction. Only rows still mapping to one of `qdrant_point_ids` and created at or
+/// before `created_before` are deleted. Nothing is deleted from Qdrant. Returns the number of
+/// deleted chunk rows.
+pub async fn delete_chunks_without_qdrant_points_query(
+    chunk_ids: Vec<uuid::Uuid>,
+    qdrant_point_ids: Vec<uuid::Uuid>,
+    created_before: NaiveDateTime,
+    dataset_id: uuid::Uuid,
+    pool: web::Data<Pool>,
+) -> Result<usize, ServiceError> {
+    use crate::data::schema::chunk_group_bookmarks::dsl as chunk_group_bookmarks_columns;
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            let deleted_ids = diesel::delete(
+                chunk_metadata_columns::chunk_metadata
+                    .filter(chunk_metadata_columns::id.eq_any(chunk_ids))
+                    .filter(chunk_metadata_columns::qdrant_point_id.eq_any(qdrant_point_ids))
+                    .filter(chunk_metadata_columns::dataset_id.eq(dataset_id))
+                    .filter(chunk_metadata_columns::created_at.le(created_before)),
+            )
+            .returning(chunk_metadata_columns::id)
+            .get_results::<uuid::Uuid>(conn)
+            .await?;
+
+            diesel::delete(chunk_group_bookmarks_columns::chunk_group_bookmarks.filter(
+                chunk_group_bookmarks_columns::chunk_metadata_id.eq_any(deleted_ids.clone()),
+            ))
+            .execute(conn)
+            .await?;
+
+            Ok(deleted_ids.len())
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|err| {
+        log::error!("Failed to delete chunks without qdrant points: {:?}", err);
+        ServiceError::BadRequest("Failed to delete chunks without qdrant points".to_string())
+    })
+}
+
+/// Finds qdrant_point_ids which more than one chunk row maps to, with the ids of those chunks.
+/// Scans all of chunk_metadata, so it is only meant for offline consistency checks.
+pub async fn get_duplicate_qdrant_point_ids_query(
+    pool: web::Data<Pool>,
+) -> Result<Vec<(uuid::Uuid, Vec<uuid::Uuid>)>, ServiceError> {
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    let duplicate_point_ids: Vec<uuid::Uuid> = chunk_metadata_columns::chunk_metadata
+        .group_by(chunk_metadata_columns::qdrant_point_id)
+        .having(diesel::dsl::count(chunk_metadata_columns::id).gt(1))
+        .select(chunk_metadata_columns::qdrant_point_id)
+        .load(&mut conn)
+        .await
+        .map_err(|_| {
+            ServiceError::BadRequest("Failed to get duplicate qdrant point ids".to_string())
+        })?;
+
+    if duplicate_point_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let duplicate_rows: Vec<(uuid::Uuid, uuid::Uuid)> = chunk_metadata_columns::chunk_metadata
+        .filter(chunk_metadata_columns::qdrant_point_id.eq_any(duplicate_point_ids))
+        .select((
+            chunk_metadata_columns::qdrant_point_id,
+            chunk_metadata_columns::id,
+        ))
+        .order_by((
+            chunk_metadata_columns::qdrant_point_id,
+            chunk_metadata_columns::id,
+        ))
+        .load(&mut conn)
+        .await
+        .map_err(|_| {
+            ServiceError::BadRequest("Failed to get chunks of duplicate point ids".to_string())
+        })?;
+
+    Ok(duplicate_rows
+        .into_iter()
+        .chunk_by(|(qdrant_point_id, _)| *qdrant_point_id)
+        .into_iter()
+        .map(|(qdrant_point_id, rows)| {
+            (
+                qdrant_point_id,
+                rows.map(|(_, chunk_id)| chunk_id).collect(),
+            )
+        })
+        .collect())
+}
+
+/// Counts the chunk rows left behind in soft deleted datasets, by dataset. Their points are
+/// expected to be gone from Qdrant, so they would otherwise be mistaken for live mappings.
+pub async fn get_chunk_counts_in_deleted_datasets_query(
+    pool: web::Data<Pool>,
+) -> Result<Vec<(uuid::Uuid, i64)>, ServiceError> {
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+    use crate::data::schema::datasets::dsl as datasets_columns;
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    chunk_metadata_columns::chunk_metadata
+        .inner_join(datasets_columns::datasets)
+        .filter(datasets_columns::deleted.eq(1))
+        .group_by(chunk_metadata_columns::dataset_id)
+        .select((
+            chunk_metadata_columns::dataset_id,
+            diesel::dsl::count(chunk_metadata_columns::id),
+        ))
+        .load(&mut conn)
+        .await
+        .map_err(|_| {
+            ServiceError::BadRequest("Failed to get chunks of deleted datasets".to_string())
+        })
+}
+
+/// Returns whether any chunk in the dataset has `key` as a top level key of its metadata.
+pub async fn dataset_has_metadata_key_query(
+    dataset_id: uuid::Uuid,
+    key: String,
+    pool: web::Data<Pool>,
+) -> Result<bool, ServiceError> {
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    diesel::select(diesel::dsl::exists(
+        chunk_metadata_columns::chunk_metadata
+            .filter(chunk_metadata_columns::dataset_id.eq(dataset_id))
+            .filter(
+                diesel::dsl::sql::<diesel::sql_types::Bool>("metadata ? ")
+                    .bind::<diesel::sql_types::Text, _>(key),
+            ),
+    ))
+    .get_result::<bool>(&mut conn)
+    .await
+    .map_err(|err| {
+        log::error!("Failed to check chunk metadata keys: {:?}", err);
+        ServiceError::BadRequest("Failed to check chunk metadata keys".to_string())
+    })
+}
+
+/// Runs the query behind `get_pg_point_ids_from_qdrant_point_ids` against a single row so a
+/// missing or retyped `chunk_metadata` column fails loudly instead of matching no rows.
+pub async fn check_pg_point_ids_query_schema(pool: web::Data<Pool>) -> Result<(), ServiceError> {
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    chunk_metadata_columns::chunk_metadata
+        .select((
+            chunk_metadata_columns::qdrant_point_id,
+            chunk_metadata_columns::dataset_id,
+        ))
+        .limit(1)
+        .load::<(uuid::Uuid, uuid::Uuid)>(&mut conn)
+        .await
+        .map_err(|err| {
+            ServiceError::InternalServerError(format!(
+                "chunk_metadata does not match the expected schema: {}",
+                err
+            ))
+        })?;
+
+    Ok(())
+}
+
 pub async fn get_chunk_html_from_ids_query(
     chunk_ids: Vec<uuid::Uuid>,
     pool: web::Data<Pool>,
@@ -23,6 +23,7 @@ use diesel::upsert::excluded;
 use diesel_async::RunQueryDsl;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use time::{format_description, OffsetDateTime};
 
 use super::clickhouse_operator::EventQueue;
@@ -135,6 +136,27 @@ pub async fn get_dataset_by_id_query(
     Ok(dataset)
 }
 
+pub async fn get_datasets_by_ids_query(
+    ids: Vec<uuid::Uuid>,
+    pool: web::Data<Pool>,
+) -> Result<Vec<Dataset>, ServiceError> {
+    use crate::data::schema::datasets::dsl as datasets_columns;
+    let mut conn = pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::BadRequest("Could not get database connection".to_string()))?;
+
+    let datasets = datasets_columns::datasets
+        .filter(datasets_columns::id.eq_any(ids))
+        .filter(datasets_columns::deleted.eq(0))
+        .select(Dataset::as_select())
+        .load::<Dataset>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Could not load datasets".to_string()))?;
+
+    Ok(datasets)
+}
+
 pub async fn get_deleted_dataset_by_unifiedid_query(
     id: UnifiedId,
     pool: web::Data<Pool>,
@@ -801,6 +823,80 @@ pub async fn get_tags_in_dataset_query(
     Ok((items, total_count))
 }
 
+/// Counts the tags of the given chunks from the tag index, most common first.
+pub async fn get_tag_facets_for_chunks_query(
+    dataset_id: uuid::Uuid,
+    chunk_ids: Vec<uuid::Uuid>,
+    limit: i64,
+    pool: web::Data<Pool>,
+) -> Result<Vec<TagsWithCount>, ServiceError> {
+    use crate::data::schema::chunk_metadata_tags::dsl as chunk_metadata_tags_columns;
+    use crate::data::schema::dataset_tags::dsl as dataset_tags_columns;
+
+    if chunk_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    let tag_facets = dataset_tags_columns::dataset_tags
+        .inner_join(chunk_metadata_tags_columns::chunk_metadata_tags)
+        .filter(dataset_tags_columns::dataset_id.eq(dataset_id))
+        .filter(chunk_metadata_tags_columns::chunk_metadata_id.eq_any(chunk_ids))
+        .group_by(dataset_tags_columns::tag)
+        .select((
+            dataset_tags_columns::tag,
+            count(chunk_metadata_tags_columns::chunk_metadata_id),
+        ))
+        .order_by((
+            count(chunk_metadata_tags_columns::chunk_metadata_id).desc(),
+            dataset_tags_columns::tag,
+        ))
+        .limit(limit)
+        .load(&mut conn)
+        .await
+        .map_err(|err| ServiceError::BadRequest(format!("Failed to get tag facets {}", err)))?;
+
+    Ok(tag_facets)
+}
+
+/// Sums the chunk usage counts of all datasets which are not soft deleted by the Qdrant collection
+/// their configuration stores points in.
+pub async fn get_chunk_counts_by_qdrant_collection_query(
+    pool: web::Data<Pool>,
+) -> Result<HashMap<String, i64>, ServiceError> {
+    use crate::data::schema::dataset_usage_counts::dsl as dataset_usage_counts_columns;
+    use crate::data::schema::datasets::dsl as datasets_columns;
+
+    let mut conn = pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::BadRequest("Could not get database connection".to_string()))?;
+
+    let dataset_chunk_counts = datasets_columns::datasets
+        .inner_join(dataset_usage_counts_columns::dataset_usage_counts)
+        .filter(datasets_columns::deleted.eq(0))
+        .select((
+            datasets_columns::server_configuration,
+            dataset_usage_counts_columns::chunk_count,
+        ))
+        .load::<(serde_json::Value, i32)>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::NotFound("Failed to get dataset chunk counts".to_string()))?;
+
+    let mut chunk_counts = HashMap::new();
+    for (server_configuration, chunk_count) in dataset_chunk_counts {
+        let collection = get_qdrant_collection_from_dataset_config(
+            &DatasetConfiguration::from_json(server_configuration),
+        );
+        *chunk_counts.entry(collection).or_insert(0) += chunk_count as i64;
+    }
+
+    Ok(chunk_counts)
+}
+
 pub async fn scroll_dataset_ids_query(
     offset: uuid::Uuid,
     limit: i64,
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::data::models::{ChunkMetadataTags, DatasetTags};
 use crate::errors::ServiceError;
@@ -13,13 +13,16 @@ use crate::{
         DatasetConfiguration, FileGroup, Pool, RedisPool, UnifiedId,
     },
     handlers::group_handler::GroupsBookmarkQueryResult,
-    operators::chunk_operator::{delete_chunk_metadata_query, get_chunk_metadatas_from_point_ids},
+    operators::chunk_operator::{
+        delete_chunk_metadata_query, get_chunk_metadatas_from_point_ids, get_stop_words,
+    },
 };
 use actix_web::web;
 use diesel::prelude::*;
 use diesel::upsert::excluded;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, RunQueryDsl};
+use itertools::Itertools;
 use qdrant_client::qdrant;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -642,6 +645,213 @@ pub async fn get_groups_for_bookmark_query(
     Ok(bookmark_groups)
 }
 
+pub struct ChunkNeighborIds {
+    pub chunk_id: uuid::Uuid,
+    pub group_id: uuid::Uuid,
+    pub before: Vec<uuid::Uuid>,
+    pub after: Vec<uuid::Uuid>,
+}
+
+#[derive(QueryableByName)]
+struct ChunkNeighborRow {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    chunk_id: uuid::Uuid,
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    group_id: uuid::Uuid,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Uuid>)]
+    neighbor_id: Option<uuid::Uuid>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Bool>)]
+    is_after: Option<bool>,
+}
+
+/// Finds up to `neighbor_count` chunks on each side of every chunk within its first group. The
+/// position of a chunk in a group is the order the chunks were created in, which is the order a
+/// file's chunks were split in. Chunks outside of any group have no neighbors. Results are in the
+/// order of `chunk_ids`.
+pub async fn get_neighbor_chunk_ids_in_groups_query(
+    chunk_ids: Vec<uuid::Uuid>,
+    neighbor_count: i64,
+    dataset_uuid: uuid::Uuid,
+    pool: web::Data<Pool>,
+) -> Result<Vec<ChunkNeighborIds>, ServiceError> {
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    // One round trip for every chunk: each chunk's first group, then its neighbors on both sides
+    // of it in that group through a lateral join which can walk the group's rows in order
+    let neighbor_rows = diesel::sql_query(
+        "WITH targets AS (
+            SELECT DISTINCT ON (chunk_metadata.id)
+                chunk_metadata.id AS chunk_id,
+                chunk_group_bookmarks.group_id,
+                chunk_metadata.created_at
+            FROM chunk_group_bookmarks
+            INNER JOIN chunk_metadata ON chunk_metadata.id = chunk_group_bookmarks.chunk_metadata_id
+            WHERE chunk_metadata.dataset_id = $1 AND chunk_metadata.id = ANY($2)
+            ORDER BY chunk_metadata.id, chunk_group_bookmarks.created_at
+        )
+        SELECT targets.chunk_id, targets.group_id, neighbors.id AS neighbor_id, neighbors.is_after
+        FROM targets
+        LEFT JOIN LATERAL (
+            (
+                SELECT chunk_metadata.id, chunk_metadata.created_at, false AS is_after
+                FROM chunk_group_bookmarks
+                INNER JOIN chunk_metadata ON chunk_metadata.id = chunk_group_bookmarks.chunk_metadata_id
+                WHERE chunk_group_bookmarks.group_id = targets.group_id
+                    AND chunk_metadata.dataset_id = $1
+                    AND (chunk_metadata.created_at, chunk_metadata.id) < (targets.created_at, targets.chunk_id)
+                ORDER BY chunk_metadata.created_at DESC, chunk_metadata.id DESC
+                LIMIT $3
+            )
+            UNION ALL
+            (
+                SELECT chunk_metadata.id, chunk_metadata.created_at, true AS is_after
+                FROM chunk_group_bookmarks
+                INNER JOIN chunk_metadata ON chunk_metadata.id = chunk_group_bookmarks.chunk_metadata_id
+                WHERE chunk_group_bookmarks.group_id = targets.group_id
+                    AND chunk_metadata.dataset_id = $1
+                    AND (chunk_metadata.created_at, chunk_metadata.id) > (targets.created_at, targets.chunk_id)
+                ORDER BY chunk_metadata.created_at, chunk_metadata.id
+                LIMIT $3
+            )
+        ) neighbors ON true
+        ORDER BY targets.chunk_id, neighbors.created_at, neighbors.id",
+    )
+    .bind::<diesel::sql_types::Uuid, _>(dataset_uuid)
+    .bind::<diesel::sql_types::Array<diesel::sql_types::Uuid>, _>(chunk_ids.clone())
+    .bind::<diesel::sql_types::BigInt, _>(neighbor_count)
+    .load::<ChunkNeighborRow>(&mut conn)
+    .await
+    .map_err(|_err| ServiceError::BadRequest("Error getting neighbors of chunks".to_string()))?;
+
+    let mut chunk_neighbors_by_id: HashMap<uuid::Uuid, ChunkNeighborIds> = HashMap::new();
+    for row in neighbor_rows {
+        let chunk_neighbors = chunk_neighbors_by_id
+            .entry(row.chunk_id)
+            .or_insert_with(|| ChunkNeighborIds {
+                chunk_id: row.chunk_id,
+                group_id: row.group_id,
+                before: vec![],
+                after: vec![],
+            });
+
+        match (row.neighbor_id, row.is_after) {
+            (Some(neighbor_id), Some(true)) => chunk_neighbors.after.push(neighbor_id),
+            (Some(neighbor_id), Some(false)) => chunk_neighbors.before.push(neighbor_id),
+            _ => {}
+        }
+    }
+
+    let chunk_neighbors = chunk_ids
+        .iter()
+        .filter_map(|chunk_id| chunk_neighbors_by_id.remove(chunk_id))
+        .collect::<Vec<ChunkNeighborIds>>();
+
+    Ok(chunk_neighbors)
+}
+
+/// Group whose name contains a query term, with the first chunk of the group in creation order.
+pub struct GroupTitleMatch {
+    pub group_id: uuid::Uuid,
+    pub name: String,
+    pub first_chunk_id: uuid::Uuid,
+}
+
+/// Most query terms matched against group names by `get_groups_matching_title_query`.
+const MAX_TITLE_MATCH_TERMS: usize = 10;
+
+/// Finds the groups of the dataset whose name contains any of `terms`, case-insensitively, up to
+/// `limit` groups per matched term in total, in a single `ILIKE ANY` query. Stop words and terms
+/// shorter than 3 characters are not matched and at most `MAX_TITLE_MATCH_TERMS` terms are used.
+/// Groups without chunks are skipped.
+pub async fn get_groups_matching_title_query(
+    terms: &[String],
+    limit: i64,
+    dataset_uuid: uuid::Uuid,
+    pool: web::Data<Pool>,
+) -> Result<Vec<GroupTitleMatch>, ServiceError> {
+    use crate::data::schema::chunk_group::dsl as chunk_group_columns;
+    use crate::data::schema::chunk_group_bookmarks::dsl as chunk_group_bookmarks_columns;
+    use crate::data::schema::chunk_metadata::dsl as chunk_metadata_columns;
+
+    let stop_words = get_stop_words();
+    let patterns = terms
+        .iter()
+        .filter(|term| term.chars().count() >= 3 && !stop_words.contains(*term))
+        .unique()
+        .take(MAX_TITLE_MATCH_TERMS)
+        .map(|term| {
+            format!(
+                "%{}%",
+                term.replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            )
+        })
+        .collect::<Vec<String>>();
+    if patterns.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut conn = pool.get().await.map_err(|_e| {
+        ServiceError::InternalServerError("Failed to get postgres connection".to_string())
+    })?;
+
+    let groups = chunk_group_columns::chunk_group
+        .filter(chunk_group_columns::dataset_id.eq(dataset_uuid))
+        .filter(
+            diesel::dsl::sql::<diesel::sql_types::Bool>("chunk_group.name ILIKE ANY(")
+                .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(patterns.clone())
+                .sql(")"),
+        )
+        .select((chunk_group_columns::id, chunk_group_columns::name))
+        .limit(limit * patterns.len() as i64)
+        .load::<(uuid::Uuid, String)>(&mut conn)
+        .await
+        .map_err(|_err| {
+            ServiceError::BadRequest("Error getting groups matching title".to_string())
+        })?;
+
+    let group_ids = groups
+        .iter()
+        .map(|(group_id, _)| *group_id)
+        .collect::<Vec<uuid::Uuid>>();
+
+    let first_chunk_ids = chunk_group_bookmarks_columns::chunk_group_bookmarks
+        .inner_join(chunk_metadata_columns::chunk_metadata)
+        .filter(chunk_group_bookmarks_columns::group_id.eq_any(&group_ids))
+        .filter(chunk_metadata_columns::dataset_id.eq(dataset_uuid))
+        .order_by((
+            chunk_group_bookmarks_columns::group_id,
+            chunk_metadata_columns::created_at,
+            chunk_metadata_columns::id,
+        ))
+        .distinct_on(chunk_group_bookmarks_columns::group_id)
+        .select((
+            chunk_group_bookmarks_columns::group_id,
+            chunk_metadata_columns::id,
+        ))
+        .load::<(uuid::Uuid, uuid::Uuid)>(&mut conn)
+        .await
+        .map_err(|_err| {
+            ServiceError::BadRequest("Error getting first chunks of groups".to_string())
+        })?
+        .into_iter()
+        .collect::<std::collections::HashMap<uuid::Uuid, uuid::Uuid>>();
+
+    Ok(groups
+        .into_iter()
+        .filter_map(|(group_id, name)| {
+            Some(GroupTitleMatch {
+                group_id,
+                first_chunk_id: *first_chunk_ids.get(&group_id)?,
+                name,
+            })
+        })
+        .collect())
+}
+
 pub async fn delete_chunk_from_group_query(
     chunk_id: uuid::Uuid,
     group_id: uuid::Uuid,
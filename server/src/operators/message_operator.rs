@@ -358,6 +358,10 @@ pub async fn get_rag_chunks_query(
             query: query.clone(),
             quote_words: None,
             negated_words: None,
+            dense_prefix: None,
+            dense_suffix: None,
+            sparse_synonyms: None,
+            cache_dense_embedding: false,
         };
 
         let mut search_timer = Timer::new();
@@ -466,6 +470,10 @@ pub async fn get_rag_chunks_query(
             query: query.clone(),
             quote_words: None,
             negated_words: None,
+            dense_prefix: None,
+            dense_suffix: None,
+            sparse_synonyms: None,
+            cache_dense_embedding: false,
         };
         let mut search_timer = Timer::new();
 
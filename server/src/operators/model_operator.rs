@@ -5,10 +5,18 @@ use crate::{
     handlers::chunk_handler::{FullTextBoost, SemanticBoost},
 };
 use actix_web::web;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
 use murmur3::murmur3_32;
 use openai_dive::v1::resources::embedding::EmbeddingInput;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, io::Cursor, ops::IndexMut};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    ops::IndexMut,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use super::parse_operator::convert_html_to_text;
 
@@ -171,6 +179,95 @@ pub async fn get_dense_vector(
     .map_err(|err| ServiceError::BadRequest(format!("Thread error {:?}", err)))?
 }
 
+struct QueryEmbeddingCacheEntry {
+    vector: Arc<Vec<f32>>,
+    expiration: Instant,
+    last_used: Instant,
+}
+
+/// (embedding base url, model name, query prefix, normalized query)
+type QueryEmbeddingCacheKey = (String, String, String, String);
+
+lazy_static! {
+    static ref QUERY_EMBEDDING_CACHE: DashMap<QueryEmbeddingCacheKey, QueryEmbeddingCacheEntry> =
+        DashMap::new();
+}
+
+/// Same as `get_dense_vector` for a query without a semantic boost, but reuses the vectors of
+/// recent queries with the same normalized text and embedding model. Holds at most
+/// `QUERY_EMBEDDING_CACHE_SIZE` vectors (1000 by default, 0 disables the cache) for
+/// `QUERY_EMBEDDING_CACHE_TTL_SECS` (3600 by default), evicting the least recently used first.
+pub async fn get_cached_dense_vector(
+    message: String,
+    dataset_config: DatasetConfiguration,
+) -> Result<Vec<f32>, ServiceError> {
+    let max_entries = std::env::var("QUERY_EMBEDDING_CACHE_SIZE")
+        .ok()
+        .and_then(|size| size.parse::<usize>().ok())
+        .unwrap_or(1000);
+
+    if max_entries == 0 {
+        return get_dense_vector(message, None, "query", dataset_config).await;
+    }
+
+    let ttl = Duration::from_secs(
+        std::env::var("QUERY_EMBEDDING_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|ttl| ttl.parse::<u64>().ok())
+            .unwrap_or(3600),
+    );
+
+    let cache_key = (
+        dataset_config.EMBEDDING_BASE_URL.clone(),
+        dataset_config.EMBEDDING_MODEL_NAME.clone(),
+        dataset_config.EMBEDDING_QUERY_PREFIX.clone(),
+        message
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase(),
+    );
+
+    if let Some(mut entry) = QUERY_EMBEDDING_CACHE.get_mut(&cache_key) {
+        if Instant::now() < entry.expiration {
+            entry.last_used = Instant::now();
+            return Ok(entry.vector.as_ref().clone());
+        }
+    }
+
+    let vector = get_dense_vector(message, None, "query", dataset_config).await?;
+
+    if QUERY_EMBEDDING_CACHE.len() >= max_entries {
+        let now = Instant::now();
+        QUERY_EMBEDDING_CACHE.retain(|_, entry| now < entry.expiration);
+
+        while QUERY_EMBEDDING_CACHE.len() >= max_entries {
+            let least_recently_used = QUERY_EMBEDDING_CACHE
+                .iter()
+                .min_by_key(|entry| entry.last_used)
+                .map(|entry| entry.key().clone());
+
+            match least_recently_used {
+                Some(key) => {
+                    QUERY_EMBEDDING_CACHE.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+
+    QUERY_EMBEDDING_CACHE.insert(
+        cache_key,
+        QueryEmbeddingCacheEntry {
+            vector: Arc::new(vector.clone()),
+            expiration: Instant::now() + ttl,
+            last_used: Instant::now(),
+        },
+    );
+
+    Ok(vector)
+}
+
 pub async fn get_sparse_vector(
     message: String,
     fulltext_boost: Option<FullTextBoost>,
@@ -986,26 +1083,142 @@ pub async fn cross_encoder(
     Ok(results)
 }
 
+/// Tokenizes the chunks in the dataset's `BM25_LANGUAGE`, English when it is `None` or not
+/// supported, and returns their BM25 sparse vectors.
 pub fn get_bm25_embeddings(
     chunks_and_boost: Vec<(String, Option<FullTextBoost>)>,
     avg_len: f32,
     b: f32,
     k: f32,
+    language: Option<&str>,
 ) -> Vec<Vec<(u32, f32)>> {
-    term_frequency(tokenize_batch(chunks_and_boost), avg_len, b, k)
+    term_frequency(
+        tokenize_batch(chunks_and_boost, language),
+        avg_len,
+        b,
+        k,
+        language,
+    )
+}
+
+/// Languages written without spaces between words, which BM25 splits into character bigrams.
+const BM25_BIGRAM_LANGUAGES: [&str; 3] = ["zh", "ja", "ko"];
+
+fn get_bm25_stemmer_language(language: &str) -> Option<tantivy::tokenizer::Language> {
+    use tantivy::tokenizer::Language;
+
+    match language {
+        "ar" => Some(Language::Arabic),
+        "da" => Some(Language::Danish),
+        "de" => Some(Language::German),
+        "el" => Some(Language::Greek),
+        "en" => Some(Language::English),
+        "es" => Some(Language::Spanish),
+        "fi" => Some(Language::Finnish),
+        "fr" => Some(Language::French),
+        "hu" => Some(Language::Hungarian),
+        "it" => Some(Language::Italian),
+        "nl" => Some(Language::Dutch),
+        "no" => Some(Language::Norwegian),
+        "pt" => Some(Language::Portuguese),
+        "ro" => Some(Language::Romanian),
+        "ru" => Some(Language::Russian),
+        "sv" => Some(Language::Swedish),
+        "ta" => Some(Language::Tamil),
+        "tr" => Some(Language::Turkish),
+        _ => None,
+    }
 }
 
-fn tokenize(text: String) -> Vec<String> {
-    let mut en_stem =
+/// Lowercased primary subtag of a language code, e.g. `zh` for `zh-Hant`.
+fn normalize_bm25_language(language: &str) -> String {
+    language
+        .trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Whether BM25 has a tokenizer for the language, otherwise it falls back to English.
+pub fn is_supported_bm25_language(language: &str) -> bool {
+    let language = normalize_bm25_language(language);
+
+    BM25_BIGRAM_LANGUAGES.contains(&language.as_str())
+        || get_bm25_stemmer_language(&language).is_some()
+}
+
+fn is_cjk_char(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{3130}'..='\u{318F}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+    )
+}
+
+/// Splits runs of CJK characters in the token into overlapping character pairs, e.g. `東京都`
+/// into `東京` and `京都`, keeping other runs whole. A lone CJK character is kept as is.
+fn push_cjk_bigrams(token: &str, tokens: &mut Vec<String>) {
+    let chars = token.chars().collect::<Vec<char>>();
+    let mut start = 0;
+    while start < chars.len() {
+        let is_cjk = is_cjk_char(chars[start]);
+        let end = start
+            + chars[start..]
+                .iter()
+                .take_while(|c| is_cjk_char(**c) == is_cjk)
+                .count();
+        let run = &chars[start..end];
+
+        if !is_cjk {
+            let run = run.iter().collect::<String>();
+            if run.len() <= 40 {
+                tokens.push(run);
+            }
+        } else if run.len() == 1 {
+            tokens.push(run[0].to_string());
+        } else {
+            tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>()));
+        }
+
+        start = end;
+    }
+}
+
+fn tokenize(text: String, language: Option<&str>) -> Vec<String> {
+    let language = normalize_bm25_language(language.unwrap_or("en"));
+
+    if BM25_BIGRAM_LANGUAGES.contains(&language.as_str()) {
+        let mut analyzer = tantivy::tokenizer::TextAnalyzer::builder(
+            tantivy::tokenizer::SimpleTokenizer::default(),
+        )
+        .filter(tantivy::tokenizer::LowerCaser)
+        .build();
+
+        let mut stream = analyzer.token_stream(&text);
+        let mut tokens: Vec<String> = vec![];
+        while stream.advance() {
+            push_cjk_bigrams(&stream.token().text, &mut tokens);
+        }
+
+        return tokens;
+    }
+
+    let mut stem =
         tantivy::tokenizer::TextAnalyzer::builder(tantivy::tokenizer::SimpleTokenizer::default())
             .filter(tantivy::tokenizer::RemoveLongFilter::limit(40))
             .filter(tantivy::tokenizer::LowerCaser)
             .filter(tantivy::tokenizer::Stemmer::new(
-                tantivy::tokenizer::Language::English,
+                get_bm25_stemmer_language(&language)
+                    .unwrap_or(tantivy::tokenizer::Language::English),
             ))
             .build();
 
-    let mut stream = en_stem.token_stream(&text);
+    let mut stream = stem.token_stream(&text);
     let mut tokens: Vec<String> = vec![];
     while stream.advance() {
         tokens.push(stream.token().text.clone());
@@ -1016,10 +1229,11 @@ fn tokenize(text: String) -> Vec<String> {
 
 pub fn tokenize_batch(
     chunks: Vec<(String, Option<FullTextBoost>)>,
+    language: Option<&str>,
 ) -> Vec<(Vec<String>, Option<FullTextBoost>)> {
     chunks
         .into_iter()
-        .map(|(chunk, boost)| (tokenize(chunk), boost))
+        .map(|(chunk, boost)| (tokenize(chunk, language), boost))
         .collect()
 }
 
@@ -1028,6 +1242,7 @@ pub fn term_frequency(
     avg_len: f32,
     b: f32,
     k: f32,
+    language: Option<&str>,
 ) -> Vec<Vec<(u32, f32)>> {
     batched_tokens
         .iter()
@@ -1057,7 +1272,7 @@ pub fn term_frequency(
             }
 
             if let Some(fulltext_boost) = fulltext_boost_option {
-                let tokenized_phrase = tokenize(fulltext_boost.phrase.clone());
+                let tokenized_phrase = tokenize(fulltext_boost.phrase.clone(), language);
                 for token in tokenized_phrase {
                     let token_id =
                         (murmur3_32(&mut Cursor::new(token), 0).unwrap() as i32).unsigned_abs();
@@ -1071,3 +1286,21 @@ pub fn term_frequency(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_cjk_tokens_are_bigrams() {
+        let tokenized = tokenize_batch(
+            vec![("東京都 Tokyo 都 abc東京".to_string(), None)],
+            Some("ja"),
+        );
+
+        assert_eq!(
+            tokenized[0].0,
+            vec!["東京", "京都", "tokyo", "都", "abc", "東京"]
+        );
+    }
+}
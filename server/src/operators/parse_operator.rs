@@ -1,3 +1,4 @@
+use lazy_static::lazy_static;
 use ndarray::Array2;
 use regex::Regex;
 use regex_split::RegexSplit;
@@ -12,6 +13,40 @@ pub fn convert_html_to_text(html: &str) -> String {
     text
 }
 
+lazy_static! {
+    static ref MARKDOWN_BLOCK_PREFIX: Regex =
+        Regex::new(r"^\s*(?:(?:>\s?)+|#{1,6}\s+|[-*+]\s+|\d+[.)]\s+)").unwrap();
+    static ref MARKDOWN_RULE: Regex = Regex::new(r"^\s*(?:[-*_]\s*){3,}$").unwrap();
+    static ref MARKDOWN_INLINE: Vec<Regex> = vec![
+        Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap(),
+        Regex::new(r"\[([^\]]+)\]\([^)]*\)").unwrap(),
+        Regex::new(r"`([^`]+)`").unwrap(),
+        Regex::new(r"\*\*(.+?)\*\*").unwrap(),
+        Regex::new(r"__(.+?)__").unwrap(),
+        Regex::new(r"~~(.+?)~~").unwrap(),
+        Regex::new(r"\*([^*\s](?:[^*]*[^*\s])?)\*").unwrap(),
+        Regex::new(r"\b_([^_\s](?:[^_]*[^_\s])?)_\b").unwrap(),
+    ];
+}
+
+/// Removes Markdown syntax from text, keeping the text of emphasis, code, links and images.
+/// Headings, quotes and list markers are dropped, as are code fences and horizontal rules.
+pub fn strip_markdown(markdown: &str) -> String {
+    markdown
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```") && !MARKDOWN_RULE.is_match(line))
+        .map(|line| {
+            let line = MARKDOWN_BLOCK_PREFIX.replace(line, "");
+            MARKDOWN_INLINE
+                .iter()
+                .fold(line.into_owned(), |line, pattern| {
+                    pattern.replace_all(&line, "$1").into_owned()
+                })
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
 pub fn extract_text_from_html(html: &str) -> String {
     let document = Html::parse_document(html);
     let selector = Selector::parse("body").unwrap();
@@ -170,4 +205,14 @@ mod test {
         let result = average_embeddings(embeddings).unwrap();
         assert!(result == vec![2.0, 2.5, 1.0]);
     }
+
+    #[test]
+    pub fn test_strip_markdown() {
+        let markdown = "# Title\n\n> **Bold** and _italic_ with `code`\n- [a link](https://trieve.ai) ![alt](img.png)\n---\n```rust\nlet snake_case = 2 * 3;\n```";
+
+        assert_eq!(
+            strip_markdown(markdown),
+            "Title\n\nBold and italic with code\na link alt\nlet snake_case = 2 * 3;"
+        );
+    }
 }
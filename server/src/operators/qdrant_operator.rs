@@ -14,38 +14,40 @@ use crate::{
 use actix_web::web;
 use futures::future::try_join_all;
 use itertools::Itertools;
+use once_cell::sync::OnceCell;
 use qdrant_client::{
     qdrant::{
-        group_id::Kind, point_id::PointIdOptions, quantization_config::Quantization, query,
-        BinaryQuantization, CreateCollectionBuilder, CreateFieldIndexCollectionBuilder,
-        DeleteFieldIndexCollectionBuilder, DeletePointsBuilder, Distance, FieldType, Filter,
-        GetPointsBuilder, HnswConfigDiff, OrderBy, PointId, PointStruct, PrefetchQuery,
-        QuantizationConfig, Query, QueryBatchPoints, QueryPointGroups, QueryPoints,
-        RecommendPointGroups, RecommendPoints, RecommendStrategy, RetrievedPoint,
+        condition::ConditionOneOf, group_id::Kind, point_id::PointIdOptions,
+        quantization_config::Quantization, query, r#match::MatchValue, vectors::VectorsOptions,
+        vectors_config, BinaryQuantization, Condition, CountPointsBuilder, CreateCollectionBuilder,
+        CreateFieldIndexCollectionBuilder, DeleteFieldIndexCollectionBuilder, DeletePointsBuilder,
+        Distance, FieldType, Filter, GetPointsBuilder, HnswConfigDiff, OrderBy, PointId,
+        PointStruct, PrefetchQuery, QuantizationConfig, Query, QueryBatchPoints, QueryPointGroups,
+        QueryPoints, RecommendPointGroups, RecommendPoints, RecommendStrategy, RetrievedPoint,
         ScrollPointsBuilder, SearchBatchPoints, SearchParams, SearchPointGroups, SearchPoints,
         SetPayloadPointsBuilder, SparseIndexConfig, SparseVectorConfig, SparseVectorParams,
         TextIndexParamsBuilder, TokenizerType, UpsertPointsBuilder, UuidIndexParamsBuilder, Value,
         Vector, VectorInput, VectorParams, VectorParamsMap, VectorsConfig, WithPayloadSelector,
         WithVectorsSelector,
     },
-    Payload, Qdrant,
+    Payload, Qdrant, QdrantError,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-pub async fn get_qdrant_connection(
-    qdrant_url: Option<&str>,
-    qdrant_api_key: Option<&str>,
-) -> Result<Qdrant, ServiceError> {
-    let qdrant_url = qdrant_url.unwrap_or(get_env!(
-        "QDRANT_URL",
-        "QDRANT_URL should be set if this is called"
-    ));
-    let qdrant_api_key = qdrant_api_key.unwrap_or(get_env!(
-        "QDRANT_API_KEY",
-        "QDRANT_API_KEY should be set if this is called"
-    ));
+/// Client for the Qdrant configured by `QDRANT_URL` and `QDRANT_API_KEY`, built on first use.
+static SHARED_QDRANT_CLIENT: OnceCell<Arc<Qdrant>> = OnceCell::new();
 
+fn build_qdrant_client(qdrant_url: &str, qdrant_api_key: &str) -> Result<Qdrant, ServiceError> {
     Qdrant::from_url(qdrant_url)
         .api_key(qdrant_api_key)
         .timeout(std::time::Duration::from_secs(10))
@@ -53,6 +55,46 @@ pub async fn get_qdrant_connection(
         .map_err(|_err| ServiceError::BadRequest("Failed to connect to Qdrant".to_string()))
 }
 
+/// Returns a client for the given Qdrant, defaulting to `QDRANT_URL` and `QDRANT_API_KEY`. The
+/// client for the env configured Qdrant is shared by every caller so its connections are reused
+/// instead of paying a new handshake per operation. It is safe to use concurrently, requests are
+/// multiplexed over its channel.
+pub async fn get_qdrant_connection(
+    qdrant_url: Option<&str>,
+    qdrant_api_key: Option<&str>,
+) -> Result<Arc<Qdrant>, ServiceError> {
+    let env_qdrant_url = get_env!("QDRANT_URL", "QDRANT_URL should be set if this is called");
+    let env_qdrant_api_key = get_env!(
+        "QDRANT_API_KEY",
+        "QDRANT_API_KEY should be set if this is called"
+    );
+    let qdrant_url = qdrant_url.unwrap_or(env_qdrant_url);
+    let qdrant_api_key = qdrant_api_key.unwrap_or(env_qdrant_api_key);
+
+    if qdrant_url != env_qdrant_url || qdrant_api_key != env_qdrant_api_key {
+        return build_qdrant_client(qdrant_url, qdrant_api_key).map(Arc::new);
+    }
+
+    SHARED_QDRANT_CLIENT
+        .get_or_try_init(|| build_qdrant_client(qdrant_url, qdrant_api_key).map(Arc::new))
+        .cloned()
+}
+
+/// Pings the Qdrant configured by `QDRANT_URL` and `QDRANT_API_KEY`.
+pub async fn check_qdrant_health() -> Result<(), ServiceError> {
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    qdrant_client.health_check().await.map_err(|err| {
+        ServiceError::InternalServerError(format!("Qdrant health check failed: {:?}", err))
+    })?;
+
+    Ok(())
+}
+
 pub fn get_qdrant_collection_from_dataset_config(dataset_config: &DatasetConfiguration) -> String {
     match dataset_config.DISTANCE_METRIC {
         DistanceMetric::Euclidean => {
@@ -72,6 +114,85 @@ pub fn get_qdrant_collection_from_dataset_config(dataset_config: &DatasetConfigu
 
 /// Create Qdrant collection and indexes needed
 
+/// Maximum number of times a request throttled by Qdrant is retried.
+const QDRANT_THROTTLE_MAX_RETRIES: usize = 3;
+/// Upper bound on how long a single `Retry-After` from Qdrant is honored for.
+const QDRANT_THROTTLE_MAX_WAIT_SECS: u64 = 30;
+
+/// Total number of throttle retries left for the whole process. Unlimited unless set with
+/// `set_qdrant_retry_budget`.
+static QDRANT_RETRY_BUDGET: OnceCell<AtomicUsize> = OnceCell::new();
+static QDRANT_RETRY_BUDGET_EXHAUSTED: AtomicBool = AtomicBool::new(false);
+
+/// Caps the number of throttle retries across every Qdrant request made by this process, on
+/// top of the per-request `QDRANT_THROTTLE_MAX_RETRIES`. Once spent, throttled requests fail
+/// immediately. Returns false when a budget was already set.
+pub fn set_qdrant_retry_budget(total_retries: usize) -> bool {
+    QDRANT_RETRY_BUDGET
+        .set(AtomicUsize::new(total_retries))
+        .is_ok()
+}
+
+/// Retries left in the budget set with `set_qdrant_retry_budget`, if any.
+pub fn get_remaining_qdrant_retry_budget() -> Option<usize> {
+    QDRANT_RETRY_BUDGET
+        .get()
+        .map(|budget| budget.load(Ordering::Relaxed))
+}
+
+/// Whether a throttled request was failed because the retry budget was spent.
+pub fn qdrant_retry_budget_exhausted() -> bool {
+    QDRANT_RETRY_BUDGET_EXHAUSTED.load(Ordering::Relaxed)
+}
+
+fn take_qdrant_retry_from_budget() -> bool {
+    let Some(budget) = QDRANT_RETRY_BUDGET.get() else {
+        return true;
+    };
+
+    let taken = budget
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+            remaining.checked_sub(1)
+        })
+        .is_ok();
+    if !taken {
+        QDRANT_RETRY_BUDGET_EXHAUSTED.store(true, Ordering::Relaxed);
+    }
+
+    taken
+}
+
+/// Runs a Qdrant request, retrying it when Qdrant throttles with a resource exhausted error.
+/// Each retry waits for the `Retry-After` returned by Qdrant, capped at
+/// `QDRANT_THROTTLE_MAX_WAIT_SECS`, and is taken from the process wide retry budget when one
+/// is set.
+async fn with_qdrant_throttle_retry<T, F, Fut>(mut request: F) -> Result<T, QdrantError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, QdrantError>>,
+{
+    let mut retries = 0;
+
+    loop {
+        match request().await {
+            Err(QdrantError::ResourceExhaustedError {
+                retry_after_seconds,
+                ..
+            }) if retries < QDRANT_THROTTLE_MAX_RETRIES && take_qdrant_retry_from_budget() => {
+                retries += 1;
+                let wait_secs = retry_after_seconds.min(QDRANT_THROTTLE_MAX_WAIT_SECS);
+                log::info!(
+                    "Qdrant is throttling requests, retrying in {}s (attempt {})",
+                    wait_secs,
+                    retries
+                );
+                tokio::time::sleep(Duration::from_secs(wait_secs)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
 pub async fn create_new_qdrant_collection_query(
     qdrant_url: Option<&str>,
     qdrant_api_key: Option<&str>,
@@ -782,6 +903,15 @@ pub struct QdrantSearchQuery {
     pub sort_by: Option<SortByField>,
     pub vector: VectorType,
     pub group_size: Option<u64>,
+    /// Results per page the offset of a page is counted in, when more than a page is retrieved.
+    pub page_size: Option<u64>,
+}
+
+impl QdrantSearchQuery {
+    /// Number of results before `page`, counted in pages of `page_size` or else of `limit`.
+    pub fn page_offset(&self, page: u64) -> u64 {
+        self.page_size.unwrap_or(self.limit) * page.saturating_sub(1)
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -865,7 +995,11 @@ pub async fn search_over_groups_qdrant_query(
 
     let search_batch_future = search_point_req_payloads
         .into_iter()
-        .map(|search_point_req_payload| qdrant_client.query_groups(search_point_req_payload))
+        .map(|search_point_req_payload| {
+            with_qdrant_throttle_retry(|| {
+                qdrant_client.query_groups(search_point_req_payload.clone())
+            })
+        })
         .collect::<Vec<_>>();
 
     let search_batch_futures = try_join_all(search_batch_future);
@@ -1039,7 +1173,7 @@ pub async fn search_qdrant_query(
             let (mut prefetch, (vector_name, qdrant_query)) =
                 get_prefetch_query(query.clone(), dataset_config.clone());
 
-            let offset = query.limit * page.saturating_sub(1);
+            let offset = query.page_offset(page);
             if let Some(prefetch) = prefetch.get_mut(0) {
                 let new_page = if offset / prefetch.limit.unwrap_or(1) > 0 {
                     (offset / prefetch.limit.unwrap_or(1)) + 1
@@ -1106,7 +1240,8 @@ pub async fn search_qdrant_query(
         ..Default::default()
     };
 
-    let search_batch_future = qdrant_client.query_batch(batch_points);
+    let search_batch_future =
+        with_qdrant_throttle_retry(|| qdrant_client.query_batch(batch_points.clone()));
 
     let (count, search_batch_response) =
         futures::future::join(count_future, search_batch_future).await;
@@ -1434,12 +1569,62 @@ pub fn get_collection_name_from_config(config: &DatasetConfiguration) -> String
     format!("{}_vectors", config.EMBEDDING_SIZE)
 }
 
+/// Deletes the points from the collection, splitting the ids into sub-requests of at most
+/// `QDRANT_DELETE_BATCH_SIZE` (1000 by default) points so oversized lists stay within Qdrant's
+/// request size limits. Returns the number of point ids submitted for deletion.
 pub async fn delete_points_from_qdrant(
     point_ids: Vec<uuid::Uuid>,
     qdrant_collection: String,
-) -> Result<(), ServiceError> {
+) -> Result<usize, ServiceError> {
     if point_ids.is_empty() {
-        return Ok(());
+        return Ok(0);
+    }
+
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    let delete_batch_size = std::env::var("QDRANT_DELETE_BATCH_SIZE")
+        .ok()
+        .and_then(|batch_size| batch_size.parse::<usize>().ok())
+        .unwrap_or(1000)
+        .max(1);
+
+    let mut deleted_count = 0;
+
+    for point_ids_batch in point_ids.chunks(delete_batch_size) {
+        let points: Vec<PointId> = point_ids_batch
+            .iter()
+            .map(|x| x.to_string().into())
+            .collect();
+
+        let delete_points = DeletePointsBuilder::new(qdrant_collection.clone())
+            .points(points)
+            .build();
+
+        with_qdrant_throttle_retry(|| qdrant_client.delete_points(delete_points.clone()))
+            .await
+            .map_err(|err| {
+                log::info!("Failed to delete points from qdrant {:?}", err);
+                ServiceError::BadRequest("Failed to delete points from qdrant".to_string())
+            })?;
+
+        deleted_count += point_ids_batch.len();
+    }
+
+    Ok(deleted_count)
+}
+
+/// Returns the ids of the points which exist in the collection, without fetching their payloads
+/// or vectors.
+pub async fn get_existing_qdrant_point_ids(
+    point_ids: Vec<uuid::Uuid>,
+    qdrant_collection: String,
+) -> Result<HashSet<uuid::Uuid>, ServiceError> {
+    if point_ids.is_empty() {
+        return Ok(HashSet::new());
     }
 
     let qdrant_client = get_qdrant_connection(
@@ -1450,19 +1635,368 @@ pub async fn delete_points_from_qdrant(
 
     let points: Vec<PointId> = point_ids.iter().map(|x| x.to_string().into()).collect();
 
-    qdrant_client
-        .delete_points(
-            DeletePointsBuilder::new(qdrant_collection.clone())
-                .points(points)
+    let data = qdrant_client
+        .get_points(
+            GetPointsBuilder::new(qdrant_collection, points)
+                .with_payload(false)
+                .with_vectors(false)
                 .build(),
         )
         .await
         .map_err(|err| {
-            log::info!("Failed to delete points from qdrant {:?}", err);
-            ServiceError::BadRequest("Failed to delete points from qdrant".to_string())
+            log::info!("Failed to fetch points from qdrant {:?}", err);
+            ServiceError::BadRequest("Failed to fetch points from qdrant".to_string())
         })?;
 
-    Ok(())
+    let existing_point_ids = data
+        .result
+        .iter()
+        .filter_map(|point| match point.id.clone()?.point_id_options? {
+            PointIdOptions::Uuid(id) => uuid::Uuid::parse_str(&id).ok(),
+            PointIdOptions::Num(_) => None,
+        })
+        .collect();
+
+    Ok(existing_point_ids)
+}
+
+/// Returns the `dataset_id` payload value of each point found in the collection.
+pub async fn get_qdrant_point_dataset_ids(
+    point_ids: Vec<uuid::Uuid>,
+    qdrant_collection: String,
+) -> Result<HashMap<uuid::Uuid, uuid::Uuid>, ServiceError> {
+    if point_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    let points: Vec<PointId> = point_ids.iter().map(|x| x.to_string().into()).collect();
+
+    let data = qdrant_client
+        .get_points(
+            GetPointsBuilder::new(qdrant_collection, points)
+                .with_payload(true)
+                .with_vectors(false)
+                .build(),
+        )
+        .await
+        .map_err(|err| {
+            log::info!("Failed to fetch points from qdrant {:?}", err);
+            ServiceError::BadRequest("Failed to fetch points from qdrant".to_string())
+        })?;
+
+    let dataset_ids = data
+        .result
+        .iter()
+        .filter_map(|point| {
+            let point_id = match point.id.clone()?.point_id_options? {
+                PointIdOptions::Uuid(id) => uuid::Uuid::parse_str(&id).ok()?,
+                PointIdOptions::Num(_) => {
+                    return None;
+                }
+            };
+
+            let dataset_id = point
+                .payload
+                .get("dataset_id")
+                .and_then(|x| x.as_str())
+                .and_then(|x| uuid::Uuid::parse_str(x).ok())?;
+
+            Some((point_id, dataset_id))
+        })
+        .collect();
+
+    Ok(dataset_ids)
+}
+
+/// Returns the `time_stamp` payload value, in seconds, of each point found in the collection.
+pub async fn get_qdrant_point_time_stamps(
+    point_ids: Vec<uuid::Uuid>,
+    qdrant_collection: String,
+) -> Result<HashMap<uuid::Uuid, Option<i64>>, ServiceError> {
+    if point_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    let points: Vec<PointId> = point_ids.iter().map(|x| x.to_string().into()).collect();
+
+    let data = qdrant_client
+        .get_points(
+            GetPointsBuilder::new(qdrant_collection, points)
+                .with_payload(true)
+                .with_vectors(false)
+                .build(),
+        )
+        .await
+        .map_err(|err| {
+            log::info!("Failed to fetch points from qdrant {:?}", err);
+            ServiceError::BadRequest("Failed to fetch points from qdrant".to_string())
+        })?;
+
+    let time_stamps = data
+        .result
+        .iter()
+        .filter_map(|point| {
+            let point_id = match point.id.clone()?.point_id_options? {
+                PointIdOptions::Uuid(id) => uuid::Uuid::parse_str(&id).ok()?,
+                PointIdOptions::Num(_) => {
+                    return None;
+                }
+            };
+
+            let time_stamp = point.payload.get("time_stamp").and_then(|x| x.as_integer());
+
+            Some((point_id, time_stamp))
+        })
+        .collect();
+
+    Ok(time_stamps)
+}
+
+/// Fetches the dense vectors of the points from the dataset's collection, keyed by point id.
+/// Points which don't exist or lack a dense vector are absent.
+pub async fn get_qdrant_dense_vectors_query(
+    point_ids: Vec<uuid::Uuid>,
+    config: &DatasetConfiguration,
+) -> Result<HashMap<uuid::Uuid, Vec<f32>>, ServiceError> {
+    if point_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    let qdrant_collection = get_qdrant_collection_from_dataset_config(config);
+    let vector_name = format!("{}_vectors", config.EMBEDDING_SIZE);
+    let points: Vec<PointId> = point_ids.iter().map(|x| x.to_string().into()).collect();
+
+    let data = qdrant_client
+        .get_points(
+            GetPointsBuilder::new(qdrant_collection, points)
+                .with_payload(false)
+                .with_vectors(true)
+                .build(),
+        )
+        .await
+        .map_err(|err| {
+            log::info!("Failed to fetch point vectors from qdrant {:?}", err);
+            ServiceError::BadRequest("Failed to fetch point vectors from qdrant".to_string())
+        })?;
+
+    let vectors = data
+        .result
+        .into_iter()
+        .filter_map(|point| {
+            let point_id = match point.id?.point_id_options? {
+                PointIdOptions::Uuid(id) => uuid::Uuid::parse_str(&id).ok()?,
+                PointIdOptions::Num(_) => {
+                    return None;
+                }
+            };
+
+            let vector = match point.vectors?.vectors_options? {
+                VectorsOptions::Vectors(named_vectors) => {
+                    named_vectors.vectors.get(&vector_name)?.data.clone()
+                }
+                VectorsOptions::Vector(vector) => vector.data,
+            };
+
+            Some((point_id, vector))
+        })
+        .collect();
+
+    Ok(vectors)
+}
+
+/// Collection details from Qdrant's collection info API, for callers which select collections by
+/// more than their name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QdrantCollectionInfo {
+    pub name: String,
+    /// Approximate number of points in the collection.
+    pub points_count: Option<u64>,
+    /// Dimensions of each vector of the collection, keyed by vector name. The unnamed default
+    /// vector of single vector collections is keyed by an empty string.
+    pub vector_sizes: HashMap<String, u64>,
+}
+
+/// Lists the collections with their point counts and vector dimensions. This costs a collection
+/// info request per collection, use `get_qdrant_collections` when only the names are needed.
+pub async fn get_qdrant_collections_info() -> Result<Vec<QdrantCollectionInfo>, ServiceError> {
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    let collection_names = get_qdrant_collections().await?;
+
+    let collection_infos = try_join_all(collection_names.into_iter().map(|name| {
+        let qdrant_client = &qdrant_client;
+
+        async move {
+            let collection_info = qdrant_client
+                .collection_info(name.clone())
+                .await
+                .map_err(|err| {
+                    log::info!("Failed to get collection info from qdrant {:?}", err);
+                    ServiceError::BadRequest(
+                        "Failed to get collection info from qdrant".to_string(),
+                    )
+                })?
+                .result;
+
+            let vector_sizes = collection_info
+                .as_ref()
+                .and_then(|info| info.config.as_ref())
+                .and_then(|config| config.params.as_ref())
+                .and_then(|params| params.vectors_config.as_ref())
+                .and_then(|vectors_config| vectors_config.config.as_ref())
+                .map(|config| match config {
+                    vectors_config::Config::Params(params) => {
+                        HashMap::from([(String::new(), params.size)])
+                    }
+                    vectors_config::Config::ParamsMap(params_map) => params_map
+                        .map
+                        .iter()
+                        .map(|(vector_name, params)| (vector_name.clone(), params.size))
+                        .collect(),
+                })
+                .unwrap_or_default();
+
+            Ok::<_, ServiceError>(QdrantCollectionInfo {
+                name,
+                points_count: collection_info.and_then(|info| info.points_count),
+                vector_sizes,
+            })
+        }
+    }))
+    .await?;
+
+    Ok(collection_infos)
+}
+
+/// Renders a filter in the JSON shape of Qdrant's REST API, e.g.
+/// `{"must": [{"key": "tag_set", "match": {"any": ["docs"]}}]}`, for debugging how chunk filters
+/// were translated. Conditions without a JSON rendering here are shown as their debug string
+/// under `"unsupported"`.
+pub fn qdrant_filter_to_json(filter: &Filter) -> serde_json::Value {
+    let conditions_to_json = |conditions: &[Condition]| {
+        conditions
+            .iter()
+            .map(qdrant_condition_to_json)
+            .collect::<Vec<serde_json::Value>>()
+    };
+
+    let mut json = serde_json::Map::new();
+    for (clause, conditions) in [
+        ("must", &filter.must),
+        ("should", &filter.should),
+        ("must_not", &filter.must_not),
+    ] {
+        if !conditions.is_empty() {
+            json.insert(clause.to_string(), conditions_to_json(conditions).into());
+        }
+    }
+    if let Some(min_should) = &filter.min_should {
+        json.insert(
+            "min_should".to_string(),
+            serde_json::json!({
+                "conditions": conditions_to_json(&min_should.conditions),
+                "min_count": min_should.min_count,
+            }),
+        );
+    }
+
+    serde_json::Value::Object(json)
+}
+
+fn qdrant_condition_to_json(condition: &Condition) -> serde_json::Value {
+    let unsupported = || serde_json::json!({ "unsupported": format!("{:?}", condition) });
+
+    match &condition.condition_one_of {
+        Some(ConditionOneOf::Filter(filter)) => qdrant_filter_to_json(filter),
+        Some(ConditionOneOf::HasId(has_id)) => serde_json::json!({
+            "has_id": has_id
+                .has_id
+                .iter()
+                .filter_map(|point_id| match point_id.point_id_options.as_ref()? {
+                    PointIdOptions::Uuid(id) => Some(serde_json::json!(id)),
+                    PointIdOptions::Num(id) => Some(serde_json::json!(id)),
+                })
+                .collect::<Vec<serde_json::Value>>()
+        }),
+        Some(ConditionOneOf::IsEmpty(is_empty)) => {
+            serde_json::json!({ "is_empty": { "key": is_empty.key } })
+        }
+        Some(ConditionOneOf::IsNull(is_null)) => {
+            serde_json::json!({ "is_null": { "key": is_null.key } })
+        }
+        Some(ConditionOneOf::Field(field)) => {
+            let mut json = serde_json::Map::new();
+            json.insert("key".to_string(), field.key.clone().into());
+
+            if let Some(match_value) = field
+                .r#match
+                .as_ref()
+                .and_then(|r#match| r#match.match_value.as_ref())
+            {
+                let match_json = match match_value {
+                    MatchValue::Keyword(keyword) => serde_json::json!({ "value": keyword }),
+                    MatchValue::Integer(integer) => serde_json::json!({ "value": integer }),
+                    MatchValue::Boolean(boolean) => serde_json::json!({ "value": boolean }),
+                    MatchValue::Text(text) => serde_json::json!({ "text": text }),
+                    MatchValue::Keywords(keywords) => {
+                        serde_json::json!({ "any": keywords.strings })
+                    }
+                    MatchValue::Integers(integers) => {
+                        serde_json::json!({ "any": integers.integers })
+                    }
+                    MatchValue::ExceptKeywords(keywords) => {
+                        serde_json::json!({ "except": keywords.strings })
+                    }
+                    MatchValue::ExceptIntegers(integers) => {
+                        serde_json::json!({ "except": integers.integers })
+                    }
+                };
+                json.insert("match".to_string(), match_json);
+            }
+
+            if let Some(range) = &field.range {
+                json.insert(
+                    "range".to_string(),
+                    serde_json::json!({
+                        "gt": range.gt,
+                        "gte": range.gte,
+                        "lt": range.lt,
+                        "lte": range.lte,
+                    }),
+                );
+            }
+
+            if json.len() == 1 {
+                // Geo, datetime and values count conditions
+                return unsupported();
+            }
+
+            serde_json::Value::Object(json)
+        }
+        _ => unsupported(),
+    }
 }
 
 pub async fn get_qdrant_collections() -> Result<Vec<String>, ServiceError> {
@@ -1501,13 +2035,17 @@ pub async fn scroll_qdrant_collection_ids_custom_url(
     if let Some(limit) = limit {
         scroll_points_params = scroll_points_params.limit(limit);
     };
-    let qdrant_point_ids = qdrant_client
-        .scroll(scroll_points_params.with_payload(true).with_vectors(true))
-        .await
-        .map_err(|err| {
-            log::info!("Failed to scroll points from qdrant {:?}", err);
-            ServiceError::BadRequest("Failed to scroll points from qdrant".to_string())
-        })?;
+    let scroll_points = scroll_points_params
+        .with_payload(true)
+        .with_vectors(true)
+        .build();
+    let qdrant_point_ids =
+        with_qdrant_throttle_retry(|| qdrant_client.scroll(scroll_points.clone()))
+            .await
+            .map_err(|err| {
+                log::info!("Failed to scroll points from qdrant {:?}", err);
+                ServiceError::BadRequest("Failed to scroll points from qdrant".to_string())
+            })?;
 
     let points = qdrant_point_ids
         .result
@@ -1554,13 +2092,17 @@ pub async fn scroll_qdrant_collection_ids(
     if let Some(limit) = limit {
         scroll_points_params = scroll_points_params.limit(limit);
     };
-    let qdrant_point_ids = qdrant_client
-        .scroll(scroll_points_params.with_payload(false).with_vectors(false))
-        .await
-        .map_err(|err| {
-            log::info!("Failed to scroll points from qdrant {:?}", err);
-            ServiceError::BadRequest("Failed to scroll points from qdrant".to_string())
-        })?;
+    let scroll_points = scroll_points_params
+        .with_payload(false)
+        .with_vectors(false)
+        .build();
+    let qdrant_point_ids =
+        with_qdrant_throttle_retry(|| qdrant_client.scroll(scroll_points.clone()))
+            .await
+            .map_err(|err| {
+                log::info!("Failed to scroll points from qdrant {:?}", err);
+                ServiceError::BadRequest("Failed to scroll points from qdrant".to_string())
+            })?;
 
     let point_ids = qdrant_point_ids
         .result
@@ -1716,6 +2258,39 @@ pub async fn count_qdrant_group_query(
     Ok(max_count)
 }
 
+/// Qdrant's estimate of how many points of the dataset's collection match the filter. It is read
+/// from the index rather than counted, so it is cheap but may differ from the exact count.
+pub async fn get_approximate_point_count_query(
+    filter: Filter,
+    dataset_config: &DatasetConfiguration,
+) -> Result<u64, ServiceError> {
+    let qdrant_collection = get_qdrant_collection_from_dataset_config(dataset_config);
+
+    let qdrant_client = get_qdrant_connection(
+        Some(get_env!("QDRANT_URL", "QDRANT_URL should be set")),
+        Some(get_env!("QDRANT_API_KEY", "QDRANT_API_KEY should be set")),
+    )
+    .await?;
+
+    let request = CountPointsBuilder::new(qdrant_collection)
+        .filter(filter)
+        .exact(false)
+        .build();
+
+    let count = with_qdrant_throttle_retry(|| qdrant_client.count(request.clone()))
+        .await
+        .map_err(|err| {
+            log::error!("Error fetching approximate point count {:?}", err);
+            ServiceError::BadRequest("Error fetching approximate point count".to_string())
+        })?
+        .result
+        .map(|result| result.count);
+
+    count.ok_or(ServiceError::BadRequest(
+        "Error getting approximate point count".to_string(),
+    ))
+}
+
 pub async fn count_qdrant_query(
     limit: u64,
     queries: Vec<QdrantSearchQuery>,
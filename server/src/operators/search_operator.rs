@@ -7,7 +7,8 @@ use super::group_operator::{
     get_group_ids_from_tracking_ids_query, get_groups_from_group_ids_query,
 };
 use super::model_operator::{
-    cross_encoder, get_bm25_embeddings, get_dense_vector, get_sparse_vector,
+    cross_encoder, get_bm25_embeddings, get_cached_dense_vector, get_dense_vector,
+    get_sparse_vector,
 };
 use super::qdrant_operator::{
     count_qdrant_query, search_over_groups_qdrant_query, GroupSearchResults, QdrantSearchQuery,
@@ -24,7 +25,7 @@ use crate::data::models::{
 use crate::handlers::chunk_handler::{
     AutocompleteReqPayload, ChunkFilter, CountChunkQueryResponseBody, CountChunksReqPayload,
     ParsedQuery, ParsedQueryTypes, ScoringOptions, SearchChunkQueryResponseBody,
-    SearchChunksReqPayload,
+    SearchChunksReqPayload, SemanticBoost,
 };
 use crate::handlers::group_handler::{
     SearchOverGroupsReqPayload, SearchWithinGroupReqPayload, SearchWithinGroupResults,
@@ -357,6 +358,7 @@ impl RetrievePointQuery {
                             sort_by: None,
                             filter: filter.clone(),
                             group_size: None,
+                            page_size: None,
                         })
                     }
                     ReRankOptions::Semantic => {
@@ -386,6 +388,7 @@ impl RetrievePointQuery {
                             sort_by: None,
                             filter: filter.clone(),
                             group_size: None,
+                            page_size: None,
                         })
                     }
                     ReRankOptions::BM25 => {
@@ -416,6 +419,7 @@ impl RetrievePointQuery {
                             sort_by: None,
                             filter: filter.clone(),
                             group_size: None,
+                            page_size: None,
                         })
                     }
                     ReRankOptions::CrossEncoder => None,
@@ -435,6 +439,7 @@ impl RetrievePointQuery {
             sort_by: self.sort_by,
             filter: filter.clone(),
             group_size: self.group_size,
+            page_size: None,
         })
     }
 }
@@ -457,21 +462,24 @@ pub async fn retrieve_qdrant_points_query(
     )
     .await?;
 
-    let limit = qdrant_searches
-        .iter()
-        .map(|query| query.limit)
-        .min()
-        .unwrap_or(10);
-
-    let pages = (count as f64 / limit as f64).ceil() as i64;
-
     Ok(SearchChunkQueryResult {
         search_results: point_ids,
-        total_chunk_pages: pages,
+        total_chunk_pages: get_total_chunk_pages(count, &qdrant_searches),
         batch_lengths,
     })
 }
 
+/// Pages of `count` results, in pages of the smallest `page_size` or else `limit` of the queries.
+fn get_total_chunk_pages(count: u64, qdrant_searches: &[QdrantSearchQuery]) -> i64 {
+    let page_size = qdrant_searches
+        .iter()
+        .map(|query| query.page_size.unwrap_or(query.limit))
+        .min()
+        .unwrap_or(10);
+
+    (count as f64 / page_size as f64).ceil() as i64
+}
+
 pub async fn get_metadata_filter_condition(
     filter: &FieldCondition,
     dataset_id: uuid::Uuid,
@@ -1771,6 +1779,26 @@ pub fn rerank_groups(
     reranked_groups
 }
 
+/// Embeds the dense form of the query, going through the query embedding cache when the query
+/// opted into it and has no semantic boost.
+fn get_query_dense_vector(
+    parsed_query: &ParsedQuery,
+    semantic_boost: Option<SemanticBoost>,
+    config: &DatasetConfiguration,
+) -> impl std::future::Future<Output = Result<Vec<f32>, ServiceError>> {
+    let message = parsed_query.dense_query();
+    let use_cache = parsed_query.cache_dense_embedding && semantic_boost.is_none();
+    let config = config.clone();
+
+    async move {
+        if use_cache {
+            get_cached_dense_vector(message, config).await
+        } else {
+            get_dense_vector(message, semantic_boost, "query", config).await
+        }
+    }
+}
+
 async fn get_qdrant_vector(
     search_type: SearchMethod,
     parsed_query: ParsedQueryTypes,
@@ -1791,8 +1819,7 @@ async fn get_qdrant_vector(
 
             let embedding_vector = match parsed_query {
                 ParsedQueryTypes::Single(query) => {
-                    get_dense_vector(query.query.clone(), semantic_boost, "query", config.clone())
-                        .await?
+                    get_query_dense_vector(&query, semantic_boost, config).await?
                 }
                 ParsedQueryTypes::Multi(queries) => {
                     let mut embedding_futures = Vec::new();
@@ -1800,7 +1827,7 @@ async fn get_qdrant_vector(
 
                     for (query, _) in &queries {
                         embedding_futures.push(get_dense_vector(
-                            query.query.clone(),
+                            query.dense_query(),
                             None,
                             "query",
                             config.clone(),
@@ -1851,10 +1878,11 @@ async fn get_qdrant_vector(
 
             let sparse_vectors = match parsed_query {
                 ParsedQueryTypes::Single(query) => get_bm25_embeddings(
-                    vec![(query.query.clone(), fulltext_boost)],
+                    vec![(query.sparse_query(), fulltext_boost)],
                     config.BM25_AVG_LEN,
                     config.BM25_B,
                     config.BM25_K,
+                    config.BM25_LANGUAGE.as_deref(),
                 ),
                 ParsedQueryTypes::Multi(_) => {
                     return Err(ServiceError::BadRequest(
@@ -1880,7 +1908,7 @@ async fn get_qdrant_vector(
 
             let sparse_vector = match parsed_query {
                 ParsedQueryTypes::Single(query) => {
-                    get_sparse_vector(query.query.clone(), fulltext_boost, "query").await?
+                    get_sparse_vector(query.sparse_query(), fulltext_boost, "query").await?
                 }
                 ParsedQueryTypes::Multi(_) => {
                     return Err(ServiceError::BadRequest(
@@ -1897,6 +1925,78 @@ async fn get_qdrant_vector(
     }
 }
 
+/// Scores already retrieved points against each of the query's dense, SPLADE and BM25 vectors
+/// individually. The same boosted query vectors used for retrieval are used here, so the
+/// returned scores are the components behind a result's ranking.
+pub async fn get_component_scores_for_points(
+    parsed_query: ParsedQuery,
+    point_ids: Vec<uuid::Uuid>,
+    scoring_options: Option<ScoringOptions>,
+    config: &DatasetConfiguration,
+) -> Result<HashMap<SearchMethod, HashMap<uuid::Uuid, f32>>, ServiceError> {
+    if point_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let bm25_active = std::env::var("BM25_ACTIVE").unwrap_or("false".to_string()) == "true";
+
+    let search_methods = [
+        (SearchMethod::Semantic, config.SEMANTIC_ENABLED),
+        (SearchMethod::FullText, config.FULLTEXT_ENABLED),
+        (SearchMethod::BM25, config.BM25_ENABLED && bm25_active),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(search_method, _)| search_method)
+    .collect_vec();
+
+    let component_futures = search_methods.into_iter().map(|search_method| {
+        let parsed_query = parsed_query.clone();
+        let point_ids = point_ids.clone();
+        let scoring_options = scoring_options.clone();
+
+        async move {
+            let vector = get_qdrant_vector(
+                search_method.clone(),
+                ParsedQueryTypes::Single(parsed_query),
+                scoring_options,
+                config,
+            )
+            .await?;
+
+            let qdrant_query = QdrantSearchQuery {
+                filter: Filter::must([Condition::has_id(
+                    point_ids.iter().map(|point_id| point_id.to_string()),
+                )]),
+                limit: point_ids.len() as u64,
+                score_threshold: None,
+                rerank_by: Box::new(None),
+                sort_by: None,
+                vector,
+                group_size: None,
+                page_size: None,
+            };
+
+            let (search_results, _, _) =
+                search_qdrant_query(1, vec![qdrant_query], config.clone(), false).await?;
+
+            let scores = search_results
+                .into_iter()
+                .map(|result| (result.point_id, result.score))
+                .collect::<HashMap<uuid::Uuid, f32>>();
+
+            Ok::<_, ServiceError>((search_method, scores))
+        }
+    });
+
+    let component_scores = futures::future::try_join_all(component_futures)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(component_scores)
+}
+
 pub async fn search_chunks_query(
     mut data: SearchChunksReqPayload,
     parsed_query: ParsedQueryTypes,
@@ -2026,10 +2126,113 @@ pub async fn search_chunks_query(
     Ok(result_chunks)
 }
 
+/// Runs the dense and sparse queries of a hybrid search separately and fuses their results by
+/// the weighted sum of each result's score, normalized by the top score of its query.
+async fn retrieve_weighted_hybrid_points_query(
+    qdrant_queries: Vec<QdrantSearchQuery>,
+    (dense_weight, sparse_weight): (f32, f32),
+    page: u64,
+    get_total_pages: bool,
+    config: &DatasetConfiguration,
+) -> Result<SearchChunkQueryResult, ServiceError> {
+    let limit = qdrant_queries
+        .iter()
+        .map(|query| query.limit)
+        .min()
+        .unwrap_or(10);
+
+    let weighted_results = futures::future::try_join_all(
+        qdrant_queries
+            .into_iter()
+            .zip([dense_weight, sparse_weight])
+            .map(|(qdrant_query, weight)| async move {
+                let results =
+                    retrieve_qdrant_points_query(vec![qdrant_query], page, get_total_pages, config)
+                        .await?;
+
+                Ok::<_, ServiceError>((results, weight))
+            }),
+    )
+    .await?;
+
+    let mut total_chunk_pages = 0;
+    let mut fused_results: HashMap<uuid::Uuid, SearchResult> = HashMap::new();
+
+    for (results, weight) in weighted_results {
+        total_chunk_pages = total_chunk_pages.max(results.total_chunk_pages);
+
+        let max_score = results
+            .search_results
+            .iter()
+            .map(|result| result.score)
+            .fold(0.0_f32, f32::max);
+
+        for result in results.search_results {
+            let weighted_score = if max_score > 0.0 {
+                weight * result.score / max_score
+            } else {
+                0.0
+            };
+
+            fused_results
+                .entry(result.point_id)
+                .and_modify(|fused_result| fused_result.score += weighted_score)
+                .or_insert(SearchResult {
+                    score: weighted_score,
+                    ..result
+                });
+        }
+    }
+
+    let search_results = fused_results
+        .into_values()
+        .sorted_by(|a, b| b.score.total_cmp(&a.score))
+        .take(limit as usize)
+        .collect_vec();
+
+    Ok(SearchChunkQueryResult {
+        batch_lengths: vec![search_results.len()],
+        search_results,
+        total_chunk_pages,
+    })
+}
+
+/// Most fused candidates the cross encoder reranks for hybrid search's `rerank_candidates`, from
+/// `MAX_RERANK_CANDIDATES` (100 by default). The reranker scores candidates in parallel batches of
+/// 20, so each batch past the first few adds to the latency of every hybrid search.
+pub fn get_max_rerank_candidates() -> u64 {
+    std::env::var("MAX_RERANK_CANDIDATES")
+        .ok()
+        .and_then(|max_candidates| max_candidates.parse::<u64>().ok())
+        .unwrap_or(100)
+}
+
+/// Number of results each of the dense and sparse searches of a hybrid search retrieves for a
+/// page. Both retrieve the page by default, so the reranker sees up to twice the page size. The
+/// requested `rerank_candidates`, capped at `get_max_rerank_candidates`, are split between the two
+/// searches, each still retrieving at least the page so results found by both can't leave it
+/// short. Weighted fusion doesn't rerank, so it retrieves the page.
+fn get_hybrid_retrieve_limit(
+    page_size: u64,
+    scoring_options: Option<&ScoringOptions>,
+    weighted: bool,
+) -> u64 {
+    match scoring_options
+        .and_then(|scoring_options| scoring_options.rerank_candidates)
+        .filter(|_| !weighted)
+    {
+        Some(rerank_candidates) => (rerank_candidates as u64)
+            .min(get_max_rerank_candidates())
+            .div_ceil(2)
+            .max(page_size),
+        None => page_size,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 
 pub async fn search_hybrid_chunks(
-    mut data: SearchChunksReqPayload,
+    data: SearchChunksReqPayload,
     parsed_query: ParsedQuery,
     pool: web::Data<Pool>,
     redis_pool: web::Data<RedisPool>,
@@ -2037,6 +2240,35 @@ pub async fn search_hybrid_chunks(
     config: &DatasetConfiguration,
     timer: &mut Timer,
 ) -> Result<SearchChunkQueryResponseBody, actix_web::Error> {
+    search_hybrid_chunks_before_rerank_deadline(
+        data,
+        parsed_query,
+        pool,
+        redis_pool,
+        dataset,
+        config,
+        timer,
+        None,
+    )
+    .await
+    .map(|(result_chunks, _)| result_chunks)
+}
+
+/// Hybrid search which stops reranking once `rerank_deadline` passes. The retrieved results are
+/// then returned in retrieval order, without the score_threshold which applies to reranker
+/// scores, and the returned flag is true. Reaching the deadline with no retrieved results is a
+/// `RequestTimeout` error.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_hybrid_chunks_before_rerank_deadline(
+    mut data: SearchChunksReqPayload,
+    parsed_query: ParsedQuery,
+    pool: web::Data<Pool>,
+    redis_pool: web::Data<RedisPool>,
+    dataset: Dataset,
+    config: &DatasetConfiguration,
+    timer: &mut Timer,
+    rerank_deadline: Option<tokio::time::Instant>,
+) -> Result<(SearchChunkQueryResponseBody, bool), actix_web::Error> {
     let mut parsed_query = parsed_query.clone();
     let mut corrected_query = None;
 
@@ -2068,15 +2300,11 @@ pub async fn search_hybrid_chunks(
         .map(|options| options.fulltext_boost)
         .unwrap_or(None);
 
-    let dense_query_vector_future = get_dense_vector(
-        parsed_query.query.clone(),
-        semantic_boost,
-        "query",
-        dataset_config.clone(),
-    );
+    let dense_query_vector_future =
+        get_query_dense_vector(&parsed_query, semantic_boost, &dataset_config);
 
     let sparse_query_vector_future =
-        get_sparse_vector(parsed_query.query.clone(), fulltext_boost, "query");
+        get_sparse_vector(parsed_query.sparse_query(), fulltext_boost, "query");
 
     let (dense_vector, sparse_vector) =
         futures::try_join!(dense_query_vector_future, sparse_query_vector_future)?;
@@ -2091,13 +2319,25 @@ pub async fn search_hybrid_chunks(
         _ => (None, None),
     };
 
-    let qdrant_queries = vec![
+    let hybrid_weights = match &data.scoring_options {
+        Some(scoring_options) => scoring_options.hybrid_weights()?,
+        None => None,
+    };
+
+    let page_size = data.page_size.unwrap_or(10);
+    let retrieve_limit = get_hybrid_retrieve_limit(
+        page_size,
+        data.scoring_options.as_ref(),
+        hybrid_weights.is_some(),
+    );
+
+    let mut qdrant_queries = vec![
         RetrievePointQuery {
             vector: VectorType::Dense(dense_vector),
             score_threshold: None,
             sort_by: sort_by.clone(),
             rerank_by: rerank_by.clone(),
-            limit: data.page_size.unwrap_or(10),
+            limit: retrieve_limit,
             filter: data.filters.clone(),
             group_size: None,
         }
@@ -2114,7 +2354,7 @@ pub async fn search_hybrid_chunks(
             score_threshold: None,
             sort_by: sort_by.clone(),
             rerank_by: rerank_by.clone(),
-            limit: data.page_size.unwrap_or(10),
+            limit: retrieve_limit,
             filter: data.filters.clone(),
             group_size: None,
         }
@@ -2128,13 +2368,32 @@ pub async fn search_hybrid_chunks(
         .await?,
     ];
 
-    let search_chunk_query_results = retrieve_qdrant_points_query(
-        qdrant_queries,
-        data.page.unwrap_or(1),
-        data.get_total_pages.unwrap_or(false),
-        config,
-    )
-    .await?;
+    // Pages start after the results of the earlier pages, not after their candidates
+    for qdrant_query in qdrant_queries.iter_mut() {
+        qdrant_query.page_size = Some(page_size);
+    }
+
+    let search_chunk_query_results = match hybrid_weights {
+        Some((dense_weight, sparse_weight)) => {
+            retrieve_weighted_hybrid_points_query(
+                qdrant_queries,
+                (dense_weight, sparse_weight),
+                data.page.unwrap_or(1),
+                data.get_total_pages.unwrap_or(false),
+                config,
+            )
+            .await?
+        }
+        None => {
+            retrieve_qdrant_points_query(
+                qdrant_queries,
+                data.page.unwrap_or(1),
+                data.get_total_pages.unwrap_or(false),
+                config,
+            )
+            .await?
+        }
+    };
 
     let result_chunks = retrieve_chunks_from_point_ids(
         search_chunk_query_results,
@@ -2147,21 +2406,41 @@ pub async fn search_hybrid_chunks(
 
     timer.add("fetched metadata from postgres");
 
+    let mut partial = false;
     let mut reranked_chunks = {
         let mut reranked_chunks = {
-            let mut cross_encoder_results = cross_encoder(
-                data.query.clone().to_single_query()?,
-                data.page_size.unwrap_or(10),
-                result_chunks.score_chunks,
-                config,
-            )
-            .await?;
+            let mut scored_chunks = if hybrid_weights.is_some() {
+                result_chunks.score_chunks
+            } else {
+                let retrieved_chunks = rerank_deadline.map(|_| result_chunks.score_chunks.clone());
+                let cross_encoder_results = cross_encoder(
+                    data.query.clone().to_single_query()?,
+                    data.page_size.unwrap_or(10),
+                    result_chunks.score_chunks,
+                    config,
+                );
+
+                match (rerank_deadline, retrieved_chunks) {
+                    (Some(rerank_deadline), Some(retrieved_chunks)) => {
+                        match tokio::time::timeout_at(rerank_deadline, cross_encoder_results).await
+                        {
+                            Ok(cross_encoder_results) => cross_encoder_results?,
+                            Err(_) if !retrieved_chunks.is_empty() => {
+                                partial = true;
+                                retrieved_chunks
+                            }
+                            Err(_) => return Err(ServiceError::RequestTimeout.into()),
+                        }
+                    }
+                    _ => cross_encoder_results.await?,
+                }
+            };
 
-            if let Some(score_threshold) = data.score_threshold {
-                cross_encoder_results.retain(|chunk| chunk.score >= score_threshold.into());
+            if let Some(score_threshold) = data.score_threshold.filter(|_| !partial) {
+                scored_chunks.retain(|chunk| chunk.score >= score_threshold.into());
             }
 
-            rerank_chunks(cross_encoder_results, data.sort_options)
+            rerank_chunks(scored_chunks, data.sort_options)
         };
 
         reranked_chunks.truncate(data.page_size.unwrap_or(10) as usize);
@@ -2199,7 +2478,7 @@ pub async fn search_hybrid_chunks(
             .collect();
     }
 
-    Ok(reranked_chunks)
+    Ok((reranked_chunks, partial))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -2355,14 +2634,9 @@ pub async fn search_hybrid_groups(
         timer.add("corrected query");
     }
 
-    let dense_vector_future = get_dense_vector(
-        parsed_query.query.clone(),
-        None,
-        "query",
-        dataset_config.clone(),
-    );
+    let dense_vector_future = get_query_dense_vector(&parsed_query, None, &dataset_config);
 
-    let sparse_vector_future = get_sparse_vector(parsed_query.query.clone(), None, "query");
+    let sparse_vector_future = get_sparse_vector(parsed_query.sparse_query(), None, "query");
 
     let (dense_vector, sparse_vector) =
         futures::try_join!(dense_vector_future, sparse_vector_future)?;
@@ -2990,3 +3264,50 @@ pub async fn count_chunks_query(
 
     Ok(CountChunkQueryResponseBody { count })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_hybrid_pages_are_offset_by_page_size() {
+        let scoring_options = serde_json::from_value::<ScoringOptions>(
+            serde_json::json!({ "rerank_candidates": 10 }),
+        )
+        .unwrap();
+        let retrieve_limit = get_hybrid_retrieve_limit(10, Some(&scoring_options), false);
+        assert_eq!(retrieve_limit, 10);
+        assert_eq!(
+            get_hybrid_retrieve_limit(10, Some(&scoring_options), true),
+            10
+        );
+
+        let scoring_options = serde_json::from_value::<ScoringOptions>(
+            serde_json::json!({ "rerank_candidates": 50 }),
+        )
+        .unwrap();
+        let retrieve_limit = get_hybrid_retrieve_limit(10, Some(&scoring_options), false);
+        assert_eq!(retrieve_limit, 25);
+
+        let qdrant_query = QdrantSearchQuery {
+            filter: Filter::default(),
+            limit: retrieve_limit,
+            score_threshold: None,
+            rerank_by: Box::new(None),
+            sort_by: None,
+            vector: VectorType::Dense(vec![]),
+            group_size: None,
+            page_size: Some(10),
+        };
+        assert_eq!(qdrant_query.page_offset(1), 0);
+        assert_eq!(qdrant_query.page_offset(3), 20);
+        assert_eq!(get_total_chunk_pages(95, &[qdrant_query.clone()]), 10);
+
+        let unpaged_query = QdrantSearchQuery {
+            page_size: None,
+            ..qdrant_query
+        };
+        assert_eq!(unpaged_query.page_offset(3), 50);
+        assert_eq!(get_total_chunk_pages(95, &[unpaged_query]), 4);
+    }
+}
@@ -588,6 +588,7 @@ fn correct_query_helper(
     let query_words: Vec<&str> = query.query.split_whitespace().collect();
 
     let mut corrections = HashMap::new();
+    let mut correction_confidences = Vec::new();
     let mut new_quote_words = Vec::new();
 
     let excluded_words: HashSet<_> = options
@@ -608,6 +609,9 @@ fn correct_query_helper(
         .clone()
         .unwrap_or(TypoRange { min: 6, max: None });
 
+    let max_edit_distance = options.max_edit_distance.unwrap_or(2).min(2) as isize;
+    let prefix_length = options.prefix_length.unwrap_or(0) as usize;
+
     for &word in &query_words {
         if corrections.contains_key(word) {
             continue;
@@ -650,10 +654,12 @@ fn correct_query_helper(
             1
         } else {
             0
-        };
+        }
+        .min(max_edit_distance);
 
         if max_distance > 0 {
             let mut best_correction = None;
+            let mut best_distance = 0;
             let mut best_score = 0;
 
             for ((correction, freq), distance) in tree.find(word.to_lowercase(), max_distance) {
@@ -664,25 +670,39 @@ fn correct_query_helper(
                 if !is_best_correction(word.to_lowercase(), correction.to_string()) {
                     continue;
                 }
+                if !word
+                    .to_lowercase()
+                    .chars()
+                    .take(prefix_length)
+                    .eq(correction.chars().take(prefix_length))
+                {
+                    continue;
+                }
 
                 let score = (max_distance - distance) * 1000 + *freq as isize;
 
                 if score > best_score || best_correction.is_none() {
                     best_correction = Some(correction);
+                    best_distance = distance;
                     best_score = score;
                 }
             }
 
             if let Some(correction) = best_correction {
                 corrections.insert(word, correction.to_string());
+                correction_confidences.push(1.0 - best_distance as f32 / (max_distance + 1) as f32);
             }
         }
     }
 
+    let confidence = (!correction_confidences.is_empty())
+        .then(|| correction_confidences.iter().sum::<f32>() / correction_confidences.len() as f32);
+
     if corrections.is_empty() && new_quote_words.is_empty() {
         CorrectedQuery {
             query: Some(query),
             corrected: false,
+            confidence,
         }
     } else {
         let mut corrected_query = query.query.clone();
@@ -699,6 +719,7 @@ fn correct_query_helper(
         CorrectedQuery {
             query: Some(query),
             corrected: true,
+            confidence,
         }
     }
 }
@@ -765,6 +786,9 @@ fn is_likely_english_word(word: &str) -> bool {
 pub struct CorrectedQuery {
     pub query: Option<ParsedQuery>,
     pub corrected: bool,
+    /// Mean confidence, from 0 to 1, of the spelling corrections made to the query. None when no
+    /// word was corrected.
+    pub confidence: Option<f32>,
 }
 
 pub async fn correct_query(